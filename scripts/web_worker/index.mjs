@@ -178,6 +178,8 @@ const PROVIDER_AUTOMATION_CONFIG = {
 const state = {
   providers: new Map(),
   activeRun: null,
+  // provider/run request id -> provider, so `$/cancelRequest` can find the run.
+  runRequests: new Map(),
   lastError: null,
   bridge: {
     server: null,
@@ -1737,6 +1739,7 @@ async function handleRpcRequest(message) {
       return;
     }
 
+    state.runRequests.set(id, provider);
     try {
       const safeTimeoutMs = Number.isFinite(timeoutMs) && timeoutMs > 0 ? timeoutMs : DEFAULT_TIMEOUT_MS;
       let result;
@@ -1779,6 +1782,8 @@ async function handleRpcRequest(message) {
           failedAt: nowIso(),
         },
       });
+    } finally {
+      state.runRequests.delete(id);
     }
     return;
   }
@@ -1845,6 +1850,13 @@ async function handleLine(rawLine) {
 
   const id = parsed.id;
   const method = parsed.method;
+  if (method === '$/cancelRequest' && typeof id === 'undefined') {
+    const provider = state.runRequests.get(parsed.params?.id);
+    if (provider) {
+      await cancelProviderRun(provider);
+    }
+    return;
+  }
   if (typeof id === 'undefined' || typeof method !== 'string') {
     respondError(id ?? null, -32600, 'Invalid request');
     return;
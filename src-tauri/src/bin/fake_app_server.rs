@@ -0,0 +1,201 @@
+// Scriptable stand-in for `codex app-server` speaking JSONL JSON-RPC on stdio.
+// Point RAIL_CODEX_BIN at this binary and RAIL_FAKE_APP_SERVER_SCRIPT at a JSON
+// file such as:
+//
+//   {
+//     "onStart": [{ "notify": { "method": "fake/booted" } }],
+//     "methods": {
+//       "turn/start": [
+//         { "request": { "method": "item/commandExecution/requestApproval", "params": {} } },
+//         { "delayMs": 50 },
+//         { "respond": { "turn": { "id": "t1" } } }
+//       ],
+//       "slow": [{ "delayMs": 5000 }],
+//       "boom": [{ "error": { "code": -32000, "message": "boom" } }],
+//       "crash": [{ "exit": 3 }]
+//     }
+//   }
+//
+// Requests without a "respond", "error" or "noResponse" action are answered
+// with an echo of their method and params once the cue has run. Responses to
+// the server's own requests are reported back as "fake/response" notifications.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, BufRead, Write},
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Action {
+    Respond(Value),
+    Error {
+        code: i64,
+        message: String,
+        #[serde(default)]
+        data: Option<Value>,
+    },
+    NoResponse,
+    Notify {
+        method: String,
+        #[serde(default)]
+        params: Value,
+    },
+    Request {
+        method: String,
+        #[serde(default)]
+        params: Value,
+    },
+    DelayMs(u64),
+    Stderr(String),
+    Raw(String),
+    Exit(i32),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Script {
+    on_start: Vec<Action>,
+    methods: HashMap<String, Vec<Action>>,
+}
+
+struct Server {
+    script: Script,
+    next_request_id: u64,
+}
+
+fn load_script() -> Result<Script, String> {
+    let Ok(path) = env::var("RAIL_FAKE_APP_SERVER_SCRIPT") else {
+        return Ok(Script::default());
+    };
+    let raw = fs::read_to_string(&path).map_err(|e| format!("failed to read {path}: {e}"))?;
+    serde_json::from_str(&raw).map_err(|e| format!("failed to parse {path}: {e}"))
+}
+
+fn write_line(line: &str) {
+    let mut stdout = io::stdout().lock();
+    let _ = writeln!(stdout, "{line}");
+    let _ = stdout.flush();
+}
+
+fn write_message(message: &Value) {
+    write_line(&message.to_string());
+}
+
+fn default_result(method: &str, params: &Value) -> Value {
+    match method {
+        "initialize" => json!({ "userAgent": "rail-fake-app-server" }),
+        _ => json!({ "method": method, "params": params }),
+    }
+}
+
+impl Server {
+    // Returns true once the request has been answered (or deliberately not).
+    fn run(&mut self, actions: &[Action], request_id: Option<&Value>) -> bool {
+        let mut answered = false;
+        for action in actions {
+            match action {
+                Action::Respond(result) => {
+                    if let Some(id) = request_id {
+                        write_message(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+                    }
+                    answered = true;
+                }
+                Action::Error {
+                    code,
+                    message,
+                    data,
+                } => {
+                    if let Some(id) = request_id {
+                        let mut error = json!({ "code": code, "message": message });
+                        if let Some(data) = data {
+                            error["data"] = data.clone();
+                        }
+                        write_message(&json!({ "jsonrpc": "2.0", "id": id, "error": error }));
+                    }
+                    answered = true;
+                }
+                Action::NoResponse => answered = true,
+                Action::Notify { method, params } => {
+                    write_message(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+                }
+                Action::Request { method, params } => {
+                    let id = self.next_request_id;
+                    self.next_request_id += 1;
+                    write_message(&json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "method": method,
+                        "params": params
+                    }));
+                }
+                Action::DelayMs(ms) => thread::sleep(Duration::from_millis(*ms)),
+                Action::Stderr(line) => eprintln!("{line}"),
+                Action::Raw(line) => write_line(line),
+                Action::Exit(code) => std::process::exit(*code),
+            }
+        }
+        answered
+    }
+
+    fn handle(&mut self, message: Value) {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // A response to one of our requests.
+            write_message(&json!({
+                "jsonrpc": "2.0",
+                "method": "fake/response",
+                "params": {
+                    "id": id,
+                    "result": message.get("result"),
+                    "error": message.get("error")
+                }
+            }));
+            return;
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let actions = self.script.methods.get(method).cloned().unwrap_or_default();
+        let answered = self.run(&actions, id.as_ref());
+        if let (Some(id), false) = (id, answered) {
+            write_message(&json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": default_result(method, &params)
+            }));
+        }
+    }
+}
+
+fn main() {
+    let script = match load_script() {
+        Ok(script) => script,
+        Err(err) => {
+            eprintln!("fake app-server: {err}");
+            std::process::exit(2);
+        }
+    };
+    let on_start = script.on_start.clone();
+    let mut server = Server {
+        script,
+        next_request_id: 0,
+    };
+    server.run(&on_start, None);
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle(message),
+            Err(err) => eprintln!("fake app-server: invalid json: {err}"),
+        }
+    }
+}
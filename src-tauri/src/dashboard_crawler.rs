@@ -326,7 +326,8 @@ pub fn dashboard_snapshot_save(
     let file_path = snapshot_dir.join(format!("{stamp}_{date}_{event_label}.json"));
     let body = serde_json::to_string_pretty(&snapshot_json)
         .map_err(|err| format!("failed to serialize snapshot: {err}"))?;
-    fs::write(&file_path, body).map_err(|err| format!("failed to save snapshot: {err}"))?;
+    crate::storage::write_atomic(&file_path, body.as_bytes())
+        .map_err(|err| format!("failed to save snapshot: {err}"))?;
     Ok(file_path.to_string_lossy().to_string())
 }
 
@@ -479,7 +480,14 @@ pub fn dashboard_snapshot_delete(
 
     let mut deleted = 0usize;
     for target in targets {
-        if fs::remove_file(&target).is_ok() {
+        if crate::storage::move_to_workspace_trash(
+            &workspace,
+            "dashboardSnapshots",
+            &target,
+            "deleted",
+        )
+        .is_ok()
+        {
             deleted += 1;
         }
     }
@@ -1278,6 +1286,18 @@ fn normalize_workspace_cwd(cwd: &str) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+pub(crate) fn dashboard_raw_topic_dirs(cwd: &str) -> Result<Vec<PathBuf>, String> {
+    let workspace = normalize_workspace_cwd(cwd)?;
+    let root = workspace.join(".rail/dashboard/raw");
+    Ok(TOPIC_IDS.iter().map(|topic| root.join(topic)).collect())
+}
+
+pub(crate) fn dashboard_snapshot_topic_dirs(cwd: &str) -> Result<Vec<PathBuf>, String> {
+    let workspace = normalize_workspace_cwd(cwd)?;
+    let root = workspace.join(".rail/dashboard/snapshots");
+    Ok(TOPIC_IDS.iter().map(|topic| root.join(topic)).collect())
+}
+
 fn normalize_topics(topics: Option<Vec<String>>) -> Vec<&'static str> {
     let Some(items) = topics else {
         return TOPIC_IDS.to_vec();
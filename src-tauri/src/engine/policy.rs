@@ -0,0 +1,321 @@
+use super::routing;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+pub const POLICY_SETTINGS_NAME: &str = "engine_policy.json";
+
+// Ordered from most to least restrictive so limits compare with `>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxMode {
+    ReadOnly,
+    WorkspaceWrite,
+    #[serde(alias = "full-access")]
+    DangerFullAccess,
+}
+
+// Ordered from "ask for everything" to "never ask".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalPolicy {
+    Untrusted,
+    OnFailure,
+    OnRequest,
+    Never,
+}
+
+impl SandboxMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read-only",
+            Self::WorkspaceWrite => "workspace-write",
+            Self::DangerFullAccess => "danger-full-access",
+        }
+    }
+}
+
+impl ApprovalPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Untrusted => "untrusted",
+            Self::OnFailure => "on-failure",
+            Self::OnRequest => "on-request",
+            Self::Never => "never",
+        }
+    }
+}
+
+impl fmt::Display for SandboxMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for ApprovalPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxPolicy {
+    pub mode: SandboxMode,
+    #[serde(default)]
+    pub writable_roots: Vec<String>,
+    #[serde(default)]
+    pub network_access: bool,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self {
+            mode: SandboxMode::ReadOnly,
+            writable_roots: Vec::new(),
+            network_access: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspacePolicyLimit {
+    pub max_sandbox: SandboxMode,
+    pub max_approval: ApprovalPolicy,
+    pub allow_network: bool,
+    // Roots outside the workspace that workspace-write may also open up.
+    pub extra_writable_roots: Vec<String>,
+}
+
+impl Default for WorkspacePolicyLimit {
+    fn default() -> Self {
+        Self {
+            max_sandbox: SandboxMode::ReadOnly,
+            max_approval: ApprovalPolicy::OnRequest,
+            allow_network: false,
+            extra_writable_roots: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnginePolicyConfig {
+    pub default_limit: WorkspacePolicyLimit,
+    // Keyed by workspace path; the deepest workspace containing the cwd wins.
+    pub workspaces: BTreeMap<String, WorkspacePolicyLimit>,
+}
+
+impl EnginePolicyConfig {
+    pub fn limit_for(&self, cwd: &str) -> &WorkspacePolicyLimit {
+        routing::match_runtime_for_cwd(
+            self.workspaces
+                .keys()
+                .map(|workspace| (workspace.as_str(), workspace.as_str())),
+            cwd,
+        )
+        .and_then(|workspace| self.workspaces.get(workspace))
+        .unwrap_or(&self.default_limit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectivePolicy {
+    pub cwd: String,
+    pub sandbox: SandboxPolicy,
+    pub approval: ApprovalPolicy,
+}
+
+impl EffectivePolicy {
+    pub fn sandbox_policy_value(&self) -> Value {
+        match self.sandbox.mode {
+            SandboxMode::ReadOnly => json!({ "type": "readOnly" }),
+            SandboxMode::WorkspaceWrite => json!({
+                "type": "workspaceWrite",
+                "writableRoots": self.sandbox.writable_roots,
+                "networkAccess": self.sandbox.network_access
+            }),
+            SandboxMode::DangerFullAccess => json!({ "type": "dangerFullAccess" }),
+        }
+    }
+}
+
+fn lexical_root(base: &Path, raw: &str) -> Result<PathBuf, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err("writable root must not be empty".to_string());
+    }
+    let joined = base.join(trimmed);
+    let mut out = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return Err(format!("invalid writable root: {trimmed}"));
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    // Roots that do not exist yet still resolve through their existing
+    // ancestors, so a symlink on the way cannot lead out of the workspace.
+    crate::storage::canonicalize_existing_prefix(&out)
+        .map_err(|e| format!("failed to resolve writable root {trimmed}: {e}"))
+}
+
+// Checks a requested sandbox/approval pair against the workspace limit and
+// resolves writable roots to absolute paths inside the allowed set. Without a
+// requested approval policy the app-server's own default could be looser than
+// the limit, so one is always chosen: on-request, or the limit if stricter.
+pub fn resolve_policy(
+    limit: &WorkspacePolicyLimit,
+    cwd: &str,
+    sandbox: Option<SandboxPolicy>,
+    approval: Option<ApprovalPolicy>,
+) -> Result<EffectivePolicy, String> {
+    let mut sandbox = sandbox.unwrap_or_default();
+    if sandbox.mode > limit.max_sandbox {
+        return Err(format!(
+            "sandbox {} exceeds the workspace maximum {}",
+            sandbox.mode, limit.max_sandbox
+        ));
+    }
+    let approval = approval.unwrap_or(limit.max_approval.min(ApprovalPolicy::OnRequest));
+    if approval > limit.max_approval {
+        return Err(format!(
+            "approval policy {approval} exceeds the workspace maximum {}",
+            limit.max_approval
+        ));
+    }
+    if sandbox.network_access && !limit.allow_network {
+        return Err("network access is not allowed in this workspace".to_string());
+    }
+    if sandbox.mode != SandboxMode::WorkspaceWrite && !sandbox.writable_roots.is_empty() {
+        return Err("writableRoots require the workspace-write sandbox".to_string());
+    }
+
+    let workspace = PathBuf::from(routing::runtime_key(cwd));
+    let mut allowed = vec![lexical_root(&workspace, ".")?];
+    for extra in &limit.extra_writable_roots {
+        allowed.push(lexical_root(&workspace, extra)?);
+    }
+    let mut roots = Vec::with_capacity(sandbox.writable_roots.len());
+    for raw in &sandbox.writable_roots {
+        let root = lexical_root(&workspace, raw)?;
+        if !allowed.iter().any(|allowed| root.starts_with(allowed)) {
+            return Err(format!(
+                "writable root is outside the allowed roots: {}",
+                root.display()
+            ));
+        }
+        let root = root.to_string_lossy().to_string();
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    sandbox.writable_roots = roots;
+
+    Ok(EffectivePolicy {
+        cwd: workspace.to_string_lossy().to_string(),
+        sandbox,
+        approval,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn enforces_workspace_limits() {
+        let mut config = EnginePolicyConfig::default();
+        config.workspaces.insert(
+            "/work/app".to_string(),
+            WorkspacePolicyLimit {
+                max_sandbox: SandboxMode::WorkspaceWrite,
+                max_approval: ApprovalPolicy::OnFailure,
+                allow_network: false,
+                extra_writable_roots: vec!["/opt/rail-cache".to_string()],
+            },
+        );
+
+        let write = |roots: &[&str]| SandboxPolicy {
+            mode: SandboxMode::WorkspaceWrite,
+            writable_roots: roots.iter().map(|root| root.to_string()).collect(),
+            network_access: false,
+        };
+
+        let limit = config.limit_for("/work/app/src");
+        let policy = resolve_policy(
+            limit,
+            "/work/app/",
+            Some(write(&["out", "./out", "/opt/rail-cache/x"])),
+            Some(ApprovalPolicy::Untrusted),
+        )
+        .unwrap();
+        assert_eq!(policy.cwd, "/work/app");
+        assert_eq!(
+            policy.sandbox.writable_roots,
+            vec!["/work/app/out", "/opt/rail-cache/x"]
+        );
+        assert_eq!(policy.sandbox_policy_value()["type"], "workspaceWrite");
+
+        assert!(resolve_policy(limit, "/work/app", Some(write(&["../other"])), None).is_err());
+        assert!(resolve_policy(limit, "/work/app", None, Some(ApprovalPolicy::Never)).is_err());
+        let full = SandboxPolicy {
+            mode: SandboxMode::DangerFullAccess,
+            ..SandboxPolicy::default()
+        };
+        assert!(resolve_policy(limit, "/work/app", Some(full), None).is_err());
+
+        // Outside any configured workspace the read-only default applies.
+        let fallback = config.limit_for("/elsewhere");
+        assert!(resolve_policy(fallback, "/elsewhere", Some(write(&[])), None).is_err());
+        let default = resolve_policy(fallback, "/elsewhere", None, None).unwrap();
+        assert_eq!(
+            default.sandbox_policy_value(),
+            json!({ "type": "readOnly" })
+        );
+        assert_eq!(default.approval, ApprovalPolicy::OnRequest);
+        let strict = resolve_policy(limit, "/work/app", None, None).unwrap();
+        assert_eq!(strict.approval, ApprovalPolicy::OnFailure);
+    }
+
+    #[test]
+    fn writable_roots_cannot_leave_through_symlinks() {
+        let base = std::env::temp_dir().join(format!(
+            "rail_engine_policy_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let workspace = base.join("workspace");
+        let outside = base.join("outside");
+        fs::create_dir_all(&workspace).expect("create workspace");
+        fs::create_dir_all(&outside).expect("create outside");
+        let limit = WorkspacePolicyLimit {
+            max_sandbox: SandboxMode::WorkspaceWrite,
+            ..WorkspacePolicyLimit::default()
+        };
+        let write = |root: &str| SandboxPolicy {
+            mode: SandboxMode::WorkspaceWrite,
+            writable_roots: vec![root.to_string()],
+            network_access: false,
+        };
+        let cwd = workspace.to_string_lossy().to_string();
+
+        assert!(resolve_policy(&limit, &cwd, Some(write("new/dir")), None).is_ok());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, workspace.join("link")).expect("create link");
+            assert!(resolve_policy(&limit, &cwd, Some(write("link/new")), None).is_err());
+        }
+
+        let _ = fs::remove_dir_all(base);
+    }
+}
@@ -0,0 +1,388 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Instant,
+};
+use tokio::io::{AsyncBufRead, AsyncWrite, AsyncWriteExt, Lines};
+
+pub const CHANNEL_ENGINE: &str = "engine";
+pub const CHANNEL_WEB_WORKER: &str = "webWorker";
+pub const RECORDING_EXTENSION: &str = "jsonl";
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    Outbound,
+    Inbound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedMessage {
+    pub at: String,
+    pub elapsed_ms: u64,
+    pub channel: String,
+    pub runtime_id: Option<String>,
+    pub direction: Direction,
+    pub message: Value,
+}
+
+// Recordings outlive the session, so secrets the worker only keeps in memory
+// (the bridge token in `bridge/status` and `bridge/tokenRotate` answers, for
+// one) must not end up in them. `token` only counts at the end of a key so
+// usage counters and masked previews such as `tokenMasked` stay readable.
+const SECRET_KEY_PARTS: [&str; 6] = [
+    "secret",
+    "password",
+    "apikey",
+    "api_key",
+    "cookie",
+    "authorization",
+];
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.ends_with("token") || SECRET_KEY_PARTS.iter().any(|part| key.contains(part))
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if field.is_string() && is_secret_key(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    started: Instant,
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create recordings directory: {e}"))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("failed to open recording {}: {e}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Recording is diagnostics only, so write failures never reach the engine.
+    pub fn record(
+        &self,
+        channel: &str,
+        runtime_id: Option<&str>,
+        direction: Direction,
+        message: &Value,
+    ) {
+        let mut message = message.clone();
+        redact(&mut message);
+        let entry = RecordedMessage {
+            at: chrono::Utc::now().to_rfc3339(),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            channel: channel.to_string(),
+            runtime_id: runtime_id.map(str::to_string),
+            direction,
+            message,
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+
+    // Lines that are not JSON are kept verbatim as strings.
+    pub fn record_line(
+        &self,
+        channel: &str,
+        runtime_id: Option<&str>,
+        direction: Direction,
+        line: &str,
+    ) {
+        let message =
+            serde_json::from_str(line).unwrap_or_else(|_| Value::String(line.to_string()));
+        self.record(channel, runtime_id, direction, &message);
+    }
+}
+
+pub fn load_recording(path: &Path) -> Result<Vec<RecordedMessage>, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read recording {}: {e}", path.display()))?;
+    raw.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("invalid recording line {}: {e}", index + 1))
+        })
+        .collect()
+}
+
+// A recording may hold several runtimes and the web worker; replay follows the
+// first engine runtime it finds.
+pub fn engine_script(messages: Vec<RecordedMessage>) -> Vec<RecordedMessage> {
+    let runtime_id = messages
+        .iter()
+        .find(|entry| entry.channel == CHANNEL_ENGINE)
+        .map(|entry| entry.runtime_id.clone());
+    let Some(runtime_id) = runtime_id else {
+        return Vec::new();
+    };
+    messages
+        .into_iter()
+        .filter(|entry| entry.channel == CHANNEL_ENGINE && entry.runtime_id == runtime_id)
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub sent: usize,
+    pub received: usize,
+    pub mismatches: usize,
+}
+
+fn id_key(id: &Value) -> String {
+    id.to_string()
+}
+
+// Plays the server side of a recording. Outbound entries wait for the runtime
+// to write its next line; inbound entries are written back with response ids
+// remapped onto the ids the live runtime used for the matching requests.
+pub async fn replay<R, W>(
+    script: Vec<RecordedMessage>,
+    lines: &mut Lines<R>,
+    inbound: &mut W,
+    on_mismatch: impl Fn(String),
+) -> Result<ReplayStats, String>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut live_ids: HashMap<String, Value> = HashMap::new();
+    let mut stats = ReplayStats::default();
+
+    for entry in script {
+        match entry.direction {
+            Direction::Outbound => {
+                let Some(line) = lines
+                    .next_line()
+                    .await
+                    .map_err(|e| format!("failed to read replay input: {e}"))?
+                else {
+                    return Ok(stats);
+                };
+                stats.received += 1;
+                let live: Value = serde_json::from_str(&line).unwrap_or(Value::Null);
+                let expected = entry.message.get("method");
+                if expected != live.get("method") {
+                    stats.mismatches += 1;
+                    on_mismatch(format!(
+                        "expected {} but runtime sent {}",
+                        expected.unwrap_or(&Value::Null),
+                        live.get("method").unwrap_or(&Value::Null)
+                    ));
+                }
+                if let (Some(recorded_id), Some(live_id), Some(_)) = (
+                    entry.message.get("id"),
+                    live.get("id"),
+                    entry.message.get("method"),
+                ) {
+                    live_ids.insert(id_key(recorded_id), live_id.clone());
+                }
+            }
+            Direction::Inbound => {
+                let mut message = entry.message;
+                if message.get("method").is_none() {
+                    let live_id = message
+                        .get("id")
+                        .and_then(|id| live_ids.remove(&id_key(id)));
+                    if let Some(live_id) = live_id {
+                        message["id"] = live_id;
+                    }
+                }
+                let mut bytes = match &message {
+                    Value::String(raw) => raw.clone().into_bytes(),
+                    _ => serde_json::to_vec(&message)
+                        .map_err(|e| format!("failed to serialize replay message: {e}"))?,
+                };
+                bytes.push(b'\n');
+                inbound
+                    .write_all(&bytes)
+                    .await
+                    .map_err(|e| format!("failed to write replay output: {e}"))?;
+                inbound
+                    .flush()
+                    .await
+                    .map_err(|e| format!("failed to flush replay output: {e}"))?;
+                stats.sent += 1;
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+// Consumes whatever the runtime writes after the script ended so its writes
+// never block; returns how many lines were discarded.
+pub async fn drain<R: AsyncBufRead + Unpin>(lines: &mut Lines<R>) -> usize {
+    let mut count = 0;
+    while let Ok(Some(_)) = lines.next_line().await {
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{duplex, AsyncBufReadExt, BufReader};
+
+    fn entry(direction: Direction, message: Value) -> RecordedMessage {
+        RecordedMessage {
+            at: String::new(),
+            elapsed_ms: 0,
+            channel: CHANNEL_ENGINE.to_string(),
+            runtime_id: Some("rt".to_string()),
+            direction,
+            message,
+        }
+    }
+
+    #[test]
+    fn redacts_secrets_before_writing() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "rail_recorder_{}",
+                chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+            ))
+            .join("session.jsonl");
+        let recorder = Recorder::create(&path).unwrap();
+        recorder.record_line(
+            CHANNEL_WEB_WORKER,
+            None,
+            Direction::Inbound,
+            r#"{"jsonrpc":"2.0","id":3,"result":{"token":"s3cret","tokenMasked":"s3***","tokenStorage":"memory"}}"#,
+        );
+        recorder.record(
+            CHANNEL_ENGINE,
+            Some("rt"),
+            Direction::Inbound,
+            &json!({ "params": { "tokenUsage": { "totalTokens": 5 }, "items": [{ "accessToken": "abc" }] } }),
+        );
+
+        recorder.record(
+            CHANNEL_ENGINE,
+            Some("rt"),
+            Direction::Outbound,
+            &json!({ "params": {
+                "api_key": "k1",
+                "clientSecret": "k2",
+                "Password": "k3",
+                "headers": { "Set-Cookie": "k4", "Proxy-Authorization": "k5" },
+                "refresh_token": "k6"
+            } }),
+        );
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("s3cret") && !raw.contains("abc"));
+        for secret in ["k1", "k2", "k3", "k4", "k5", "k6"] {
+            assert!(!raw.contains(&format!("\"{secret}\"")), "{secret} leaked");
+        }
+        let messages = load_recording(&path).unwrap();
+        let result = &messages[0].message["result"];
+        assert_eq!(result["token"], REDACTED);
+        assert_eq!(result["tokenMasked"], "s3***");
+        let params = &messages[1].message["params"];
+        assert_eq!(params["tokenUsage"]["totalTokens"], 5);
+        assert_eq!(params["items"][0]["accessToken"], REDACTED);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn replays_with_remapped_ids() {
+        let script = vec![
+            entry(
+                Direction::Outbound,
+                json!({ "jsonrpc": "2.0", "id": 7, "method": "initialize" }),
+            ),
+            entry(
+                Direction::Inbound,
+                json!({ "jsonrpc": "2.0", "method": "engine/ping", "params": {} }),
+            ),
+            entry(
+                Direction::Inbound,
+                json!({ "jsonrpc": "2.0", "id": 7, "result": { "ok": true } }),
+            ),
+        ];
+        // One pipe per direction so closing the runtime side ends the replay.
+        let (mut runtime_stdin, replay_input) = duplex(4096);
+        let (replay_output, runtime_stdout) = duplex(4096);
+        let replay_task = tokio::spawn(async move {
+            let mut lines = BufReader::new(replay_input).lines();
+            let mut output = replay_output;
+            let stats = replay(script, &mut lines, &mut output, |_| {}).await;
+            (stats, drain(&mut lines).await)
+        });
+
+        runtime_stdin
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\"}\n")
+            .await
+            .unwrap();
+        let mut lines = BufReader::new(runtime_stdout).lines();
+        let first: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        let second: Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(first["method"], "engine/ping");
+        assert_eq!(second["id"], 1);
+        assert_eq!(second["result"]["ok"], true);
+
+        runtime_stdin
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"initialized\"}\n")
+            .await
+            .unwrap();
+        drop(runtime_stdin);
+        let (stats, drained) = replay_task.await.unwrap();
+        assert_eq!(
+            stats.unwrap(),
+            ReplayStats {
+                sent: 2,
+                received: 1,
+                mismatches: 0
+            }
+        );
+        assert_eq!(drained, 1);
+    }
+}
@@ -0,0 +1,62 @@
+use std::{fs, path::PathBuf};
+
+fn normalize_cwd(cwd: &str) -> PathBuf {
+    let trimmed = cwd.trim();
+    fs::canonicalize(trimmed).unwrap_or_else(|_| {
+        let stripped = trimmed.trim_end_matches(['/', '\\']);
+        PathBuf::from(if stripped.is_empty() {
+            trimmed
+        } else {
+            stripped
+        })
+    })
+}
+
+// Runtimes started without an explicit id are keyed by their canonical cwd so
+// the same workspace reached through different spellings maps to one runtime.
+pub fn runtime_key(cwd: &str) -> String {
+    normalize_cwd(cwd).to_string_lossy().to_string()
+}
+
+// Picks the runtime whose workspace contains `cwd`, preferring the deepest one.
+pub fn match_runtime_for_cwd<'a>(
+    runtimes: impl IntoIterator<Item = (&'a str, &'a str)>,
+    cwd: &str,
+) -> Option<&'a str> {
+    let target = normalize_cwd(cwd);
+    runtimes
+        .into_iter()
+        .filter_map(|(id, runtime_cwd)| {
+            let root = normalize_cwd(runtime_cwd);
+            target
+                .starts_with(&root)
+                .then(|| (root.components().count(), id))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, id)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_cwd_to_deepest_runtime() {
+        let runtimes = [
+            ("a", "/work/projects"),
+            ("b", "/work/projects/app/"),
+            ("c", "/other"),
+        ];
+        let pairs = || runtimes.iter().map(|(id, cwd)| (*id, *cwd));
+        assert_eq!(
+            match_runtime_for_cwd(pairs(), "/work/projects/app/src"),
+            Some("b")
+        );
+        assert_eq!(
+            match_runtime_for_cwd(pairs(), "/work/projects/lib"),
+            Some("a")
+        );
+        assert_eq!(match_runtime_for_cwd(pairs(), "/work"), None);
+        assert_eq!(runtime_key("/missing/dir/"), "/missing/dir");
+    }
+}
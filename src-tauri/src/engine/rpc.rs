@@ -0,0 +1,385 @@
+use super::recorder::Direction;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    process::{Child, ChildStderr},
+    sync::{mpsc, oneshot, Mutex},
+    task::JoinHandle,
+    time::{timeout, Duration},
+};
+
+// JSON-RPC's conventional notification for abandoning a request by id.
+pub const CANCEL_REQUEST_METHOD: &str = "$/cancelRequest";
+// The exact error a cancelled request fails with, so callers can tell a
+// cancellation from a failure without parsing messages.
+pub const REQUEST_CANCELLED: &str = "request cancelled";
+
+pub type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
+// Sees every JSONL line written to or read from the app-server, e.g. for recording.
+pub type TrafficTap = Arc<dyn Fn(Direction, &str) + Send + Sync>;
+type AppServerStdin = Mutex<Box<dyn AsyncWrite + Send + Unpin>>;
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RpcIncomingMessage {
+    #[serde(default)]
+    pub(super) id: Option<Value>,
+    #[serde(default)]
+    pub(super) method: Option<String>,
+    #[serde(default)]
+    pub(super) params: Option<Value>,
+    #[serde(default)]
+    pub(super) result: Option<Value>,
+    #[serde(default)]
+    pub(super) error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(default)]
+    data: Option<Value>,
+}
+
+pub(super) fn rpc_id_to_u64(id: &Value) -> Option<u64> {
+    match id {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse::<u64>().ok(),
+        _ => None,
+    }
+}
+
+pub(super) fn format_rpc_error(err: RpcError) -> String {
+    match err.data {
+        Some(data) => format!("rpc error {}: {} ({data})", err.code, err.message),
+        None => format!("rpc error {}: {}", err.code, err.message),
+    }
+}
+
+pub async fn resolve_all_pending(pending: &Mutex<PendingMap>, reason: &str) {
+    let mut locked = pending.lock().await;
+    for (_id, sender) in locked.drain() {
+        let _ = sender.send(Err(reason.to_string()));
+    }
+}
+
+pub struct AppServerTransport {
+    pub child: Option<Child>,
+    pub stdin: Box<dyn AsyncWrite + Send + Unpin>,
+    pub stdout: Box<dyn AsyncRead + Send + Unpin>,
+    pub stderr: Option<ChildStderr>,
+    // Drives the other end of the pipes when there is no child process.
+    pub replay_task: Option<JoinHandle<()>>,
+}
+
+impl AppServerTransport {
+    pub fn from_child(mut child: Child) -> Result<Self, String> {
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| "failed to open child stdin".to_string())?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "failed to open child stdout".to_string())?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| "failed to open child stderr".to_string())?;
+        Ok(Self {
+            child: Some(child),
+            stdin: Box::new(stdin),
+            stdout: Box::new(stdout),
+            stderr: Some(stderr),
+            replay_task: None,
+        })
+    }
+}
+
+// Everything the app-server sends that is not a response to one of our requests.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incoming {
+    Request {
+        id: u64,
+        method: String,
+        params: Value,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+    ParseError(String),
+    Stderr(String),
+    StderrError(String),
+    // Sent last, after every pending request has been failed.
+    Disconnected,
+    ReadError(String),
+}
+
+pub struct RpcConnection {
+    child: Option<Arc<Mutex<Child>>>,
+    pid: Option<u32>,
+    stdin: Arc<AppServerStdin>,
+    tap: Option<TrafficTap>,
+    pending: Arc<Mutex<PendingMap>>,
+    next_id: AtomicU64,
+    reader_task: JoinHandle<()>,
+    stderr_task: Option<JoinHandle<()>>,
+    replay_task: Option<JoinHandle<()>>,
+}
+
+impl RpcConnection {
+    // Responses resolve pending requests directly; everything else is handed
+    // to `incoming` in the order it was read.
+    pub fn start(
+        transport: AppServerTransport,
+        incoming: mpsc::UnboundedSender<Incoming>,
+        tap: Option<TrafficTap>,
+    ) -> Self {
+        let AppServerTransport {
+            child,
+            stdin,
+            stdout,
+            stderr,
+            replay_task,
+        } = transport;
+        let pid = child.as_ref().and_then(Child::id);
+        let pending: Arc<Mutex<PendingMap>> = Arc::new(Mutex::new(HashMap::new()));
+
+        let reader_task = {
+            let pending = pending.clone();
+            let tap = tap.clone();
+            let incoming = incoming.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stdout).lines();
+                let end = loop {
+                    match lines.next_line().await {
+                        Ok(Some(raw_line)) => {
+                            let line = raw_line.trim();
+                            if line.is_empty() {
+                                continue;
+                            }
+                            if let Some(tap) = &tap {
+                                tap(Direction::Inbound, line);
+                            }
+                            if let Some(message) = route_incoming_line(&pending, line).await {
+                                let _ = incoming.send(message);
+                            }
+                        }
+                        Ok(None) => break Incoming::Disconnected,
+                        Err(err) => {
+                            break Incoming::ReadError(format!(
+                                "failed while reading stdout: {err}"
+                            ))
+                        }
+                    }
+                };
+                resolve_all_pending(&pending, "engine output stream closed").await;
+                let _ = incoming.send(end);
+            })
+        };
+
+        let stderr_task = stderr.map(|stderr| {
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                loop {
+                    match lines.next_line().await {
+                        Ok(Some(line)) => {
+                            let _ = incoming.send(Incoming::Stderr(line));
+                        }
+                        Ok(None) => break,
+                        Err(err) => {
+                            let _ = incoming.send(Incoming::StderrError(format!(
+                                "failed while reading stderr: {err}"
+                            )));
+                            break;
+                        }
+                    }
+                }
+            })
+        });
+
+        Self {
+            child: child.map(|child| Arc::new(Mutex::new(child))),
+            pid,
+            stdin: Arc::new(Mutex::new(stdin)),
+            tap,
+            pending,
+            next_id: AtomicU64::new(1),
+            reader_task,
+            stderr_task,
+            replay_task,
+        }
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    pub async fn pending_count(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    // Lets callers know a request's id before it is sent, e.g. to cancel it.
+    pub fn next_request_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Value,
+        limit: Duration,
+    ) -> Result<Value, String> {
+        self.request_with_id(self.next_request_id(), method, params, limit)
+            .await
+    }
+
+    pub async fn request_with_id(
+        &self,
+        id: u64,
+        method: &str,
+        params: Value,
+        limit: Duration,
+    ) -> Result<Value, String> {
+        let payload = json!({
+          "jsonrpc": "2.0",
+          "id": id,
+          "method": method,
+          "params": params
+        });
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        if let Err(err) = self.write(&payload).await {
+            self.pending.lock().await.remove(&id);
+            return Err(err);
+        }
+
+        match timeout(limit, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_recv_closed)) => Err("response channel closed".to_string()),
+            Err(_elapsed) => {
+                self.pending.lock().await.remove(&id);
+                Err(format!("request timed out: {method}"))
+            }
+        }
+    }
+
+    // Fails the request right away and asks the server to stop working on it;
+    // a late response is dropped like any other unknown id. Returns false when
+    // the request already finished.
+    pub async fn cancel(&self, id: u64) -> Result<bool, String> {
+        let Some(sender) = self.pending.lock().await.remove(&id) else {
+            return Ok(false);
+        };
+        let _ = sender.send(Err(REQUEST_CANCELLED.to_string()));
+        self.notify(CANCEL_REQUEST_METHOD, json!({ "id": id }))
+            .await?;
+        Ok(true)
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.write(&json!({
+          "jsonrpc": "2.0",
+          "method": method,
+          "params": params
+        }))
+        .await
+    }
+
+    pub async fn respond(&self, request_id: u64, result: Value) -> Result<(), String> {
+        self.write(&json!({
+          "jsonrpc": "2.0",
+          "id": request_id,
+          "result": result
+        }))
+        .await
+    }
+
+    pub async fn write(&self, payload: &Value) -> Result<(), String> {
+        let mut line = serde_json::to_string(payload)
+            .map_err(|e| format!("failed to serialize JSON-RPC payload: {e}"))?;
+        if let Some(tap) = &self.tap {
+            tap(Direction::Outbound, &line);
+        }
+        line.push('\n');
+
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("failed to write to app-server stdin: {e}"))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("failed to flush app-server stdin: {e}"))
+    }
+
+    pub async fn has_exited(&self) -> bool {
+        match (&self.child, &self.replay_task) {
+            (Some(child), _) => !matches!(child.lock().await.try_wait(), Ok(None)),
+            (None, Some(replay_task)) => replay_task.is_finished(),
+            (None, None) => self.reader_task.is_finished(),
+        }
+    }
+
+    pub async fn shutdown(&self) {
+        self.reader_task.abort();
+        if let Some(stderr_task) = &self.stderr_task {
+            stderr_task.abort();
+        }
+        if let Some(replay_task) = &self.replay_task {
+            replay_task.abort();
+        }
+
+        if let Some(child) = &self.child {
+            let mut child = child.lock().await;
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+        }
+
+        resolve_all_pending(&self.pending, "engine stopped").await;
+    }
+}
+
+async fn route_incoming_line(pending: &Mutex<PendingMap>, line: &str) -> Option<Incoming> {
+    let incoming: RpcIncomingMessage = match serde_json::from_str(line) {
+        Ok(incoming) => incoming,
+        Err(e) => return Some(Incoming::ParseError(format!("invalid json: {e}"))),
+    };
+
+    match (incoming.method, incoming.id) {
+        // Server requests with a non-numeric id cannot be answered and are dropped.
+        (Some(method), Some(id)) if incoming.result.is_none() && incoming.error.is_none() => {
+            Some(Incoming::Request {
+                id: rpc_id_to_u64(&id)?,
+                method,
+                params: incoming.params.unwrap_or(Value::Null),
+            })
+        }
+        (Some(method), None) => Some(Incoming::Notification {
+            method,
+            params: incoming.params.unwrap_or(Value::Null),
+        }),
+        (_, Some(id)) => {
+            let sender = pending.lock().await.remove(&rpc_id_to_u64(&id)?)?;
+            let response = match incoming.error {
+                Some(err) => Err(format_rpc_error(err)),
+                None => Ok(incoming.result.unwrap_or(Value::Null)),
+            };
+            let _ = sender.send(response);
+            None
+        }
+        (None, None) => None,
+    }
+}
@@ -0,0 +1,259 @@
+use super::{emit_lifecycle, EngineManager, EngineRuntime, EngineRuntimeStatus, EngineSource};
+use serde_json::json;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc, Weak,
+};
+use tauri::{AppHandle, Manager};
+use tokio::{
+    sync::Mutex,
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+
+const RESTART_BASE_DELAY: Duration = Duration::from_millis(500);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+const MAX_RESTART_ATTEMPTS: u32 = 8;
+// A runtime that stayed up at least this long resets the backoff.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+pub(super) fn restart_delay(attempt: u32) -> Duration {
+    let factor = 1u32
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    RESTART_BASE_DELAY
+        .saturating_mul(factor)
+        .min(RESTART_MAX_DELAY)
+}
+
+pub(super) struct EngineSupervisor {
+    pub(super) id: String,
+    pub(super) cwd: String,
+    source: EngineSource,
+    app: AppHandle,
+    current: Mutex<Arc<EngineRuntime>>,
+    restarting: AtomicBool,
+    failed: AtomicBool,
+    stopping: AtomicBool,
+    restart_count: AtomicU32,
+    task: std::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl EngineSupervisor {
+    pub(super) async fn start(
+        app: AppHandle,
+        id: String,
+        cwd: String,
+        source: EngineSource,
+    ) -> Result<Arc<Self>, String> {
+        let runtime = EngineRuntime::start(app.clone(), id.clone(), cwd.clone(), &source).await?;
+        let supervisor = Arc::new(Self {
+            id,
+            cwd,
+            source,
+            app,
+            current: Mutex::new(runtime),
+            restarting: AtomicBool::new(false),
+            failed: AtomicBool::new(false),
+            stopping: AtomicBool::new(false),
+            restart_count: AtomicU32::new(0),
+            task: std::sync::Mutex::new(None),
+        });
+        let task = tokio::spawn(Self::supervise(Arc::downgrade(&supervisor)));
+        if let Ok(mut slot) = supervisor.task.lock() {
+            *slot = Some(task);
+        }
+        Ok(supervisor)
+    }
+
+    pub(super) async fn current(&self) -> Result<Arc<EngineRuntime>, String> {
+        if self.failed.load(Ordering::SeqCst) {
+            return Err(format!("engine runtime {} failed to restart", self.id));
+        }
+        if self.restarting.load(Ordering::SeqCst) {
+            return Err(format!("engine runtime {} is restarting", self.id));
+        }
+        Ok(self.current.lock().await.clone())
+    }
+
+    // Any runtime that is still usable or may become usable again.
+    pub(super) fn is_alive(&self) -> bool {
+        !self.failed.load(Ordering::SeqCst) && !self.stopping.load(Ordering::SeqCst)
+    }
+
+    pub(super) fn is_replay(&self) -> bool {
+        matches!(self.source, EngineSource::Replay(_))
+    }
+
+    pub(super) async fn stop(&self) -> Result<(), String> {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(task) = self.task.lock().ok().and_then(|mut slot| slot.take()) {
+            task.abort();
+        }
+        let runtime = self.current.lock().await.clone();
+        runtime.stop().await
+    }
+
+    pub(super) async fn status(&self, thread_count: usize) -> EngineRuntimeStatus {
+        let runtime = self.current.lock().await.clone();
+        let mut status = runtime.status(thread_count).await;
+        if self.failed.load(Ordering::SeqCst) {
+            status.state = "failed".to_string();
+        } else if self.restarting.load(Ordering::SeqCst) {
+            status.state = "restarting".to_string();
+        }
+        status.restart_count = self.restart_count.load(Ordering::SeqCst);
+        status
+    }
+
+    // Holds only a weak reference so dropping the supervisor ends the loop.
+    async fn supervise(supervisor: Weak<Self>) {
+        let mut attempt = 0;
+        loop {
+            let runtime = match supervisor.upgrade() {
+                Some(supervisor) => supervisor.current.lock().await.clone(),
+                None => return,
+            };
+            runtime.closed.notified().await;
+            let uptime = runtime.started.elapsed();
+            drop(runtime);
+
+            let Some(supervisor) = supervisor.upgrade() else {
+                return;
+            };
+            if supervisor.stopping.load(Ordering::SeqCst)
+                || matches!(supervisor.source, EngineSource::Replay(_))
+            {
+                return;
+            }
+            if uptime >= STABLE_UPTIME {
+                attempt = 0;
+            }
+            match supervisor.restart(attempt).await {
+                Some(used) => attempt = used,
+                None => return,
+            }
+        }
+    }
+
+    async fn restart(&self, mut attempt: u32) -> Option<u32> {
+        self.restarting.store(true, Ordering::SeqCst);
+        let previous = self.current.lock().await.clone();
+        previous.shutdown().await;
+
+        loop {
+            attempt += 1;
+            if attempt > MAX_RESTART_ATTEMPTS {
+                self.failed.store(true, Ordering::SeqCst);
+                self.restarting.store(false, Ordering::SeqCst);
+                emit_lifecycle(
+                    &self.app,
+                    &self.id,
+                    "restartFailed",
+                    Some(format!("gave up after {MAX_RESTART_ATTEMPTS} attempts")),
+                );
+                return None;
+            }
+
+            let delay = restart_delay(attempt);
+            emit_lifecycle(
+                &self.app,
+                &self.id,
+                "restarting",
+                Some(format!(
+                    "attempt {attempt}/{MAX_RESTART_ATTEMPTS} in {}ms",
+                    delay.as_millis()
+                )),
+            );
+            sleep(delay).await;
+            if self.stopping.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            match EngineRuntime::start(
+                self.app.clone(),
+                self.id.clone(),
+                self.cwd.clone(),
+                &self.source,
+            )
+            .await
+            {
+                Ok(runtime) => {
+                    if self.stopping.load(Ordering::SeqCst) {
+                        runtime.shutdown().await;
+                        return None;
+                    }
+                    *self.current.lock().await = runtime.clone();
+                    self.restart_count.fetch_add(1, Ordering::SeqCst);
+                    // Commands keep seeing "restarting" until the threads are
+                    // loaded again, so no turn reaches a thread the new
+                    // app-server does not know yet.
+                    let (resumed, failed) = self.resume_threads(&runtime).await;
+                    self.restarting.store(false, Ordering::SeqCst);
+                    emit_lifecycle(
+                        &self.app,
+                        &self.id,
+                        "recovered",
+                        Some(format!("resumed {resumed} thread(s), {failed} failed")),
+                    );
+                    return Some(attempt);
+                }
+                Err(err) => {
+                    emit_lifecycle(&self.app, &self.id, "restartError", Some(err));
+                }
+            }
+        }
+    }
+
+    // Thread ids stay mapped to this runtime id across restarts; the new
+    // app-server only needs to load them again.
+    async fn resume_threads(&self, runtime: &EngineRuntime) -> (usize, usize) {
+        let manager = self.app.state::<EngineManager>();
+        let thread_ids = manager
+            .thread_runtimes
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, owner)| **owner == self.id)
+            .map(|(thread_id, _)| thread_id.clone())
+            .collect::<Vec<_>>();
+
+        let mut resumed = 0;
+        let mut failed = 0;
+        for thread_id in thread_ids {
+            match runtime
+                .request("thread/resume", json!({ "threadId": thread_id }))
+                .await
+            {
+                Ok(_) => {
+                    resumed += 1;
+                    emit_lifecycle(&self.app, &self.id, "threadResumed", Some(thread_id));
+                }
+                Err(err) => {
+                    failed += 1;
+                    emit_lifecycle(
+                        &self.app,
+                        &self.id,
+                        "threadResumeFailed",
+                        Some(format!("{thread_id}: {err}")),
+                    );
+                }
+            }
+        }
+        (resumed, failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restart_delay_backs_off_exponentially_and_caps() {
+        assert_eq!(restart_delay(1), Duration::from_millis(500));
+        assert_eq!(restart_delay(2), Duration::from_secs(1));
+        assert_eq!(restart_delay(4), Duration::from_secs(4));
+        assert_eq!(restart_delay(7), RESTART_MAX_DELAY);
+        assert_eq!(restart_delay(40), RESTART_MAX_DELAY);
+    }
+}
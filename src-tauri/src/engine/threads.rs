@@ -0,0 +1,516 @@
+use super::routing::runtime_key;
+use super::turns::{TurnResult, TurnStatus};
+use crate::storage::atomic_file::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const THREADS_DIR: &str = ".rail/threads";
+const INDEX_FILE_NAME: &str = "index.json";
+const EXPORTS_DIR_NAME: &str = "exports";
+const TITLE_MAX_CHARS: usize = 80;
+
+// Runtimes and commands update the same index files; keep their
+// read-modify-write cycles from interleaving.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadIndexEntry {
+    pub thread_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub turn_count: u32,
+    #[serde(default)]
+    pub last_status: Option<TurnStatus>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ThreadIndex {
+    pub threads: Vec<ThreadIndexEntry>,
+}
+
+impl ThreadIndex {
+    pub fn get(&self, thread_id: &str) -> Option<&ThreadIndexEntry> {
+        self.threads
+            .iter()
+            .find(|entry| entry.thread_id == thread_id)
+    }
+
+    pub fn entry_mut(&mut self, thread_id: &str) -> &mut ThreadIndexEntry {
+        let index = match self
+            .threads
+            .iter()
+            .position(|entry| entry.thread_id == thread_id)
+        {
+            Some(index) => index,
+            None => {
+                let now = chrono::Utc::now().to_rfc3339();
+                self.threads.push(ThreadIndexEntry {
+                    thread_id: thread_id.to_string(),
+                    title: None,
+                    model: None,
+                    created_at: now.clone(),
+                    updated_at: now,
+                    turn_count: 0,
+                    last_status: None,
+                    archived: false,
+                });
+                self.threads.len() - 1
+            }
+        };
+        &mut self.threads[index]
+    }
+
+    // Most recently active first.
+    pub fn listed(&self, include_archived: bool) -> Vec<ThreadIndexEntry> {
+        let mut threads = self
+            .threads
+            .iter()
+            .filter(|entry| include_archived || !entry.archived)
+            .cloned()
+            .collect::<Vec<_>>();
+        threads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        threads
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum HistoryEntry {
+    UserMessage {
+        at: String,
+        text: String,
+    },
+    Turn {
+        at: String,
+        result: TurnResult,
+    },
+    Approval {
+        at: String,
+        request_id: u64,
+        method: String,
+        decision: String,
+        // The approval rule that answered; None when the user did.
+        #[serde(default)]
+        rule: Option<String>,
+        #[serde(default)]
+        summary: Option<String>,
+    },
+}
+
+pub fn threads_dir(workspace: &Path) -> PathBuf {
+    workspace.join(THREADS_DIR)
+}
+
+// Thread ids become file names, so only accept what app-servers hand out.
+fn validate_thread_id(thread_id: &str) -> Result<&str, String> {
+    let valid = !thread_id.is_empty()
+        && !thread_id.starts_with('.')
+        && thread_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(thread_id)
+    } else {
+        Err(format!("invalid thread id: {thread_id}"))
+    }
+}
+
+fn history_path(workspace: &Path, thread_id: &str) -> Result<PathBuf, String> {
+    Ok(threads_dir(workspace).join(format!("{}.jsonl", validate_thread_id(thread_id)?)))
+}
+
+pub fn load_index(workspace: &Path) -> Result<ThreadIndex, String> {
+    let path = threads_dir(workspace).join(INDEX_FILE_NAME);
+    if !path.exists() {
+        return Ok(ThreadIndex::default());
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| format!("failed to read thread index: {e}"))?;
+    serde_json::from_str(&raw).map_err(|e| format!("failed to parse thread index: {e}"))
+}
+
+pub fn update_index<T>(
+    workspace: &Path,
+    change: impl FnOnce(&mut ThreadIndex) -> T,
+) -> Result<T, String> {
+    let _guard = INDEX_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut index = load_index(workspace)?;
+    let output = change(&mut index);
+    let dir = threads_dir(workspace);
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create threads directory: {e}"))?;
+    let json = serde_json::to_string_pretty(&index)
+        .map_err(|e| format!("failed to serialize thread index: {e}"))?;
+    write_atomic(&dir.join(INDEX_FILE_NAME), json.as_bytes())
+        .map_err(|e| format!("failed to write thread index: {e}"))?;
+    Ok(output)
+}
+
+fn title_from(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    if line.chars().count() <= TITLE_MAX_CHARS {
+        return Some(line.to_string());
+    }
+    let mut title = line.chars().take(TITLE_MAX_CHARS).collect::<String>();
+    title.push('…');
+    Some(title)
+}
+
+// Appends to the thread's history and keeps its index entry in step.
+pub fn record(workspace: &Path, thread_id: &str, entry: &HistoryEntry) -> Result<(), String> {
+    let path = history_path(workspace, thread_id)?;
+    fs::create_dir_all(threads_dir(workspace))
+        .map_err(|e| format!("failed to create threads directory: {e}"))?;
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("failed to serialize thread history entry: {e}"))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("failed to append thread history: {e}"))?;
+
+    update_index(workspace, |index| {
+        let thread = index.entry_mut(thread_id);
+        thread.updated_at = chrono::Utc::now().to_rfc3339();
+        match entry {
+            HistoryEntry::UserMessage { text, .. } => {
+                thread.turn_count += 1;
+                if thread.title.is_none() {
+                    thread.title = title_from(text);
+                }
+            }
+            HistoryEntry::Turn { result, .. } => thread.last_status = Some(result.status),
+            HistoryEntry::Approval { .. } => {}
+        }
+    })
+}
+
+fn timestamp_field(item: &Value, key: &str) -> Option<String> {
+    match item.get(key)? {
+        Value::Number(seconds) => {
+            chrono::DateTime::from_timestamp(seconds.as_i64()?, 0).map(|at| at.to_rfc3339())
+        }
+        Value::String(at) => Some(at.clone()),
+        _ => None,
+    }
+}
+
+// Adopts threads the app-server knows about in this workspace, e.g. ones
+// started from the CLI, and returns how many were new to the index.
+pub fn merge_listed(index: &mut ThreadIndex, listed: &Value, workspace: &Path) -> usize {
+    let workspace = PathBuf::from(runtime_key(&workspace.to_string_lossy()));
+    let items = listed
+        .get("data")
+        .or_else(|| listed.get("threads"))
+        .and_then(Value::as_array);
+    let mut added = 0;
+    for item in items.into_iter().flatten() {
+        let Some(thread_id) = item.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let in_workspace = item
+            .get("cwd")
+            .and_then(Value::as_str)
+            .is_some_and(|cwd| Path::new(&runtime_key(cwd)).starts_with(&workspace));
+        if !in_workspace || validate_thread_id(thread_id).is_err() || index.get(thread_id).is_some()
+        {
+            continue;
+        }
+        let thread = index.entry_mut(thread_id);
+        thread.title = item
+            .get("preview")
+            .and_then(Value::as_str)
+            .and_then(title_from);
+        if let Some(created_at) = timestamp_field(item, "createdAt") {
+            thread.created_at = created_at.clone();
+            thread.updated_at = timestamp_field(item, "updatedAt").unwrap_or(created_at);
+        }
+        added += 1;
+    }
+    added
+}
+
+// Oldest first; unreadable lines are skipped rather than failing the read.
+pub fn read_history(workspace: &Path, thread_id: &str) -> Result<Vec<HistoryEntry>, String> {
+    let path = history_path(workspace, thread_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw =
+        fs::read_to_string(&path).map_err(|e| format!("failed to read thread history: {e}"))?;
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn status_label(status: TurnStatus) -> &'static str {
+    match status {
+        TurnStatus::InProgress => "in progress",
+        TurnStatus::Completed => "completed",
+        TurnStatus::Interrupted => "interrupted",
+        TurnStatus::Failed => "failed",
+        TurnStatus::Cancelled => "cancelled",
+        TurnStatus::TimedOut => "timed out",
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+fn inline_code(text: &str) -> String {
+    let ticks = "`".repeat(longest_backtick_run(text) + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{ticks} {text} {ticks}")
+    } else {
+        format!("{ticks}{text}{ticks}")
+    }
+}
+
+fn code_block(text: &str) -> String {
+    let fence = "`".repeat((longest_backtick_run(text) + 1).max(3));
+    format!("{fence}text\n{}\n{fence}\n", text.trim_end())
+}
+
+pub fn render_markdown(thread: &ThreadIndexEntry, history: &[HistoryEntry]) -> String {
+    let mut out = String::new();
+    let title = thread
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("Thread {}", thread.thread_id));
+    let _ = writeln!(out, "# {title}\n");
+    let _ = writeln!(out, "- Thread: {}", inline_code(&thread.thread_id));
+    if let Some(model) = &thread.model {
+        let _ = writeln!(out, "- Model: {model}");
+    }
+    let _ = writeln!(out, "- Created: {}", thread.created_at);
+    let _ = writeln!(out, "- Updated: {}", thread.updated_at);
+    if thread.archived {
+        let _ = writeln!(out, "- Archived");
+    }
+
+    for entry in history {
+        match entry {
+            HistoryEntry::UserMessage { at, text } => {
+                let _ = write!(out, "\n## User\n\n_{at}_\n\n{}\n", text.trim_end());
+            }
+            HistoryEntry::Turn { at, result } => {
+                let _ = write!(
+                    out,
+                    "\n## Assistant ({})\n\n_{at}_\n\n",
+                    status_label(result.status)
+                );
+                let messages = if result.agent_messages.is_empty() && !result.text.is_empty() {
+                    std::slice::from_ref(&result.text)
+                } else {
+                    result.agent_messages.as_slice()
+                };
+                for message in messages {
+                    let _ = write!(out, "{}\n\n", message.trim_end());
+                }
+                if !result.tool_calls.is_empty() {
+                    out.push_str("### Tool calls\n\n");
+                    for call in &result.tool_calls {
+                        let mut details = vec![call.kind.clone()];
+                        details.extend(call.status.clone());
+                        details.extend(call.exit_code.map(|code| format!("exit {code}")));
+                        let name = call.name.as_deref().unwrap_or(&call.item_id);
+                        let _ = write!(out, "- {} ({})\n\n", inline_code(name), details.join(", "));
+                        if let Some(output) = call.output.as_deref().filter(|o| !o.is_empty()) {
+                            let _ = writeln!(out, "{}", code_block(output));
+                        }
+                    }
+                }
+                if !result.file_changes.is_empty() {
+                    out.push_str("### File changes\n\n");
+                    for change in &result.file_changes {
+                        match &change.kind {
+                            Some(kind) => {
+                                let _ = writeln!(out, "- {} ({kind})", inline_code(&change.path));
+                            }
+                            None => {
+                                let _ = writeln!(out, "- {}", inline_code(&change.path));
+                            }
+                        }
+                    }
+                    out.push('\n');
+                }
+                if let Some(error) = &result.error {
+                    let _ = write!(out, "**Error:** {error}\n\n");
+                }
+            }
+            HistoryEntry::Approval {
+                at,
+                method,
+                decision,
+                rule,
+                summary,
+                ..
+            } => {
+                let by = match rule {
+                    Some(rule) => format!("rule {rule}"),
+                    None => "user".to_string(),
+                };
+                let _ = write!(out, "\n> **Approval: {decision}** ({method})");
+                if let Some(summary) = summary {
+                    let _ = write!(out, " {}", inline_code(summary));
+                }
+                let _ = writeln!(out, "\n> _{at}, by {by}_");
+            }
+        }
+    }
+    out
+}
+
+// Writes the transcript to `.rail/threads/exports/<thread>.md`, replacing
+// any earlier export of the same thread.
+pub fn export_markdown(workspace: &Path, thread_id: &str) -> Result<(PathBuf, String), String> {
+    let history = read_history(workspace, thread_id)?;
+    let index = load_index(workspace)?;
+    let thread = match index.get(thread_id) {
+        Some(thread) => thread.clone(),
+        None if history.is_empty() => return Err(format!("unknown thread: {thread_id}")),
+        None => ThreadIndex::default().entry_mut(thread_id).clone(),
+    };
+    let markdown = render_markdown(&thread, &history);
+    let dir = threads_dir(workspace).join(EXPORTS_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create exports directory: {e}"))?;
+    let path = dir.join(format!("{}.md", validate_thread_id(thread_id)?));
+    write_atomic(&path, markdown.as_bytes())
+        .map_err(|e| format!("failed to write thread export: {e}"))?;
+    Ok((path, markdown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::turns::ToolCall;
+
+    #[test]
+    fn records_history_and_exports_markdown() {
+        let workspace = std::env::temp_dir().join(format!(
+            "rail_threads_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let at = "2026-01-01T00:00:00+00:00".to_string();
+        update_index(&workspace, |index| {
+            index.entry_mut("th-1").model = Some("gpt-5".to_string())
+        })
+        .expect("create entry");
+
+        record(
+            &workspace,
+            "th-1",
+            &HistoryEntry::UserMessage {
+                at: at.clone(),
+                text: "\n  List the files\nplease".to_string(),
+            },
+        )
+        .expect("record message");
+        record(
+            &workspace,
+            "th-1",
+            &HistoryEntry::Approval {
+                at: at.clone(),
+                request_id: 7,
+                method: "item/commandExecution/requestApproval".to_string(),
+                decision: "accept".to_string(),
+                rule: None,
+                summary: Some("ls".to_string()),
+            },
+        )
+        .expect("record approval");
+        record(
+            &workspace,
+            "th-1",
+            &HistoryEntry::Turn {
+                at: at.clone(),
+                result: TurnResult {
+                    thread_id: "th-1".to_string(),
+                    turn_id: Some("t1".to_string()),
+                    status: TurnStatus::Completed,
+                    text: "Done.".to_string(),
+                    agent_messages: vec!["Done.".to_string()],
+                    tool_calls: vec![ToolCall {
+                        item_id: "c1".to_string(),
+                        kind: "commandExecution".to_string(),
+                        name: Some("ls".to_string()),
+                        status: Some("completed".to_string()),
+                        exit_code: Some(0),
+                        output: Some("a.txt\n```\n".to_string()),
+                    }],
+                    file_changes: Vec::new(),
+                    error: None,
+                    started_at: at.clone(),
+                    completed_at: Some(at.clone()),
+                    duration_ms: 5,
+                    usage: None,
+                },
+            },
+        )
+        .expect("record turn");
+
+        let index = load_index(&workspace).expect("load index");
+        let thread = index.get("th-1").expect("indexed thread");
+        assert_eq!(thread.title.as_deref(), Some("List the files"));
+        assert_eq!(thread.model.as_deref(), Some("gpt-5"));
+        assert_eq!(thread.turn_count, 1);
+        assert_eq!(thread.last_status, Some(TurnStatus::Completed));
+
+        assert_eq!(
+            read_history(&workspace, "th-1")
+                .expect("read history")
+                .len(),
+            3
+        );
+        let (path, markdown) = export_markdown(&workspace, "th-1").expect("export");
+        assert_eq!(fs::read_to_string(path).expect("read export"), markdown);
+        assert!(markdown.starts_with("# List the files\n"));
+        assert!(markdown.contains("## Assistant (completed)"));
+        assert!(markdown.contains("- `ls` (commandExecution, completed, exit 0)"));
+        assert!(markdown.contains("````text\na.txt\n```\n````"));
+        assert!(markdown
+            .contains("> **Approval: accept** (item/commandExecution/requestApproval) `ls`"));
+
+        assert!(export_markdown(&workspace, "th-2").is_err());
+
+        let listed = serde_json::json!({
+            "data": [
+                { "id": "th-1", "preview": "ignored", "cwd": workspace },
+                { "id": "th-2", "preview": "From the CLI", "createdAt": 1767225600, "cwd": workspace.join("sub") },
+                { "id": "th-3", "preview": "Elsewhere", "cwd": "/elsewhere" }
+            ]
+        });
+        let mut index = load_index(&workspace).expect("reload index");
+        assert_eq!(merge_listed(&mut index, &listed, &workspace), 1);
+        let adopted = index.get("th-2").expect("adopted thread");
+        assert_eq!(adopted.title.as_deref(), Some("From the CLI"));
+        assert_eq!(adopted.created_at, "2026-01-01T00:00:00+00:00");
+        assert_eq!(index.listed(false)[0].thread_id, "th-1");
+        assert!(read_history(&workspace, "../escape").is_err());
+        let _ = fs::remove_dir_all(workspace);
+    }
+}
@@ -0,0 +1,511 @@
+use super::usage::{parse_token_usage_update, TokenUsage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, time::Instant};
+use tokio::sync::oneshot;
+
+const MAX_TOOL_OUTPUT_CHARS: usize = 16_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TurnStatus {
+    InProgress,
+    Completed,
+    Interrupted,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub item_id: String,
+    pub kind: String,
+    // The command line for shell calls, "server/tool" for MCP calls.
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub exit_code: Option<i64>,
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub item_id: String,
+    pub path: String,
+    pub kind: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnResult {
+    pub thread_id: String,
+    pub turn_id: Option<String>,
+    pub status: TurnStatus,
+    // Final agent message; earlier ones are kept in `agent_messages`.
+    pub text: String,
+    pub agent_messages: Vec<String>,
+    pub tool_calls: Vec<ToolCall>,
+    pub file_changes: Vec<FileChange>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+struct ActiveTurn {
+    result: TurnResult,
+    started: Instant,
+    // Agent message text by item id, in the order the items appeared.
+    messages: Vec<(String, String)>,
+    // Thread total before this turn, derived from the first usage update.
+    usage_baseline: Option<TokenUsage>,
+}
+
+impl ActiveTurn {
+    fn new(thread_id: &str, turn_id: Option<&str>) -> Self {
+        Self {
+            result: TurnResult {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.map(str::to_string),
+                status: TurnStatus::InProgress,
+                text: String::new(),
+                agent_messages: Vec::new(),
+                tool_calls: Vec::new(),
+                file_changes: Vec::new(),
+                error: None,
+                started_at: chrono::Utc::now().to_rfc3339(),
+                completed_at: None,
+                duration_ms: 0,
+                usage: None,
+            },
+            started: Instant::now(),
+            messages: Vec::new(),
+            usage_baseline: None,
+        }
+    }
+
+    fn message_mut(&mut self, item_id: &str) -> &mut String {
+        let index = match self.messages.iter().position(|(id, _)| id == item_id) {
+            Some(index) => index,
+            None => {
+                self.messages.push((item_id.to_string(), String::new()));
+                self.messages.len() - 1
+            }
+        };
+        &mut self.messages[index].1
+    }
+
+    fn snapshot(&self) -> TurnResult {
+        let mut result = self.result.clone();
+        result.agent_messages = self
+            .messages
+            .iter()
+            .map(|(_, text)| text.clone())
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        result.text = result.agent_messages.last().cloned().unwrap_or_default();
+        result.duration_ms = self.started.elapsed().as_millis() as u64;
+        result
+    }
+
+    fn apply_item(&mut self, item: &Value) {
+        let Some(item_id) = str_field(item, &["id"]) else {
+            return;
+        };
+        let status = str_field(item, &["status"]);
+        match str_field(item, &["type"]).unwrap_or_default().as_str() {
+            "agentMessage" => {
+                if let Some(text) = str_field(item, &["text"]) {
+                    *self.message_mut(&item_id) = text;
+                } else {
+                    self.message_mut(&item_id);
+                }
+            }
+            "commandExecution" => self.upsert_tool_call(ToolCall {
+                item_id,
+                kind: "commandExecution".to_string(),
+                name: str_field(item, &["command"]),
+                status,
+                exit_code: item.get("exitCode").and_then(Value::as_i64),
+                output: str_field(item, &["aggregatedOutput", "output"]).map(truncate_output),
+            }),
+            "mcpToolCall" => {
+                let name = match (str_field(item, &["server"]), str_field(item, &["tool"])) {
+                    (Some(server), Some(tool)) => Some(format!("{server}/{tool}")),
+                    (server, tool) => tool.or(server),
+                };
+                let output = item
+                    .get("error")
+                    .filter(|error| !error.is_null())
+                    .or_else(|| item.get("result").filter(|result| !result.is_null()))
+                    .map(|value| truncate_output(value_text(value)));
+                self.upsert_tool_call(ToolCall {
+                    item_id,
+                    kind: "mcpToolCall".to_string(),
+                    name,
+                    status,
+                    exit_code: None,
+                    output,
+                });
+            }
+            "webSearch" => self.upsert_tool_call(ToolCall {
+                item_id,
+                kind: "webSearch".to_string(),
+                name: str_field(item, &["query"]),
+                status,
+                exit_code: None,
+                output: None,
+            }),
+            "fileChange" => {
+                let changes = item
+                    .get("changes")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default();
+                self.result
+                    .file_changes
+                    .retain(|change| change.item_id != item_id);
+                for change in changes {
+                    let Some(path) = str_field(&change, &["path"]) else {
+                        continue;
+                    };
+                    let kind = change.get("kind").and_then(|kind| match kind {
+                        Value::String(kind) => Some(kind.clone()),
+                        other => str_field(other, &["type"]),
+                    });
+                    self.result.file_changes.push(FileChange {
+                        item_id: item_id.clone(),
+                        path,
+                        kind,
+                        status: status.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Totals are cumulative for the thread, so the turn's share is measured
+    // from the total just before its first model call.
+    fn apply_usage(&mut self, last: TokenUsage, total: Option<TokenUsage>) {
+        match total {
+            Some(total) => {
+                let baseline = *self
+                    .usage_baseline
+                    .get_or_insert_with(|| total.saturating_sub(&last));
+                self.result.usage = Some(total.saturating_sub(&baseline));
+            }
+            None => self
+                .result
+                .usage
+                .get_or_insert_with(TokenUsage::default)
+                .add(&last),
+        }
+    }
+
+    fn upsert_tool_call(&mut self, call: ToolCall) {
+        match self
+            .result
+            .tool_calls
+            .iter_mut()
+            .find(|existing| existing.item_id == call.item_id)
+        {
+            Some(existing) => {
+                // Started items carry no output yet; keep whatever we already had.
+                existing.name = call.name.or(existing.name.take());
+                existing.status = call.status.or(existing.status.take());
+                existing.exit_code = call.exit_code.or(existing.exit_code);
+                existing.output = call.output.or(existing.output.take());
+            }
+            None => self.result.tool_calls.push(call),
+        }
+    }
+}
+
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn truncate_output(output: String) -> String {
+    if output.chars().count() <= MAX_TOOL_OUTPUT_CHARS {
+        return output;
+    }
+    let mut truncated = output
+        .chars()
+        .take(MAX_TOOL_OUTPUT_CHARS)
+        .collect::<String>();
+    truncated.push_str("\n…[truncated]");
+    truncated
+}
+
+fn error_message(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(message) => Some(message.clone()),
+        other => str_field(other, &["message"]).or_else(|| Some(other.to_string())),
+    }
+}
+
+fn completed_status(raw: Option<&str>) -> TurnStatus {
+    match raw.unwrap_or("completed") {
+        "interrupted" => TurnStatus::Interrupted,
+        "failed" | "error" => TurnStatus::Failed,
+        _ => TurnStatus::Completed,
+    }
+}
+
+// Folds the app-server's turn and item notifications into one result per
+// thread and hands finished results to whoever is waiting on that thread.
+#[derive(Default)]
+pub struct TurnTracker {
+    active: HashMap<String, ActiveTurn>,
+    waiters: HashMap<String, Vec<oneshot::Sender<TurnResult>>>,
+}
+
+impl TurnTracker {
+    // Subscribe before starting the turn so a fast completion is not missed.
+    pub fn subscribe(&mut self, thread_id: &str) -> oneshot::Receiver<TurnResult> {
+        let (tx, rx) = oneshot::channel();
+        let waiters = self.waiters.entry(thread_id.to_string()).or_default();
+        waiters.retain(|waiter| !waiter.is_closed());
+        waiters.push(tx);
+        rx
+    }
+
+    pub fn begin(&mut self, thread_id: &str, turn_id: Option<&str>) {
+        let turn = self
+            .active
+            .entry(thread_id.to_string())
+            .or_insert_with(|| ActiveTurn::new(thread_id, turn_id));
+        if turn.result.turn_id.is_none() {
+            turn.result.turn_id = turn_id.map(str::to_string);
+        }
+    }
+
+    pub fn turn_id(&self, thread_id: &str) -> Option<String> {
+        self.active
+            .get(thread_id)
+            .and_then(|turn| turn.result.turn_id.clone())
+    }
+
+    // Returns the finished result when the notification ends a turn.
+    pub fn apply(&mut self, method: &str, params: &Value) -> Option<TurnResult> {
+        let thread_id = str_field(params, &["threadId", "thread_id"])?;
+        let turn_id = str_field(params, &["turnId", "turn_id"])
+            .or_else(|| params.get("turn").and_then(|turn| str_field(turn, &["id"])));
+
+        match method {
+            "turn/started" => {
+                // A new turn on the thread replaces anything left over.
+                if self.active.get(&thread_id).is_some_and(|turn| {
+                    turn.result.turn_id.is_some() && turn.result.turn_id != turn_id
+                }) {
+                    self.active.remove(&thread_id);
+                }
+                self.begin(&thread_id, turn_id.as_deref());
+                None
+            }
+            "item/started" | "item/completed" => {
+                let item = params.get("item")?;
+                self.begin(&thread_id, turn_id.as_deref());
+                self.active.get_mut(&thread_id)?.apply_item(item);
+                None
+            }
+            "item/agentMessage/delta" => {
+                let item_id = str_field(params, &["itemId", "item_id"])?;
+                let delta = str_field(params, &["delta"])?;
+                self.begin(&thread_id, turn_id.as_deref());
+                self.active
+                    .get_mut(&thread_id)?
+                    .message_mut(&item_id)
+                    .push_str(&delta);
+                None
+            }
+            "thread/tokenUsage/updated" => {
+                let (last, total) = parse_token_usage_update(params)?;
+                self.begin(&thread_id, turn_id.as_deref());
+                self.active.get_mut(&thread_id)?.apply_usage(last, total);
+                None
+            }
+            "error" => {
+                // Retried errors may still end in a completed turn.
+                if params.get("willRetry").and_then(Value::as_bool) == Some(true) {
+                    return None;
+                }
+                let message = params.get("error").and_then(error_message)?;
+                self.begin(&thread_id, turn_id.as_deref());
+                self.active.get_mut(&thread_id)?.result.error = Some(message);
+                None
+            }
+            "turn/completed" | "turn/failed" => {
+                let turn = params.get("turn");
+                let status = if method == "turn/failed" {
+                    TurnStatus::Failed
+                } else {
+                    completed_status(
+                        turn.and_then(|turn| turn.get("status"))
+                            .and_then(Value::as_str),
+                    )
+                };
+                let error = turn
+                    .and_then(|turn| turn.get("error"))
+                    .or_else(|| params.get("error"))
+                    .and_then(error_message);
+                self.begin(&thread_id, turn_id.as_deref());
+                self.finish(&thread_id, status, error)
+            }
+            _ => None,
+        }
+    }
+
+    // Ends the thread's turn locally (completion, cancellation or timeout) and
+    // resolves its waiters with what has been accumulated so far.
+    pub fn finish(
+        &mut self,
+        thread_id: &str,
+        status: TurnStatus,
+        error: Option<String>,
+    ) -> Option<TurnResult> {
+        let turn = self.active.remove(thread_id)?;
+        let mut result = turn.snapshot();
+        result.status = status;
+        if error.is_some() {
+            result.error = error;
+        }
+        result.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        for waiter in self.waiters.remove(thread_id).unwrap_or_default() {
+            let _ = waiter.send(result.clone());
+        }
+        Some(result)
+    }
+
+    // Used when the app-server goes away mid-turn.
+    pub fn fail_all(&mut self, reason: &str) -> Vec<TurnResult> {
+        let thread_ids = self
+            .active
+            .keys()
+            .chain(self.waiters.keys())
+            .cloned()
+            .collect::<std::collections::BTreeSet<_>>();
+        thread_ids
+            .into_iter()
+            .filter_map(|thread_id| {
+                self.begin(&thread_id, None);
+                self.finish(&thread_id, TurnStatus::Failed, Some(reason.to_string()))
+            })
+            .collect()
+    }
+
+    pub fn snapshot(&self, thread_id: &str) -> Option<TurnResult> {
+        self.active.get(thread_id).map(ActiveTurn::snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn aggregates_a_turn_and_resolves_waiters() {
+        let mut tracker = TurnTracker::default();
+        let mut waiter = tracker.subscribe("th1");
+
+        let events = [
+            (
+                "turn/started",
+                json!({ "threadId": "th1", "turn": { "id": "t1" } }),
+            ),
+            (
+                "item/started",
+                json!({ "threadId": "th1", "turnId": "t1", "item": {
+                    "type": "commandExecution", "id": "c1", "command": "ls", "status": "inProgress"
+                } }),
+            ),
+            (
+                "item/completed",
+                json!({ "threadId": "th1", "turnId": "t1", "item": {
+                    "type": "commandExecution", "id": "c1", "status": "completed",
+                    "exitCode": 0, "aggregatedOutput": "a.txt\n"
+                } }),
+            ),
+            (
+                "item/completed",
+                json!({ "threadId": "th1", "turnId": "t1", "item": {
+                    "type": "fileChange", "id": "f1", "status": "completed",
+                    "changes": [{ "path": "a.txt", "kind": { "type": "update" } }]
+                } }),
+            ),
+            (
+                "item/agentMessage/delta",
+                json!({ "threadId": "th1", "turnId": "t1", "itemId": "m1", "delta": "Do" }),
+            ),
+            (
+                "item/agentMessage/delta",
+                json!({ "threadId": "th1", "turnId": "t1", "itemId": "m1", "delta": "ne" }),
+            ),
+            (
+                "thread/tokenUsage/updated",
+                json!({ "threadId": "th1", "turnId": "t1", "tokenUsage": {
+                    "last": { "inputTokens": 100, "outputTokens": 20, "totalTokens": 120 },
+                    "total": { "inputTokens": 600, "outputTokens": 70, "totalTokens": 670 }
+                } }),
+            ),
+            (
+                "thread/tokenUsage/updated",
+                json!({ "threadId": "th1", "turnId": "t1", "tokenUsage": {
+                    "last": { "inputTokens": 150, "outputTokens": 30, "totalTokens": 180 },
+                    "total": { "inputTokens": 750, "outputTokens": 100, "totalTokens": 850 }
+                } }),
+            ),
+        ];
+        for (method, params) in &events {
+            assert!(tracker.apply(method, params).is_none());
+        }
+        assert_eq!(tracker.snapshot("th1").unwrap().text, "Done");
+        assert!(waiter.try_recv().is_err());
+
+        let result = tracker
+            .apply(
+                "turn/completed",
+                &json!({ "threadId": "th1", "turn": { "id": "t1", "status": "completed" } }),
+            )
+            .unwrap();
+        assert_eq!(result.status, TurnStatus::Completed);
+        assert_eq!(result.turn_id.as_deref(), Some("t1"));
+        assert_eq!(result.text, "Done");
+        assert_eq!(result.tool_calls.len(), 1);
+        assert_eq!(result.tool_calls[0].name.as_deref(), Some("ls"));
+        assert_eq!(result.tool_calls[0].exit_code, Some(0));
+        assert_eq!(result.file_changes[0].path, "a.txt");
+        assert_eq!(result.file_changes[0].kind.as_deref(), Some("update"));
+        let usage = result.usage.unwrap();
+        assert_eq!(
+            (usage.input_tokens, usage.output_tokens, usage.total_tokens),
+            (250, 50, 300)
+        );
+        assert_eq!(waiter.try_recv().unwrap().text, "Done");
+        assert!(tracker.snapshot("th1").is_none());
+
+        // A waiter with no turn in flight still hears about a dead app-server.
+        let mut orphan = tracker.subscribe("th2");
+        assert_eq!(tracker.fail_all("engine stopped").len(), 1);
+        let failed = orphan.try_recv().unwrap();
+        assert_eq!(failed.status, TurnStatus::Failed);
+        assert_eq!(failed.error.as_deref(), Some("engine stopped"));
+    }
+}
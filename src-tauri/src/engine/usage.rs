@@ -0,0 +1,496 @@
+use super::turns::TurnStatus;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+pub const USAGE_SETTINGS_NAME: &str = "engine_usage.json";
+const LEDGER_PATH: &str = ".rail/usage/ledger.jsonl";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+fn u64_field(value: &Value, keys: &[&str]) -> Option<u64> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_u64))
+}
+
+fn f64_field(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_f64))
+}
+
+fn object_field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter()
+        .find_map(|key| value.get(*key).filter(|field| field.is_object()))
+}
+
+impl TokenUsage {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let input_tokens = u64_field(value, &["inputTokens", "input_tokens"]);
+        let output_tokens = u64_field(value, &["outputTokens", "output_tokens"]);
+        let total_tokens = u64_field(value, &["totalTokens", "total_tokens"]);
+        if input_tokens.is_none() && output_tokens.is_none() && total_tokens.is_none() {
+            return None;
+        }
+        let input_tokens = input_tokens.unwrap_or(0);
+        let output_tokens = output_tokens.unwrap_or(0);
+        Some(Self {
+            input_tokens,
+            cached_input_tokens: u64_field(value, &["cachedInputTokens", "cached_input_tokens"])
+                .unwrap_or(0),
+            output_tokens,
+            reasoning_output_tokens: u64_field(
+                value,
+                &["reasoningOutputTokens", "reasoning_output_tokens"],
+            )
+            .unwrap_or(0),
+            total_tokens: total_tokens.unwrap_or(input_tokens + output_tokens),
+        })
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_output_tokens += other.reasoning_output_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+
+    pub fn saturating_sub(&self, other: &TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            cached_input_tokens: self
+                .cached_input_tokens
+                .saturating_sub(other.cached_input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            reasoning_output_tokens: self
+                .reasoning_output_tokens
+                .saturating_sub(other.reasoning_output_tokens),
+            total_tokens: self.total_tokens.saturating_sub(other.total_tokens),
+        }
+    }
+}
+
+// `thread/tokenUsage/updated` carries the latest model call (`last`) and the
+// thread's running total; older servers nest the same under `info`.
+pub fn parse_token_usage_update(params: &Value) -> Option<(TokenUsage, Option<TokenUsage>)> {
+    let usage = object_field(params, &["tokenUsage", "token_usage", "info"]).unwrap_or(params);
+    let last = object_field(usage, &["last", "lastTokenUsage", "last_token_usage"])
+        .and_then(TokenUsage::from_value);
+    let total = object_field(usage, &["total", "totalTokenUsage", "total_token_usage"])
+        .and_then(TokenUsage::from_value);
+    if last.is_none() && total.is_none() {
+        return None;
+    }
+    Some((last.unwrap_or_default(), total))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitWindow {
+    pub used_percent: f64,
+    pub window_minutes: Option<u64>,
+    pub resets_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitSnapshot {
+    pub primary: Option<RateLimitWindow>,
+    pub secondary: Option<RateLimitWindow>,
+    pub observed_at: String,
+}
+
+impl RateLimitSnapshot {
+    pub fn windows(&self) -> impl Iterator<Item = (&'static str, &RateLimitWindow)> {
+        [("primary", &self.primary), ("secondary", &self.secondary)]
+            .into_iter()
+            .filter_map(|(name, window)| window.as_ref().map(|window| (name, window)))
+    }
+}
+
+fn parse_window(value: &Value) -> Option<RateLimitWindow> {
+    let resets_at = match value.get("resetsAt").or_else(|| value.get("resets_at")) {
+        Some(Value::Number(seconds)) => seconds
+            .as_i64()
+            .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+            .map(|at| at.to_rfc3339()),
+        Some(Value::String(at)) => Some(at.clone()),
+        _ => u64_field(value, &["resetsInSeconds", "resets_in_seconds"]).map(|seconds| {
+            (chrono::Utc::now() + chrono::Duration::seconds(seconds as i64)).to_rfc3339()
+        }),
+    };
+    Some(RateLimitWindow {
+        used_percent: f64_field(value, &["usedPercent", "used_percent"])?,
+        window_minutes: u64_field(
+            value,
+            &["windowDurationMins", "windowMinutes", "window_minutes"],
+        ),
+        resets_at,
+    })
+}
+
+// Accepts the `account/rateLimits/read` result, the `account/rateLimits/updated`
+// notification and the snake_case shape older servers report.
+pub fn parse_rate_limits(value: &Value) -> Option<RateLimitSnapshot> {
+    let limits = object_field(value, &["rateLimits", "rate_limits"]).unwrap_or(value);
+    let primary = limits.get("primary").and_then(parse_window);
+    let secondary = limits.get("secondary").and_then(parse_window);
+    if primary.is_none() && secondary.is_none() {
+        return None;
+    }
+    Some(RateLimitSnapshot {
+        primary,
+        secondary,
+        observed_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UsageConfig {
+    // Percentages of a rate-limit window that raise an event when crossed.
+    pub thresholds: Vec<f64>,
+    // 0 turns background polling off.
+    pub poll_interval_secs: u64,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: vec![80.0, 95.0],
+            poll_interval_secs: 300,
+        }
+    }
+}
+
+impl UsageConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(threshold) = self
+            .thresholds
+            .iter()
+            .find(|threshold| !(0.0..=100.0).contains(*threshold))
+        {
+            return Err(format!(
+                "usage threshold must be between 0 and 100: {threshold}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThresholdCrossing {
+    pub window: String,
+    pub threshold: f64,
+    pub used_percent: f64,
+    pub window_minutes: Option<u64>,
+    pub resets_at: Option<String>,
+}
+
+// Reports each threshold once per window until usage falls back below it,
+// so a poll every few minutes does not repeat the same warning.
+#[derive(Debug, Default)]
+pub struct ThresholdTracker {
+    reported: HashMap<String, f64>,
+}
+
+impl ThresholdTracker {
+    pub fn check(
+        &mut self,
+        thresholds: &[f64],
+        snapshot: &RateLimitSnapshot,
+    ) -> Vec<ThresholdCrossing> {
+        let mut crossings = Vec::new();
+        for (name, window) in snapshot.windows() {
+            let reached = thresholds
+                .iter()
+                .copied()
+                .filter(|threshold| window.used_percent >= *threshold)
+                .reduce(f64::max);
+            let Some(reached) = reached else {
+                self.reported.remove(name);
+                continue;
+            };
+            let previous = self.reported.insert(name.to_string(), reached);
+            if previous.is_some_and(|previous| previous >= reached) {
+                continue;
+            }
+            crossings.push(ThresholdCrossing {
+                window: name.to_string(),
+                threshold: reached,
+                used_percent: window.used_percent,
+                window_minutes: window.window_minutes,
+                resets_at: window.resets_at.clone(),
+            });
+        }
+        crossings
+    }
+}
+
+// Caller-supplied attribution so usage can be traced back to what spent it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UsageScope {
+    pub graph_id: Option<String>,
+    pub run_id: Option<String>,
+    pub node_id: Option<String>,
+}
+
+impl UsageScope {
+    // Fields set here win; the rest come from `fallback`.
+    pub fn or(self, fallback: &UsageScope) -> UsageScope {
+        UsageScope {
+            graph_id: self.graph_id.or_else(|| fallback.graph_id.clone()),
+            run_id: self.run_id.or_else(|| fallback.run_id.clone()),
+            node_id: self.node_id.or_else(|| fallback.node_id.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub at: String,
+    pub runtime_id: String,
+    pub thread_id: String,
+    pub turn_id: Option<String>,
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub scope: UsageScope,
+    pub status: TurnStatus,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LedgerFilter {
+    pub graph_id: Option<String>,
+    pub run_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub model: Option<String>,
+    // RFC 3339; entries before it are skipped.
+    pub since: Option<String>,
+}
+
+impl LedgerFilter {
+    fn matches(&self, entry: &LedgerEntry) -> bool {
+        let field =
+            |wanted: &Option<String>, actual: &Option<String>| wanted.is_none() || wanted == actual;
+        field(&self.graph_id, &entry.scope.graph_id)
+            && field(&self.run_id, &entry.scope.run_id)
+            && field(&self.model, &entry.model)
+            && self
+                .thread_id
+                .as_ref()
+                .is_none_or(|thread_id| *thread_id == entry.thread_id)
+            && self.since.as_ref().is_none_or(|since| {
+                match (
+                    chrono::DateTime::parse_from_rfc3339(since),
+                    chrono::DateTime::parse_from_rfc3339(&entry.at),
+                ) {
+                    (Ok(since), Ok(at)) => at >= since,
+                    _ => entry.at.as_str() >= since.as_str(),
+                }
+            })
+    }
+}
+
+pub fn ledger_path(workspace: &Path) -> PathBuf {
+    workspace.join(LEDGER_PATH)
+}
+
+pub fn append_ledger(workspace: &Path, entry: &LedgerEntry) -> Result<(), String> {
+    let path = ledger_path(workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create usage ledger directory: {e}"))?;
+    }
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("failed to serialize usage ledger entry: {e}"))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("failed to append usage ledger entry: {e}"))
+}
+
+// Oldest first; unreadable lines are skipped rather than failing the read.
+pub fn read_ledger(workspace: &Path, filter: &LedgerFilter) -> Result<Vec<LedgerEntry>, String> {
+    let path = ledger_path(workspace);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| format!("failed to read usage ledger: {e}"))?;
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str::<LedgerEntry>(line).ok())
+        .filter(|entry| filter.matches(entry))
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageGroup {
+    Graph,
+    Run,
+    Model,
+    Thread,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    // None collects turns that were not attributed to anything.
+    pub key: Option<String>,
+    pub turns: u64,
+    pub usage: TokenUsage,
+    pub first_at: String,
+    pub last_at: String,
+}
+
+// Heaviest first.
+pub fn summarize(entries: &[LedgerEntry], group: UsageGroup) -> Vec<UsageSummary> {
+    let mut groups: BTreeMap<Option<String>, UsageSummary> = BTreeMap::new();
+    for entry in entries {
+        let key = match group {
+            UsageGroup::Graph => entry.scope.graph_id.clone(),
+            UsageGroup::Run => entry.scope.run_id.clone(),
+            UsageGroup::Model => entry.model.clone(),
+            UsageGroup::Thread => Some(entry.thread_id.clone()),
+        };
+        let summary = groups.entry(key.clone()).or_insert_with(|| UsageSummary {
+            key,
+            turns: 0,
+            usage: TokenUsage::default(),
+            first_at: entry.at.clone(),
+            last_at: entry.at.clone(),
+        });
+        summary.turns += 1;
+        summary.usage.add(&entry.usage);
+        if entry.at < summary.first_at {
+            summary.first_at = entry.at.clone();
+        }
+        if entry.at > summary.last_at {
+            summary.last_at = entry.at.clone();
+        }
+    }
+    let mut summaries = groups.into_values().collect::<Vec<_>>();
+    summaries.sort_by_key(|summary| std::cmp::Reverse(summary.usage.total_tokens));
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(graph_id: Option<&str>, total_tokens: u64) -> LedgerEntry {
+        LedgerEntry {
+            at: "2026-01-01T00:00:00+00:00".to_string(),
+            runtime_id: "rt".to_string(),
+            thread_id: "th1".to_string(),
+            turn_id: None,
+            model: Some("gpt-5".to_string()),
+            scope: UsageScope {
+                graph_id: graph_id.map(str::to_string),
+                ..UsageScope::default()
+            },
+            status: TurnStatus::Completed,
+            usage: TokenUsage {
+                total_tokens,
+                ..TokenUsage::default()
+            },
+        }
+    }
+
+    #[test]
+    fn parses_usage_and_reports_thresholds_once() {
+        let (last, total) = parse_token_usage_update(&json!({
+            "threadId": "th1",
+            "tokenUsage": {
+                "last": { "inputTokens": 10, "cachedInputTokens": 4, "outputTokens": 5, "totalTokens": 15 },
+                "total": { "inputTokens": 30, "outputTokens": 12, "totalTokens": 42 }
+            }
+        }))
+        .expect("token usage");
+        assert_eq!(last.cached_input_tokens, 4);
+        assert_eq!(total.map(|total| total.total_tokens), Some(42));
+
+        let limits = |primary: f64| {
+            parse_rate_limits(&json!({
+                "rateLimits": {
+                    "primary": { "usedPercent": primary, "windowDurationMins": 300, "resetsAt": 1767225600 },
+                    "secondary": { "used_percent": 10.0, "window_minutes": 10080 }
+                }
+            }))
+            .expect("rate limits")
+        };
+        let snapshot = limits(85.0);
+        let primary = snapshot.primary.as_ref().expect("primary window");
+        assert_eq!(primary.window_minutes, Some(300));
+        assert_eq!(
+            primary.resets_at.as_deref(),
+            Some("2026-01-01T00:00:00+00:00")
+        );
+
+        let thresholds = UsageConfig::default().thresholds;
+        let mut tracker = ThresholdTracker::default();
+        let crossed = tracker.check(&thresholds, &snapshot);
+        assert_eq!(crossed.len(), 1);
+        assert_eq!(
+            (crossed[0].window.as_str(), crossed[0].threshold),
+            ("primary", 80.0)
+        );
+        assert!(tracker.check(&thresholds, &limits(90.0)).is_empty());
+        assert_eq!(tracker.check(&thresholds, &limits(96.0))[0].threshold, 95.0);
+        // The window reset, so the next climb is reported again.
+        assert!(tracker.check(&thresholds, &limits(5.0)).is_empty());
+        assert_eq!(tracker.check(&thresholds, &limits(81.0)).len(), 1);
+
+        let entries = [
+            entry(Some("g1"), 100),
+            entry(None, 40),
+            entry(Some("g2"), 500),
+            entry(Some("g1"), 450),
+        ];
+        let summaries = summarize(&entries, UsageGroup::Graph);
+        let keys = summaries
+            .iter()
+            .map(|summary| {
+                (
+                    summary.key.as_deref(),
+                    summary.turns,
+                    summary.usage.total_tokens,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [(Some("g1"), 2, 550), (Some("g2"), 1, 500), (None, 1, 40)]
+        );
+        let filter = LedgerFilter {
+            graph_id: Some("g1".to_string()),
+            ..LedgerFilter::default()
+        };
+        assert_eq!(
+            entries.iter().filter(|entry| filter.matches(entry)).count(),
+            2
+        );
+    }
+}
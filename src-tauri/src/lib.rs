@@ -5,6 +5,11 @@ mod quality;
 mod storage;
 mod system;
 mod via_bridge;
+
+// Only for the integration tests in tests/.
+#[cfg(feature = "test-support")]
+#[doc(hidden)]
+pub use engine::testing as engine_testing;
 use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -14,18 +19,63 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .manage(engine::EngineManager::default())
         .manage(system::WorkspaceTerminalManager::default())
+        .manage(storage::StorageRecoveryState::default())
+        .manage(storage::StorageWatcherState::default())
+        .setup(|app| {
+            // Recovery reads every stored file, so it must not hold up the window.
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                match storage::recover_storage(&handle) {
+                    Ok(report) => {
+                        for row in &report.quarantined {
+                            eprintln!(
+                                "quarantined corrupt storage file {} -> {} ({})",
+                                row.original_path, row.quarantine_path, row.reason
+                            );
+                        }
+                    }
+                    Err(error) => eprintln!("storage recovery pass failed: {error}"),
+                }
+            });
+            if let Err(error) = storage::start_storage_watch(app.handle(), None) {
+                eprintln!("storage watcher failed to start: {error}");
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             engine::engine_start,
             engine::engine_stop,
+            engine::engine_list,
+            engine::engine_policy_get,
+            engine::engine_policy_set,
+            engine::engine_recording_start,
+            engine::engine_recording_stop,
+            engine::engine_recording_list,
+            engine::approval_rules_get,
+            engine::approval_rules_set,
+            engine::approval_audit_list,
             engine::login_chatgpt,
             engine::logout_codex,
             engine::auth_probe,
             engine::agent_rules_read,
             engine::usage_check,
+            engine::usage_config_get,
+            engine::usage_config_set,
+            engine::usage_ledger_list,
+            engine::usage_ledger_summary,
+            engine::rate_limits_get,
             engine::thread_start,
+            engine::thread_list,
+            engine::thread_resume,
+            engine::thread_archive,
+            engine::thread_history,
+            engine::thread_export_markdown,
             engine::turn_start,
             engine::turn_start_blocking,
             engine::turn_interrupt,
+            engine::turn_cancel,
+            engine::turn_result_get,
+            engine::engine_request_cancel,
             engine::approval_respond,
             engine::provider_window_open,
             engine::provider_window_close,
@@ -62,18 +112,46 @@ pub fn run() {
             quality::quality_run_checks,
             storage::graph_list,
             storage::graph_save,
+            storage::graph_validate,
             storage::graph_load,
             storage::graph_delete,
             storage::graph_rename,
+            storage::graph_list_meta,
+            storage::graph_meta_update,
+            storage::graph_revision_list,
+            storage::graph_revision_diff,
+            storage::graph_revision_restore,
+            storage::graph_export_bundle,
+            storage::graph_import_bundle,
             storage::run_save,
             storage::run_list,
             storage::run_load,
             storage::run_delete,
+            storage::run_query,
+            storage::run_diff,
+            storage::run_artifact_add,
+            storage::run_artifact_list,
+            storage::run_artifact_read,
+            storage::run_export_report,
+            storage::trash_list,
+            storage::trash_restore,
+            storage::trash_empty,
+            storage::storage_search,
+            storage::storage_watch_start,
+            storage::storage_watch_stop,
+            storage::storage_watch_status,
             storage::run_directory,
+            storage::workspace_root_list,
+            storage::workspace_root_adopt_legacy,
+            storage::workspace_root_remove,
             storage::workspace_write_markdown,
             storage::workspace_write_text,
             storage::workspace_read_text,
             storage::workspace_delete_file,
+            storage::storage_recovery_report,
+            storage::storage_retention_get,
+            storage::storage_retention_set,
+            storage::storage_gc,
             storage::dialog_pick_directory,
             storage::dialog_pick_knowledge_files,
             system::task_terminal_exec,
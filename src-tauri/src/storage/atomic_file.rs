@@ -0,0 +1,115 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const TEMP_FILE_EXTENSION: &str = "tmp";
+
+fn temp_path_for(target: &Path) -> io::Result<PathBuf> {
+    let parent = target
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "target has no parent"))?;
+    let file_name = target
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "target has no file name"))?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    Ok(parent.join(format!(
+        ".{file_name}.{}.{nanos}.{TEMP_FILE_EXTENSION}",
+        std::process::id()
+    )))
+}
+
+fn sync_parent_dir(target: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        if let Some(parent) = target.parent() {
+            File::open(parent)?.sync_all()?;
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = target;
+    }
+    Ok(())
+}
+
+// Readers only ever observe the old or the new content: temp file, fsync, rename.
+pub fn write_atomic(target: &Path, bytes: &[u8]) -> io::Result<()> {
+    super::watcher::note_self_write(target);
+    let temp_path = temp_path_for(target)?;
+    let result = (|| -> io::Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, target)?;
+        sync_parent_dir(target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+pub fn is_temp_file(path: &Path) -> bool {
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    file_name.starts_with('.')
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext == TEMP_FILE_EXTENSION)
+            .unwrap_or(false)
+}
+
+// Temp files carry the writer's pid; ones from this process may still be
+// mid-write and must not be cleaned up under it.
+pub fn is_own_temp_file(path: &Path) -> bool {
+    let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+        return false;
+    };
+    let mut parts = stem.rsplitn(3, '.');
+    let _nanos = parts.next();
+    is_temp_file(path) && parts.next() == Some(std::process::id().to_string().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_replaces_content_without_leftovers() {
+        let root = std::env::temp_dir().join(format!(
+            "rail_atomic_write_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_nanos())
+                .unwrap_or(0)
+        ));
+        fs::create_dir_all(&root).expect("create temp dir");
+        let target = root.join("graph.json");
+
+        write_atomic(&target, b"{\"version\":1}").expect("first write");
+        write_atomic(&target, b"{\"version\":2}").expect("second write");
+
+        assert_eq!(
+            fs::read_to_string(&target).expect("read target"),
+            "{\"version\":2}"
+        );
+        let leftovers = fs::read_dir(&root)
+            .expect("read temp dir")
+            .filter_map(Result::ok)
+            .filter(|entry| is_temp_file(&entry.path()))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,567 @@
+use super::graph_schema::{migrate_graph, GraphDocument, GRAPH_SCHEMA_VERSION};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    io::{Cursor, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+pub const BUNDLE_FORMAT: &str = "rail-graph-bundle";
+pub const BUNDLE_VERSION: u64 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const GRAPH_ENTRY: &str = "graph.json";
+const KNOWLEDGE_PREFIX: &str = "knowledge";
+const RULES_PREFIX: &str = "rules";
+const IMPORTED_KNOWLEDGE_DIR: &str = ".rail/knowledge";
+const MAX_BUNDLE_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleKnowledgeEntry {
+    pub id: String,
+    pub name: String,
+    pub bundle_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRuleEntry {
+    pub cwd: String,
+    pub relative_path: String,
+    pub bundle_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format: String,
+    pub version: u64,
+    pub graph_name: String,
+    pub graph_schema_version: u64,
+    pub exported_at: String,
+    #[serde(default)]
+    pub knowledge: Vec<BundleKnowledgeEntry>,
+    #[serde(default)]
+    pub rules: Vec<BundleRuleEntry>,
+    #[serde(default)]
+    pub allowlist_by_topic: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+pub struct BundleExportInput<'a> {
+    pub graph_name: &'a str,
+    pub document: GraphDocument,
+    pub workspace: &'a Path,
+    // Resolved node working directories paired with the rule files found there.
+    pub rule_sets: Vec<(PathBuf, Vec<PathBuf>)>,
+    pub allowlist_by_topic: Option<BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleConflictPolicy {
+    Rename,
+    Overwrite,
+    Skip,
+}
+
+impl BundleConflictPolicy {
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(str::trim).unwrap_or("rename") {
+            "" | "rename" => Ok(Self::Rename),
+            "overwrite" => Ok(Self::Overwrite),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!("unsupported conflict policy: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleInstallReport {
+    pub written: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub renamed: Vec<BundleRenamedFile>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRenamedFile {
+    pub requested_path: String,
+    pub written_path: String,
+}
+
+pub struct BundleContents {
+    pub manifest: BundleManifest,
+    pub document: GraphDocument,
+    files: HashMap<String, Vec<u8>>,
+}
+
+fn safe_relative_path(raw: &str) -> Option<PathBuf> {
+    let path = Path::new(raw.trim());
+    if raw.trim().is_empty() {
+        return None;
+    }
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+fn bundle_path_string(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sanitize_bundle_file_name(raw: &str) -> String {
+    let cleaned = raw
+        .chars()
+        .map(|ch| match ch {
+            '/' | '\\' | ':' | '\0' => '_',
+            _ => ch,
+        })
+        .collect::<String>();
+    let trimmed = cleaned.trim().trim_start_matches('.');
+    if trimmed.is_empty() {
+        "file".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn relative_cwd(workspace: &Path, cwd: &Path) -> Option<String> {
+    let relative = cwd.strip_prefix(workspace).ok()?;
+    let value = bundle_path_string(relative);
+    Some(if value.is_empty() {
+        ".".to_string()
+    } else {
+        value
+    })
+}
+
+pub fn write_bundle(input: BundleExportInput<'_>) -> Result<(Vec<u8>, BundleManifest), String> {
+    let mut document = input.document;
+    let mut manifest = BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        graph_name: input.graph_name.to_string(),
+        graph_schema_version: GRAPH_SCHEMA_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        knowledge: Vec::new(),
+        rules: Vec::new(),
+        allowlist_by_topic: input.allowlist_by_topic,
+        warnings: Vec::new(),
+    };
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+
+    for file in &mut document.knowledge.files {
+        let source = PathBuf::from(file.path.trim());
+        let bytes = match fs::read(&source) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                manifest.warnings.push(format!(
+                    "knowledge file {} was not bundled: {error}",
+                    source.display()
+                ));
+                continue;
+            }
+        };
+        let file_name = source
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(file.name.as_str());
+        let bundle_path = format!(
+            "{KNOWLEDGE_PREFIX}/{}-{}",
+            sanitize_bundle_file_name(&file.id),
+            sanitize_bundle_file_name(file_name)
+        );
+        manifest.knowledge.push(BundleKnowledgeEntry {
+            id: file.id.clone(),
+            name: file.name.clone(),
+            bundle_path: bundle_path.clone(),
+        });
+        file.path = bundle_path.clone();
+        files.push((bundle_path, bytes));
+    }
+
+    for node in &mut document.nodes {
+        let Some(Value::String(cwd)) = node.config.get("cwd") else {
+            continue;
+        };
+        let path = PathBuf::from(cwd.trim());
+        if !path.is_absolute() {
+            continue;
+        }
+        match relative_cwd(input.workspace, &path) {
+            Some(relative) => {
+                node.config
+                    .insert("cwd".to_string(), Value::String(relative));
+            }
+            None => manifest.warnings.push(format!(
+                "node {} uses a working directory outside the workspace: {}",
+                node.id,
+                path.display()
+            )),
+        }
+    }
+
+    let mut seen_rules = BTreeSet::new();
+    for (cwd, paths) in input.rule_sets {
+        let Some(cwd_key) = relative_cwd(input.workspace, &cwd) else {
+            manifest.warnings.push(format!(
+                "agent rules outside the workspace were not bundled: {}",
+                cwd.display()
+            ));
+            continue;
+        };
+        for path in paths {
+            let Ok(relative) = path.strip_prefix(&cwd) else {
+                continue;
+            };
+            let relative_path = bundle_path_string(relative);
+            let bundle_path = if cwd_key == "." {
+                format!("{RULES_PREFIX}/{relative_path}")
+            } else {
+                format!("{RULES_PREFIX}/{cwd_key}/{relative_path}")
+            };
+            if !seen_rules.insert(bundle_path.to_lowercase()) {
+                continue;
+            }
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    manifest.warnings.push(format!(
+                        "agent rule {} was not bundled: {error}",
+                        path.display()
+                    ));
+                    continue;
+                }
+            };
+            manifest.rules.push(BundleRuleEntry {
+                cwd: cwd_key.clone(),
+                relative_path,
+                bundle_path: bundle_path.clone(),
+            });
+            files.push((bundle_path, bytes));
+        }
+    }
+
+    let graph_bytes = serde_json::to_vec_pretty(&document)
+        .map_err(|e| format!("failed to serialize bundle graph: {e}"))?;
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("failed to serialize bundle manifest: {e}"))?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, bytes) in [
+        (MANIFEST_ENTRY.to_string(), manifest_bytes),
+        (GRAPH_ENTRY.to_string(), graph_bytes),
+    ]
+    .into_iter()
+    .chain(files)
+    {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("failed to add {name} to bundle: {e}"))?;
+        writer
+            .write_all(&bytes)
+            .map_err(|e| format!("failed to write {name} to bundle: {e}"))?;
+    }
+    let cursor = writer
+        .finish()
+        .map_err(|e| format!("failed to finish bundle: {e}"))?;
+    Ok((cursor.into_inner(), manifest))
+}
+
+// The declared size in the archive is not trusted; reading one byte past the
+// limit shows whether the entry really fits.
+fn read_limited(reader: impl Read, limit: u64, name: &str) -> Result<Vec<u8>, String> {
+    let mut content = Vec::new();
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut content)
+        .map_err(|e| format!("failed to read bundle entry {name}: {e}"))?;
+    if content.len() as u64 > limit {
+        return Err(format!("bundle entry is too large: {name}"));
+    }
+    Ok(content)
+}
+
+pub fn read_bundle(bytes: &[u8]) -> Result<BundleContents, String> {
+    let mut archive =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("invalid bundle archive: {e}"))?;
+    let mut files = HashMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| format!("failed to read bundle entry: {e}"))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        if safe_relative_path(&name).is_none() {
+            return Err(format!("bundle entry has an unsafe path: {name}"));
+        }
+        if entry.size() > MAX_BUNDLE_ENTRY_BYTES {
+            return Err(format!("bundle entry is too large: {name}"));
+        }
+        let content = read_limited(&mut entry, MAX_BUNDLE_ENTRY_BYTES, &name)?;
+        files.insert(name, content);
+    }
+
+    let manifest_bytes = files
+        .remove(MANIFEST_ENTRY)
+        .ok_or_else(|| "bundle is missing manifest.json".to_string())?;
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("invalid bundle manifest: {e}"))?;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err(format!("unsupported bundle format: {}", manifest.format));
+    }
+    if manifest.version > BUNDLE_VERSION {
+        return Err(format!(
+            "bundle version {} is newer than supported version {BUNDLE_VERSION}",
+            manifest.version
+        ));
+    }
+
+    let graph_bytes = files
+        .remove(GRAPH_ENTRY)
+        .ok_or_else(|| "bundle is missing graph.json".to_string())?;
+    let raw: Value =
+        serde_json::from_slice(&graph_bytes).map_err(|e| format!("invalid bundle graph: {e}"))?;
+    let document = migrate_graph(raw)?.document;
+
+    Ok(BundleContents {
+        manifest,
+        document,
+        files,
+    })
+}
+
+fn renamed_target(target: &Path) -> PathBuf {
+    let parent = target.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = target
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or("file");
+    let ext = target.extension().and_then(|ext| ext.to_str());
+    let mut index = 2;
+    loop {
+        let file_name = match ext {
+            Some(ext) => format!("{stem}-{index}.{ext}"),
+            None => format!("{stem}-{index}"),
+        };
+        let candidate = parent.join(file_name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+// Returns the path the content now lives at, or the existing path when skipped.
+fn install_file(
+    target: &Path,
+    bytes: &[u8],
+    policy: BundleConflictPolicy,
+    report: &mut BundleInstallReport,
+) -> Result<PathBuf, String> {
+    let display = target.to_string_lossy().to_string();
+    let mut destination = target.to_path_buf();
+    if target.exists() {
+        if fs::read(target).ok().as_deref() == Some(bytes) {
+            report.unchanged.push(display);
+            return Ok(destination);
+        }
+        match policy {
+            BundleConflictPolicy::Skip => {
+                report.skipped.push(display);
+                return Ok(destination);
+            }
+            BundleConflictPolicy::Overwrite => {}
+            BundleConflictPolicy::Rename => {
+                destination = renamed_target(target);
+                report.renamed.push(BundleRenamedFile {
+                    requested_path: display.clone(),
+                    written_path: destination.to_string_lossy().to_string(),
+                });
+            }
+        }
+    }
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    super::atomic_file::write_atomic(&destination, bytes)
+        .map_err(|e| format!("failed to write {}: {e}", destination.display()))?;
+    if destination == target {
+        report.written.push(display);
+    }
+    Ok(destination)
+}
+
+// Writes knowledge and rule files under the workspace and points the graph's
+// knowledge entries at their installed locations.
+pub fn install_bundle_files(
+    contents: &mut BundleContents,
+    workspace: &Path,
+    policy: BundleConflictPolicy,
+) -> Result<BundleInstallReport, String> {
+    let mut report = BundleInstallReport::default();
+    let knowledge_dir = workspace.join(IMPORTED_KNOWLEDGE_DIR);
+
+    for entry in &contents.manifest.knowledge {
+        let Some(bytes) = contents.files.get(&entry.bundle_path) else {
+            continue;
+        };
+        let Some(file_name) = safe_relative_path(&entry.bundle_path)
+            .and_then(|path| path.file_name().map(|name| name.to_os_string()))
+        else {
+            continue;
+        };
+        let installed = install_file(&knowledge_dir.join(file_name), bytes, policy, &mut report)?;
+        for file in &mut contents.document.knowledge.files {
+            if file.id == entry.id && file.path == entry.bundle_path {
+                file.path = installed.to_string_lossy().to_string();
+            }
+        }
+    }
+
+    for entry in &contents.manifest.rules {
+        let Some(bytes) = contents.files.get(&entry.bundle_path) else {
+            continue;
+        };
+        let (Some(cwd), Some(relative)) = (
+            safe_relative_path(&entry.cwd),
+            safe_relative_path(&entry.relative_path),
+        ) else {
+            return Err(format!(
+                "bundle rule has an unsafe path: {}",
+                entry.bundle_path
+            ));
+        };
+        install_file(
+            &workspace.join(cwd).join(relative),
+            bytes,
+            policy,
+            &mut report,
+        )?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bundle_round_trips_with_relative_paths_and_renames_conflicts() {
+        let root = std::env::temp_dir().join(format!(
+            "rail_graph_bundle_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let source = root.join("source");
+        let target = root.join("target");
+        fs::create_dir_all(source.join("agents/skills/review")).expect("create source");
+        fs::write(source.join("notes.md"), "# notes").expect("write knowledge");
+        fs::write(source.join("agents/AGENTS.md"), "be brief").expect("write rules");
+        fs::write(source.join("agents/skills/review/SKILL.md"), "review").expect("write skill");
+
+        let document = migrate_graph(json!({
+            "version": 3,
+            "nodes": [{
+                "id": "turn-1",
+                "type": "turn",
+                "position": { "x": 0, "y": 0 },
+                "config": { "cwd": source.join("agents").to_string_lossy() }
+            }],
+            "edges": [],
+            "knowledge": {
+                "files": [{
+                    "id": "k1",
+                    "name": "notes.md",
+                    "path": source.join("notes.md").to_string_lossy(),
+                    "ext": ".md"
+                }]
+            }
+        }))
+        .expect("valid graph")
+        .document;
+
+        let (bytes, manifest) = write_bundle(BundleExportInput {
+            graph_name: "shared",
+            document,
+            workspace: &source,
+            rule_sets: vec![(
+                source.join("agents"),
+                vec![
+                    source.join("agents/AGENTS.md"),
+                    source.join("agents/skills/review/SKILL.md"),
+                ],
+            )],
+            allowlist_by_topic: Some(BTreeMap::from([(
+                "globalHeadlines".to_string(),
+                vec!["apnews.com".to_string()],
+            )])),
+        })
+        .expect("export bundle");
+        assert!(manifest.warnings.is_empty());
+        assert_eq!(manifest.rules.len(), 2);
+
+        fs::create_dir_all(target.join(IMPORTED_KNOWLEDGE_DIR)).expect("create target");
+        fs::write(
+            target.join(IMPORTED_KNOWLEDGE_DIR).join("k1-notes.md"),
+            "local",
+        )
+        .expect("write conflicting knowledge");
+
+        let mut contents = read_bundle(&bytes).expect("read bundle");
+        assert_eq!(contents.document.nodes[0].config["cwd"], json!("agents"));
+        let report = install_bundle_files(&mut contents, &target, BundleConflictPolicy::Rename)
+            .expect("install bundle");
+
+        assert_eq!(report.renamed.len(), 1);
+        assert_eq!(report.written.len(), 2);
+        let installed = PathBuf::from(&contents.document.knowledge.files[0].path);
+        assert_eq!(
+            installed,
+            target.join(IMPORTED_KNOWLEDGE_DIR).join("k1-notes-2.md")
+        );
+        assert_eq!(fs::read_to_string(installed).expect("read"), "# notes");
+        assert!(target.join("agents/skills/review/SKILL.md").exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_unsafe_relative_paths() {
+        assert!(safe_relative_path("../escape.md").is_none());
+        assert!(safe_relative_path("/etc/passwd").is_none());
+        assert_eq!(
+            safe_relative_path("./skills/a/SKILL.md"),
+            Some(PathBuf::from("skills/a/SKILL.md"))
+        );
+    }
+
+    #[test]
+    fn rejects_entries_over_the_size_limit() {
+        assert_eq!(read_limited(&b"1234"[..], 4, "a").unwrap(), b"1234");
+        assert_eq!(
+            read_limited(&b"12345"[..], 4, "a").unwrap_err(),
+            "bundle entry is too large: a"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use super::atomic_file::write_atomic;
+use super::graph_schema::GraphDocument;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+pub const GRAPH_INDEX_DIR: &str = ".index";
+const GRAPH_INDEX_FILE: &str = "manifest.json";
+const GRAPH_INDEX_VERSION: u64 = 1;
+pub const GRAPH_META_KEY: &str = "meta";
+
+static GRAPH_INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphMetadata {
+    pub file_name: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+    pub mode: String,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub created_at: String,
+    pub updated_at: String,
+    pub last_run_status: Option<String>,
+    pub last_run_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphIndexManifest {
+    version: u64,
+    #[serde(default)]
+    entries: BTreeMap<String, GraphMetadata>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphMetaPatch {
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub folder: Option<String>,
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphListQuery {
+    pub tag: Option<String>,
+    pub folder: Option<String>,
+    pub mode: Option<String>,
+    pub text: Option<String>,
+    pub sort: Option<String>,
+}
+
+fn manifest_path(graphs_dir: &Path) -> PathBuf {
+    graphs_dir.join(GRAPH_INDEX_DIR).join(GRAPH_INDEX_FILE)
+}
+
+fn load_manifest(graphs_dir: &Path) -> GraphIndexManifest {
+    fs::read_to_string(manifest_path(graphs_dir))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<GraphIndexManifest>(&raw).ok())
+        .filter(|manifest| manifest.version == GRAPH_INDEX_VERSION)
+        .unwrap_or(GraphIndexManifest {
+            version: GRAPH_INDEX_VERSION,
+            entries: BTreeMap::new(),
+        })
+}
+
+fn save_manifest(graphs_dir: &Path, manifest: &GraphIndexManifest) -> Result<(), String> {
+    let path = manifest_path(graphs_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create graph index directory: {e}"))?;
+    }
+    let json = serde_json::to_string(manifest)
+        .map_err(|e| format!("failed to serialize graph index: {e}"))?;
+    write_atomic(&path, json.as_bytes()).map_err(|e| format!("failed to write graph index: {e}"))
+}
+
+fn with_manifest<T>(
+    graphs_dir: &Path,
+    update: impl FnOnce(&mut GraphIndexManifest) -> (T, bool),
+) -> Result<T, String> {
+    let _guard = GRAPH_INDEX_LOCK
+        .lock()
+        .map_err(|_| "graph index lock is poisoned".to_string())?;
+    let mut manifest = load_manifest(graphs_dir);
+    let (result, changed) = update(&mut manifest);
+    if changed {
+        save_manifest(graphs_dir, &manifest)?;
+    }
+    Ok(result)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn normalize_tags(tags: impl IntoIterator<Item = String>) -> Vec<String> {
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn normalize_folder(raw: Option<&str>) -> Option<String> {
+    let folder = raw?
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!folder.is_empty()).then_some(folder)
+}
+
+fn derive_mode(document: &GraphDocument) -> String {
+    let is_rag = document.nodes.iter().any(|node| {
+        node.node_type == "turn"
+            && node.config.get("executor").and_then(Value::as_str) == Some("via_flow")
+    });
+    if is_rag { "rag" } else { "dag" }.to_string()
+}
+
+pub fn graph_meta_object(document: &GraphDocument) -> Map<String, Value> {
+    document
+        .extra
+        .get(GRAPH_META_KEY)
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+// Merges the patch into the document's `meta` object so the metadata travels
+// with the graph file and can be rebuilt from it.
+pub fn apply_meta_patch(document: &mut GraphDocument, patch: &GraphMetaPatch) {
+    let mut meta = graph_meta_object(document);
+    if let Some(description) = &patch.description {
+        meta.insert(
+            "description".to_string(),
+            Value::String(description.trim().to_string()),
+        );
+    }
+    if let Some(tags) = &patch.tags {
+        meta.insert(
+            "tags".to_string(),
+            Value::from(normalize_tags(tags.iter().cloned())),
+        );
+    }
+    if let Some(folder) = &patch.folder {
+        meta.insert(
+            "folder".to_string(),
+            Value::String(normalize_folder(Some(folder)).unwrap_or_default()),
+        );
+    }
+    if let Some(mode) = &patch.mode {
+        meta.insert(
+            "mode".to_string(),
+            Value::String(mode.trim().to_lowercase()),
+        );
+    }
+    document
+        .extra
+        .insert(GRAPH_META_KEY.to_string(), Value::Object(meta));
+}
+
+pub fn summarize_graph(
+    file_name: &str,
+    document: &GraphDocument,
+    previous: Option<&GraphMetadata>,
+    now: &str,
+) -> GraphMetadata {
+    let meta = graph_meta_object(document);
+    let tags = meta
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|rows| normalize_tags(rows.iter().filter_map(Value::as_str).map(str::to_string)))
+        .unwrap_or_default();
+    let mode = non_empty(meta.get("mode").and_then(Value::as_str))
+        .filter(|mode| mode == "dag" || mode == "rag")
+        .unwrap_or_else(|| derive_mode(document));
+    GraphMetadata {
+        file_name: file_name.to_string(),
+        name: file_name.trim_end_matches(".json").to_string(),
+        description: non_empty(meta.get("description").and_then(Value::as_str)),
+        tags,
+        folder: normalize_folder(meta.get("folder").and_then(Value::as_str)),
+        mode,
+        node_count: document.nodes.len(),
+        edge_count: document.edges.len(),
+        created_at: previous
+            .map(|entry| entry.created_at.clone())
+            .or_else(|| non_empty(meta.get("createdAt").and_then(Value::as_str)))
+            .unwrap_or_else(|| now.to_string()),
+        updated_at: now.to_string(),
+        last_run_status: previous.and_then(|entry| entry.last_run_status.clone()),
+        last_run_at: previous.and_then(|entry| entry.last_run_at.clone()),
+    }
+}
+
+pub fn upsert_graph(
+    graphs_dir: &Path,
+    file_name: &str,
+    document: &GraphDocument,
+    now: &str,
+) -> Result<GraphMetadata, String> {
+    with_manifest(graphs_dir, |manifest| {
+        let entry = summarize_graph(file_name, document, manifest.entries.get(file_name), now);
+        manifest
+            .entries
+            .insert(file_name.to_string(), entry.clone());
+        (entry, true)
+    })
+}
+
+pub fn remove_graph(graphs_dir: &Path, file_name: &str) -> Result<(), String> {
+    with_manifest(graphs_dir, |manifest| {
+        ((), manifest.entries.remove(file_name).is_some())
+    })
+}
+
+pub fn rename_graph(
+    graphs_dir: &Path,
+    from_file_name: &str,
+    to_file_name: &str,
+    now: &str,
+) -> Result<(), String> {
+    with_manifest(graphs_dir, |manifest| {
+        let Some(mut entry) = manifest.entries.remove(from_file_name) else {
+            return ((), false);
+        };
+        entry.file_name = to_file_name.to_string();
+        entry.name = to_file_name.trim_end_matches(".json").to_string();
+        entry.updated_at = now.to_string();
+        manifest.entries.insert(to_file_name.to_string(), entry);
+        ((), true)
+    })
+}
+
+// Matches a run to a graph by file name or stem; unknown graphs are ignored.
+pub fn record_graph_run(
+    graphs_dir: &Path,
+    graph_name: &str,
+    status: &str,
+    at: &str,
+) -> Result<(), String> {
+    let trimmed = graph_name.trim();
+    let file_name = if trimmed.ends_with(".json") {
+        trimmed.to_string()
+    } else {
+        format!("{trimmed}.json")
+    };
+    with_manifest(graphs_dir, |manifest| {
+        let Some(entry) = manifest.entries.get_mut(&file_name) else {
+            return ((), false);
+        };
+        entry.last_run_status = Some(status.to_string());
+        entry.last_run_at = Some(at.to_string());
+        ((), true)
+    })
+}
+
+// Indexes graphs that predate the manifest (or were copied in by hand) and
+// drops entries whose files are gone; existing entries are not re-read.
+pub fn reconcile_graph_index(
+    graphs_dir: &Path,
+    file_names: &[String],
+    load: impl Fn(&str) -> Option<GraphDocument>,
+) -> Result<Vec<GraphMetadata>, String> {
+    with_manifest(graphs_dir, |manifest| {
+        let mut changed = false;
+        let mut next = BTreeMap::new();
+        for file_name in file_names {
+            if let Some(entry) = manifest.entries.remove(file_name) {
+                next.insert(file_name.clone(), entry);
+                continue;
+            }
+            let Some(document) = load(file_name) else {
+                continue;
+            };
+            let modified = fs::metadata(graphs_dir.join(file_name))
+                .and_then(|meta| meta.modified())
+                .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339())
+                .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
+            next.insert(
+                file_name.clone(),
+                summarize_graph(file_name, &document, None, &modified),
+            );
+            changed = true;
+        }
+        changed |= !manifest.entries.is_empty();
+        manifest.entries = next;
+        (manifest.entries.values().cloned().collect(), changed)
+    })
+}
+
+pub fn query_graphs(entries: Vec<GraphMetadata>, query: &GraphListQuery) -> Vec<GraphMetadata> {
+    let tag = non_empty(query.tag.as_deref()).map(|tag| tag.to_lowercase());
+    let folder = normalize_folder(query.folder.as_deref());
+    let mode = non_empty(query.mode.as_deref()).map(|mode| mode.to_lowercase());
+    let text = non_empty(query.text.as_deref()).map(|text| text.to_lowercase());
+
+    let mut items = entries
+        .into_iter()
+        .filter(|entry| {
+            tag.as_ref()
+                .map(|tag| entry.tags.iter().any(|row| row.to_lowercase() == *tag))
+                .unwrap_or(true)
+        })
+        .filter(|entry| {
+            folder
+                .as_ref()
+                .map(|folder| {
+                    entry
+                        .folder
+                        .as_ref()
+                        .map(|value| value == folder || value.starts_with(&format!("{folder}/")))
+                        .unwrap_or(false)
+                })
+                .unwrap_or(true)
+        })
+        .filter(|entry| {
+            mode.as_ref()
+                .map(|mode| entry.mode == *mode)
+                .unwrap_or(true)
+        })
+        .filter(|entry| {
+            text.as_ref()
+                .map(|text| {
+                    entry.name.to_lowercase().contains(text)
+                        || entry
+                            .description
+                            .as_ref()
+                            .map(|value| value.to_lowercase().contains(text))
+                            .unwrap_or(false)
+                })
+                .unwrap_or(true)
+        })
+        .collect::<Vec<_>>();
+
+    match query.sort.as_deref().unwrap_or("updatedDesc") {
+        "updatedAsc" => items.sort_by(|a, b| a.updated_at.cmp(&b.updated_at)),
+        "createdDesc" => items.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+        "lastRunDesc" => items.sort_by(|a, b| b.last_run_at.cmp(&a.last_run_at)),
+        "nameAsc" => items.sort_by(|a, b| a.name.cmp(&b.name)),
+        _ => items.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::graph_schema::migrate_graph;
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn maintains_metadata_across_save_rename_and_runs() {
+        let dir = std::env::temp_dir().join(format!(
+            "rail_graph_index_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        fs::create_dir_all(&dir).expect("create graphs dir");
+
+        let mut document = migrate_graph(json!({
+            "version": 3,
+            "nodes": [{ "id": "a", "type": "turn", "config": { "executor": "via_flow" } }],
+            "edges": []
+        }))
+        .expect("valid graph")
+        .document;
+        apply_meta_patch(
+            &mut document,
+            &GraphMetaPatch {
+                description: Some("daily market".to_string()),
+                tags: Some(vec!["market".to_string(), " market ".to_string()]),
+                folder: Some("/research//daily/".to_string()),
+                mode: None,
+            },
+        );
+
+        let saved =
+            upsert_graph(&dir, "alpha.json", &document, "2026-01-01T00:00:00Z").expect("upsert");
+        assert_eq!(saved.mode, "rag");
+        assert_eq!(saved.tags, vec!["market"]);
+        assert_eq!(saved.folder.as_deref(), Some("research/daily"));
+
+        upsert_graph(&dir, "alpha.json", &document, "2026-01-02T00:00:00Z").expect("resave");
+        rename_graph(&dir, "alpha.json", "beta.json", "2026-01-03T00:00:00Z").expect("rename");
+        record_graph_run(&dir, "beta", "done", "2026-01-04T00:00:00Z").expect("record run");
+
+        let entries =
+            reconcile_graph_index(&dir, &["beta.json".to_string()], |_| None).expect("reconcile");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].created_at, "2026-01-01T00:00:00Z");
+        assert_eq!(entries[0].last_run_status.as_deref(), Some("done"));
+
+        let filtered = query_graphs(
+            entries,
+            &GraphListQuery {
+                tag: Some("MARKET".to_string()),
+                folder: Some("research".to_string()),
+                ..GraphListQuery::default()
+            },
+        );
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name, "beta");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,239 @@
+use super::graph_schema::{GraphDocument, GraphEdge, GraphNode};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+pub const MAX_GRAPH_REVISIONS: usize = 30;
+pub const GRAPH_REVISION_DIR: &str = ".revisions";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphRevisionSummary {
+    pub id: String,
+    pub saved_at: String,
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNodeChange {
+    pub node_id: String,
+    pub type_changed: Option<(String, String)>,
+    pub moved: bool,
+    pub changed_config_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphRevisionDiff {
+    pub from_revision: String,
+    pub to_revision: String,
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub changed_nodes: Vec<GraphNodeChange>,
+    pub added_edges: Vec<String>,
+    pub removed_edges: Vec<String>,
+    pub knowledge_changed: bool,
+}
+
+pub fn revision_id_for(now: chrono::DateTime<chrono::Utc>) -> String {
+    now.format("%Y%m%dT%H%M%S%3fZ").to_string()
+}
+
+pub fn revision_saved_at(id: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(id.trim_end_matches('Z'), "%Y%m%dT%H%M%S%3f")
+        .map(|value| value.and_utc().to_rfc3339())
+        .unwrap_or_else(|_| id.to_string())
+}
+
+// Revision ids sort by time, so the oldest go first. Pruned revisions are
+// deleted rather than trashed; they are history, not user files.
+pub fn prune_revisions(dir: &Path, keep: usize) -> Result<usize, String> {
+    let mut revisions = fs::read_dir(dir)
+        .map_err(|e| format!("failed to read graph revisions: {e}"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        })
+        .collect::<Vec<_>>();
+    revisions.sort();
+    let overflow = revisions.len().saturating_sub(keep);
+    for stale in revisions.iter().take(overflow) {
+        fs::remove_file(stale).map_err(|e| format!("failed to prune graph revision: {e}"))?;
+    }
+    Ok(overflow)
+}
+
+pub fn summarize_revision(id: &str, document: &GraphDocument) -> GraphRevisionSummary {
+    GraphRevisionSummary {
+        id: id.to_string(),
+        saved_at: revision_saved_at(id),
+        node_count: document.nodes.len(),
+        edge_count: document.edges.len(),
+    }
+}
+
+pub fn edge_key(edge: &GraphEdge) -> String {
+    format!(
+        "{}:{}->{}:{}",
+        edge.from.node_id, edge.from.port, edge.to.node_id, edge.to.port
+    )
+}
+
+fn diff_node(before: &GraphNode, after: &GraphNode) -> Option<GraphNodeChange> {
+    let type_changed = (before.node_type != after.node_type)
+        .then(|| (before.node_type.clone(), after.node_type.clone()));
+    let moved = before.position != after.position;
+    let changed_config_keys = before
+        .config
+        .keys()
+        .chain(after.config.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|key| before.config.get(*key) != after.config.get(*key))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if type_changed.is_none() && !moved && changed_config_keys.is_empty() {
+        return None;
+    }
+    Some(GraphNodeChange {
+        node_id: after.id.clone(),
+        type_changed,
+        moved,
+        changed_config_keys,
+    })
+}
+
+pub fn diff_graph_documents(
+    from_revision: &str,
+    before: &GraphDocument,
+    to_revision: &str,
+    after: &GraphDocument,
+) -> GraphRevisionDiff {
+    let before_nodes = before
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect::<BTreeMap<_, _>>();
+    let after_nodes = after
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut diff = GraphRevisionDiff {
+        from_revision: from_revision.to_string(),
+        to_revision: to_revision.to_string(),
+        ..GraphRevisionDiff::default()
+    };
+
+    for (id, after_node) in &after_nodes {
+        match before_nodes.get(id) {
+            Some(before_node) => {
+                if let Some(change) = diff_node(before_node, after_node) {
+                    diff.changed_nodes.push(change);
+                }
+            }
+            None => diff.added_nodes.push(id.to_string()),
+        }
+    }
+    diff.removed_nodes = before_nodes
+        .keys()
+        .filter(|id| !after_nodes.contains_key(*id))
+        .map(|id| id.to_string())
+        .collect();
+
+    let before_edges = before.edges.iter().map(edge_key).collect::<BTreeSet<_>>();
+    let after_edges = after.edges.iter().map(edge_key).collect::<BTreeSet<_>>();
+    diff.added_edges = after_edges.difference(&before_edges).cloned().collect();
+    diff.removed_edges = before_edges.difference(&after_edges).cloned().collect();
+
+    diff.knowledge_changed =
+        serde_json::to_value(&before.knowledge).ok() != serde_json::to_value(&after.knowledge).ok();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::graph_schema::migrate_graph;
+    use super::*;
+    use serde_json::json;
+
+    fn document(raw: serde_json::Value) -> GraphDocument {
+        migrate_graph(raw).expect("valid graph").document
+    }
+
+    #[test]
+    fn diff_reports_structural_changes() {
+        let before = document(json!({
+            "version": 3,
+            "nodes": [
+                { "id": "a", "type": "turn", "position": { "x": 0, "y": 0 }, "config": { "model": "x" } },
+                { "id": "b", "type": "turn", "position": { "x": 0, "y": 0 }, "config": {} }
+            ],
+            "edges": [{ "from": { "nodeId": "a", "port": "out" }, "to": { "nodeId": "b", "port": "in" } }]
+        }));
+        let after = document(json!({
+            "version": 3,
+            "nodes": [
+                { "id": "a", "type": "turn", "position": { "x": 0, "y": 0 }, "config": { "model": "y" } },
+                { "id": "c", "type": "gate", "position": { "x": 0, "y": 0 }, "config": {} }
+            ],
+            "edges": [{ "from": { "nodeId": "a", "port": "out" }, "to": { "nodeId": "c", "port": "in" } }]
+        }));
+
+        let diff = diff_graph_documents("r1", &before, "r2", &after);
+        assert_eq!(diff.added_nodes, vec!["c"]);
+        assert_eq!(diff.removed_nodes, vec!["b"]);
+        assert_eq!(diff.changed_nodes.len(), 1);
+        assert_eq!(diff.changed_nodes[0].changed_config_keys, vec!["model"]);
+        assert_eq!(diff.added_edges, vec!["a:out->c:in"]);
+        assert_eq!(diff.removed_edges, vec!["a:out->b:in"]);
+        assert!(!diff.knowledge_changed);
+    }
+
+    #[test]
+    fn saving_past_the_limit_prunes_the_oldest_revisions() {
+        let dir = std::env::temp_dir().join(format!(
+            "rail_revisions_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let start = chrono::Utc::now();
+        for save in 0..MAX_GRAPH_REVISIONS + 5 {
+            let id = revision_id_for(start + chrono::Duration::milliseconds(save as i64));
+            fs::write(dir.join(format!("{id}.json")), "{}").unwrap();
+            prune_revisions(&dir, MAX_GRAPH_REVISIONS).unwrap();
+        }
+
+        let mut kept = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        kept.sort();
+        assert_eq!(kept.len(), MAX_GRAPH_REVISIONS);
+        assert_eq!(
+            kept[0],
+            format!(
+                "{}.json",
+                revision_id_for(start + chrono::Duration::milliseconds(5))
+            )
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn revision_id_round_trips_to_timestamp() {
+        let id = "20260101T010203004Z";
+        assert_eq!(revision_saved_at(id), "2026-01-01T01:02:03.004+00:00");
+    }
+}
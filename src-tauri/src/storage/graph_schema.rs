@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+pub const GRAPH_SCHEMA_VERSION: u64 = 3;
+
+const DEFAULT_KNOWLEDGE_TOP_K: u64 = 0;
+const DEFAULT_KNOWLEDGE_MAX_CHARS: u64 = 2800;
+
+const LEGACY_VIA_NODE_TYPES: [(&str, &str); 4] = [
+    ("source.x", "source.sns"),
+    ("source.threads", "source.sns"),
+    ("source.reddit", "source.community"),
+    ("source.hn", "source.dev"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphDocument {
+    pub version: u64,
+    #[serde(default)]
+    pub nodes: Vec<GraphNode>,
+    #[serde(default)]
+    pub edges: Vec<GraphEdge>,
+    #[serde(default)]
+    pub knowledge: GraphKnowledgeConfig,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(default)]
+    pub position: GraphPoint,
+    #[serde(default)]
+    pub config: Map<String, Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    pub from: GraphEdgeEndpoint,
+    pub to: GraphEdgeEndpoint,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control: Option<GraphPoint>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdgeEndpoint {
+    pub node_id: String,
+    pub port: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphKnowledgeConfig {
+    #[serde(default)]
+    pub files: Vec<GraphKnowledgeFile>,
+    #[serde(default = "default_knowledge_top_k")]
+    pub top_k: u64,
+    #[serde(default = "default_knowledge_max_chars")]
+    pub max_chars: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphKnowledgeFile {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub ext: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for GraphKnowledgeConfig {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            top_k: DEFAULT_KNOWLEDGE_TOP_K,
+            max_chars: DEFAULT_KNOWLEDGE_MAX_CHARS,
+        }
+    }
+}
+
+fn default_knowledge_top_k() -> u64 {
+    DEFAULT_KNOWLEDGE_TOP_K
+}
+
+fn default_knowledge_max_chars() -> u64 {
+    DEFAULT_KNOWLEDGE_MAX_CHARS
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphMigrationOutcome {
+    pub document: GraphDocument,
+    pub from_version: u64,
+    pub migrated: bool,
+}
+
+type GraphMigrationStep = fn(&mut Map<String, Value>);
+
+// Each entry upgrades a document from `version` to `version + 1`.
+const GRAPH_MIGRATIONS: [(u64, GraphMigrationStep); 2] =
+    [(1, migrate_v1_to_v2), (2, migrate_v2_to_v3)];
+
+fn raw_graph_version(root: &Map<String, Value>) -> u64 {
+    root.get("version")
+        .and_then(|value| value.as_u64())
+        .filter(|version| *version > 0)
+        .unwrap_or(1)
+}
+
+pub fn migrate_graph(raw: Value) -> Result<GraphMigrationOutcome, String> {
+    let Value::Object(mut root) = raw else {
+        return Err("graph document must be a JSON object".to_string());
+    };
+    let from_version = raw_graph_version(&root);
+    if from_version > GRAPH_SCHEMA_VERSION {
+        return Err(format!(
+            "graph schema v{from_version} is newer than supported v{GRAPH_SCHEMA_VERSION}"
+        ));
+    }
+
+    let mut version = from_version;
+    for (step_version, step) in GRAPH_MIGRATIONS {
+        if version == step_version {
+            step(&mut root);
+            version += 1;
+        }
+    }
+    root.insert("version".to_string(), json!(version));
+
+    let document = serde_json::from_value::<GraphDocument>(Value::Object(root))
+        .map_err(|e| format!("invalid graph document (schema v{from_version}): {e}"))?;
+
+    Ok(GraphMigrationOutcome {
+        document,
+        from_version,
+        migrated: from_version < GRAPH_SCHEMA_VERSION,
+    })
+}
+
+fn object_array_mut<'a>(root: &'a mut Map<String, Value>, key: &str) -> &'a mut Vec<Value> {
+    let slot = root
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if !slot.is_array() {
+        *slot = Value::Array(Vec::new());
+    }
+    slot.as_array_mut()
+        .expect("slot was just normalized to an array")
+}
+
+// v1 graphs predate attached knowledge and were written before nodes always
+// carried a config/position and edges always carried explicit ports.
+fn migrate_v1_to_v2(root: &mut Map<String, Value>) {
+    object_array_mut(root, "nodes").retain_mut(|node| {
+        let Some(row) = node.as_object_mut() else {
+            return false;
+        };
+        if !row.get("config").map(Value::is_object).unwrap_or(false) {
+            row.insert("config".to_string(), json!({}));
+        }
+        if !row.get("position").map(Value::is_object).unwrap_or(false) {
+            row.insert("position".to_string(), json!({ "x": 0, "y": 0 }));
+        }
+        true
+    });
+
+    object_array_mut(root, "edges").retain_mut(|edge| {
+        let Some(row) = edge.as_object_mut() else {
+            return false;
+        };
+        for (key, port) in [("from", "out"), ("to", "in")] {
+            let Some(endpoint) = row.get_mut(key).and_then(Value::as_object_mut) else {
+                return false;
+            };
+            if !endpoint.get("port").map(Value::is_string).unwrap_or(false) {
+                endpoint.insert("port".to_string(), json!(port));
+            }
+        }
+        true
+    });
+
+    if !root.get("knowledge").map(Value::is_object).unwrap_or(false) {
+        root.insert(
+            "knowledge".to_string(),
+            serde_json::to_value(GraphKnowledgeConfig::default()).unwrap_or(Value::Null),
+        );
+    }
+}
+
+// v3 folded the per-platform RAG source nodes into their merged successors.
+fn migrate_v2_to_v3(root: &mut Map<String, Value>) {
+    for node in object_array_mut(root, "nodes").iter_mut() {
+        let Some(config) = node.get_mut("config").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for key in ["viaNodeType", "viaSourceTypeHint"] {
+            let Some(current) = config.get(key).and_then(Value::as_str) else {
+                continue;
+            };
+            if let Some((_, renamed)) = LEGACY_VIA_NODE_TYPES
+                .iter()
+                .find(|(legacy, _)| *legacy == current)
+            {
+                config.insert(key.to_string(), json!(renamed));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_v1_graph_to_current_schema() {
+        let raw = json!({
+            "nodes": [
+                { "id": "a", "type": "turn" },
+                { "id": "b", "type": "turn", "config": { "viaNodeType": "source.reddit" } }
+            ],
+            "edges": [
+                { "from": { "nodeId": "a" }, "to": { "nodeId": "b" } }
+            ]
+        });
+
+        let outcome = migrate_graph(raw).expect("migrate v1 graph");
+        assert_eq!(outcome.from_version, 1);
+        assert!(outcome.migrated);
+        assert_eq!(outcome.document.version, GRAPH_SCHEMA_VERSION);
+        assert_eq!(outcome.document.nodes[0].position, GraphPoint::default());
+        assert_eq!(outcome.document.edges[0].from.port, "out");
+        assert_eq!(outcome.document.edges[0].to.port, "in");
+        assert_eq!(
+            outcome.document.nodes[1].config.get("viaNodeType"),
+            Some(&json!("source.community"))
+        );
+        assert_eq!(
+            outcome.document.knowledge.max_chars,
+            DEFAULT_KNOWLEDGE_MAX_CHARS
+        );
+    }
+
+    #[test]
+    fn keeps_current_graph_and_unknown_fields_untouched() {
+        let raw = json!({
+            "version": GRAPH_SCHEMA_VERSION,
+            "nodes": [{
+                "id": "a",
+                "type": "gate",
+                "position": { "x": 10.0, "y": 20.0 },
+                "config": { "decisionPath": "decision" },
+                "label": "keep me"
+            }],
+            "edges": [],
+            "knowledge": { "files": [], "topK": 2, "maxChars": 900 },
+            "viewport": { "zoom": 1.5 }
+        });
+
+        let outcome = migrate_graph(raw.clone()).expect("load current graph");
+        assert!(!outcome.migrated);
+        let round_trip = serde_json::to_value(&outcome.document).expect("serialize graph");
+        assert_eq!(round_trip, raw);
+    }
+
+    #[test]
+    fn rejects_non_object_graph() {
+        assert!(migrate_graph(json!([1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn rejects_graph_from_newer_schema() {
+        let raw = json!({ "version": GRAPH_SCHEMA_VERSION + 1, "nodes": [], "edges": [] });
+        let error = migrate_graph(raw).expect_err("newer schema");
+        assert_eq!(
+            error,
+            format!(
+                "graph schema v{} is newer than supported v{GRAPH_SCHEMA_VERSION}",
+                GRAPH_SCHEMA_VERSION + 1
+            )
+        );
+    }
+}
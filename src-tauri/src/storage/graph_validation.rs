@@ -0,0 +1,481 @@
+use super::graph_revisions::edge_key;
+use super::graph_schema::{GraphDocument, GraphNode};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+const NODE_TYPES: [&str; 3] = ["turn", "transform", "gate"];
+const TURN_EXECUTORS: [&str; 8] = [
+    "codex",
+    "via_flow",
+    "web_gemini",
+    "web_gpt",
+    "web_grok",
+    "web_perplexity",
+    "web_claude",
+    "ollama",
+];
+const TRANSFORM_MODES: [&str; 3] = ["pick", "merge", "template"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphDiagnostic {
+    pub severity: String,
+    pub code: String,
+    pub message: String,
+    pub node_ids: Vec<String>,
+    pub edge: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphValidationReport {
+    pub valid: bool,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub diagnostics: Vec<GraphDiagnostic>,
+}
+
+impl GraphValidationReport {
+    fn push(&mut self, severity: &str, code: &str, message: String, node_ids: Vec<String>) {
+        self.diagnostics.push(GraphDiagnostic {
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message,
+            node_ids,
+            edge: None,
+        });
+    }
+
+    fn push_edge(&mut self, code: &str, message: String, node_ids: Vec<String>, edge: String) {
+        self.diagnostics.push(GraphDiagnostic {
+            severity: "error".to_string(),
+            code: code.to_string(),
+            message,
+            node_ids,
+            edge: Some(edge),
+        });
+    }
+
+    pub fn first_error(&self) -> Option<&GraphDiagnostic> {
+        self.diagnostics.iter().find(|row| row.severity == "error")
+    }
+}
+
+fn config_str<'a>(node: &'a GraphNode, key: &str) -> &'a str {
+    node.config
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or("")
+}
+
+// Position of a RAG node kind in the pipeline; edges must move strictly forward.
+fn rag_stage(via_node_type: &str) -> Option<u8> {
+    match via_node_type {
+        "trigger.manual" => Some(0),
+        value if value.starts_with("source.") => Some(1),
+        "transform.normalize" => Some(2),
+        "transform.verify" => Some(3),
+        "transform.rank" => Some(4),
+        "agent.codex" => Some(5),
+        "export.rag" => Some(6),
+        _ => None,
+    }
+}
+
+fn via_node_type(node: &GraphNode) -> Option<&str> {
+    (node.node_type == "turn" && config_str(node, "executor") == "via_flow")
+        .then(|| config_str(node, "viaNodeType"))
+}
+
+fn check_json_field(report: &mut GraphValidationReport, node: &GraphNode, key: &str) {
+    let raw = config_str(node, key);
+    if raw.is_empty() {
+        return;
+    }
+    if let Err(error) = serde_json::from_str::<Value>(raw) {
+        report.push(
+            "error",
+            "invalidConfig",
+            format!("{key} is not valid JSON: {error}"),
+            vec![node.id.clone()],
+        );
+    }
+}
+
+fn check_node_config(
+    report: &mut GraphValidationReport,
+    node: &GraphNode,
+    node_ids: &BTreeSet<&str>,
+) {
+    match node.node_type.as_str() {
+        "turn" => {
+            let executor = config_str(node, "executor");
+            if !executor.is_empty() && !TURN_EXECUTORS.contains(&executor) {
+                report.push(
+                    "warning",
+                    "unknownExecutor",
+                    format!("unknown executor {executor}; codex will be used"),
+                    vec![node.id.clone()],
+                );
+            }
+            if executor == "via_flow" {
+                let via_type = config_str(node, "viaNodeType");
+                if via_type.is_empty() {
+                    report.push(
+                        "error",
+                        "missingConfig",
+                        "RAG node requires viaNodeType".to_string(),
+                        vec![node.id.clone()],
+                    );
+                } else if rag_stage(via_type).is_none() {
+                    report.push(
+                        "error",
+                        "invalidConfig",
+                        format!("unknown RAG node type {via_type}"),
+                        vec![node.id.clone()],
+                    );
+                }
+            }
+            if executor == "ollama" && config_str(node, "ollamaModel").is_empty() {
+                report.push(
+                    "warning",
+                    "missingConfig",
+                    "ollama executor has no ollamaModel".to_string(),
+                    vec![node.id.clone()],
+                );
+            }
+            check_json_field(report, node, "outputSchemaJson");
+        }
+        "transform" => {
+            let mode = config_str(node, "mode");
+            let required = match mode {
+                "" => None,
+                "pick" => Some("pickPath"),
+                "merge" => Some("mergeJson"),
+                "template" => Some("template"),
+                _ => {
+                    report.push(
+                        "error",
+                        "invalidConfig",
+                        format!(
+                            "transform mode must be one of {}",
+                            TRANSFORM_MODES.join(", ")
+                        ),
+                        vec![node.id.clone()],
+                    );
+                    None
+                }
+            };
+            if let Some(key) = required {
+                if config_str(node, key).is_empty() {
+                    report.push(
+                        "error",
+                        "missingConfig",
+                        format!("transform mode {mode} requires {key}"),
+                        vec![node.id.clone()],
+                    );
+                }
+            }
+            check_json_field(report, node, "mergeJson");
+        }
+        "gate" => {
+            if config_str(node, "decisionPath").is_empty() {
+                report.push(
+                    "warning",
+                    "missingConfig",
+                    "gate has no decisionPath".to_string(),
+                    vec![node.id.clone()],
+                );
+            }
+            for key in ["passNodeId", "rejectNodeId"] {
+                let target = config_str(node, key);
+                if !target.is_empty() && !node_ids.contains(target) {
+                    report.push(
+                        "error",
+                        "missingNode",
+                        format!("{key} references unknown node {target}"),
+                        vec![node.id.clone()],
+                    );
+                }
+            }
+            check_json_field(report, node, "schemaJson");
+        }
+        _ => {}
+    }
+}
+
+// Tarjan's algorithm; returns components that form a cycle (size > 1 or a self-loop).
+fn find_cycles(node_ids: &[&str], adjacency: &HashMap<&str, Vec<&str>>) -> Vec<Vec<String>> {
+    struct Walk<'a> {
+        adjacency: &'a HashMap<&'a str, Vec<&'a str>>,
+        index: usize,
+        indices: HashMap<&'a str, usize>,
+        lowlinks: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        cycles: Vec<Vec<String>>,
+    }
+
+    fn visit<'a>(walk: &mut Walk<'a>, node: &'a str) {
+        walk.indices.insert(node, walk.index);
+        walk.lowlinks.insert(node, walk.index);
+        walk.index += 1;
+        walk.stack.push(node);
+        walk.on_stack.insert(node);
+
+        let adjacency = walk.adjacency;
+        for next in adjacency.get(node).map(Vec::as_slice).unwrap_or(&[]) {
+            if !walk.indices.contains_key(next) {
+                visit(walk, next);
+                let low = walk.lowlinks[node].min(walk.lowlinks[next]);
+                walk.lowlinks.insert(node, low);
+            } else if walk.on_stack.contains(next) {
+                let low = walk.lowlinks[node].min(walk.indices[next]);
+                walk.lowlinks.insert(node, low);
+            }
+        }
+
+        if walk.lowlinks[node] != walk.indices[node] {
+            return;
+        }
+        let mut component = Vec::new();
+        while let Some(member) = walk.stack.pop() {
+            walk.on_stack.remove(member);
+            component.push(member.to_string());
+            if member == node {
+                break;
+            }
+        }
+        let self_loop = adjacency
+            .get(node)
+            .map(|targets| targets.contains(&node))
+            .unwrap_or(false);
+        if component.len() > 1 || self_loop {
+            component.sort();
+            walk.cycles.push(component);
+        }
+    }
+
+    let mut walk = Walk {
+        adjacency,
+        index: 0,
+        indices: HashMap::new(),
+        lowlinks: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        cycles: Vec::new(),
+    };
+    for node in node_ids {
+        if !walk.indices.contains_key(node) {
+            visit(&mut walk, node);
+        }
+    }
+    walk.cycles
+}
+
+pub fn validate_graph(document: &GraphDocument) -> GraphValidationReport {
+    let mut report = GraphValidationReport::default();
+
+    let mut nodes_by_id: BTreeMap<&str, &GraphNode> = BTreeMap::new();
+    for node in &document.nodes {
+        if node.id.trim().is_empty() {
+            report.push(
+                "error",
+                "missingNodeId",
+                "node id is empty".to_string(),
+                Vec::new(),
+            );
+            continue;
+        }
+        if nodes_by_id.insert(node.id.as_str(), node).is_some() {
+            report.push(
+                "error",
+                "duplicateNodeId",
+                format!("node id {} is used more than once", node.id),
+                vec![node.id.clone()],
+            );
+        }
+        if !NODE_TYPES.contains(&node.node_type.as_str()) {
+            report.push(
+                "error",
+                "unknownNodeType",
+                format!("unknown node type {}", node.node_type),
+                vec![node.id.clone()],
+            );
+        }
+    }
+
+    let node_ids = nodes_by_id.keys().copied().collect::<BTreeSet<_>>();
+    for node in nodes_by_id.values() {
+        check_node_config(&mut report, node, &node_ids);
+    }
+
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut seen_edges = BTreeSet::new();
+    let mut incoming: BTreeSet<&str> = BTreeSet::new();
+    for edge in &document.edges {
+        let key = edge_key(edge);
+        let from_id = edge.from.node_id.as_str();
+        let to_id = edge.to.node_id.as_str();
+        let mut dangling = false;
+        for node_id in [from_id, to_id] {
+            if !nodes_by_id.contains_key(node_id) {
+                dangling = true;
+                report.push_edge(
+                    "missingNode",
+                    format!("edge references unknown node {node_id}"),
+                    vec![from_id.to_string(), to_id.to_string()],
+                    key.clone(),
+                );
+            }
+        }
+        if edge.from.port != "out" || edge.to.port != "in" {
+            report.push_edge(
+                "invalidPort",
+                format!(
+                    "edges must connect an out port to an in port, got {} -> {}",
+                    edge.from.port, edge.to.port
+                ),
+                vec![from_id.to_string(), to_id.to_string()],
+                key.clone(),
+            );
+        }
+        if !seen_edges.insert(key.clone()) {
+            report.diagnostics.push(GraphDiagnostic {
+                severity: "warning".to_string(),
+                code: "duplicateEdge".to_string(),
+                message: "edge is defined more than once".to_string(),
+                node_ids: vec![from_id.to_string(), to_id.to_string()],
+                edge: Some(key.clone()),
+            });
+            continue;
+        }
+        if dangling {
+            continue;
+        }
+        adjacency.entry(from_id).or_default().push(to_id);
+        incoming.insert(to_id);
+
+        let from_stage = via_node_type(nodes_by_id[from_id]).and_then(rag_stage);
+        let to_stage = via_node_type(nodes_by_id[to_id]).and_then(rag_stage);
+        if let (Some(from_stage), Some(to_stage)) = (from_stage, to_stage) {
+            if from_stage >= to_stage {
+                report.push_edge(
+                    "ragOrder",
+                    format!(
+                        "RAG node {} cannot feed {}",
+                        config_str(nodes_by_id[from_id], "viaNodeType"),
+                        config_str(nodes_by_id[to_id], "viaNodeType")
+                    ),
+                    vec![from_id.to_string(), to_id.to_string()],
+                    key,
+                );
+            }
+        }
+    }
+
+    for node in nodes_by_id.values() {
+        let via_type = via_node_type(node).unwrap_or("");
+        if rag_stage(via_type).map(|stage| stage > 0).unwrap_or(false)
+            && !incoming.contains(node.id.as_str())
+        {
+            report.push(
+                "warning",
+                "ragDisconnected",
+                format!("RAG node {via_type} has no upstream node"),
+                vec![node.id.clone()],
+            );
+        }
+    }
+
+    let ordered_ids = node_ids.iter().copied().collect::<Vec<_>>();
+    for cycle in find_cycles(&ordered_ids, &adjacency) {
+        report.push(
+            "error",
+            "cycle",
+            format!("graph contains a cycle through {}", cycle.join(", ")),
+            cycle,
+        );
+    }
+
+    report.error_count = report
+        .diagnostics
+        .iter()
+        .filter(|row| row.severity == "error")
+        .count();
+    report.warning_count = report.diagnostics.len() - report.error_count;
+    report.valid = report.error_count == 0;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::graph_schema::migrate_graph;
+    use super::*;
+    use serde_json::json;
+
+    fn document(raw: Value) -> GraphDocument {
+        migrate_graph(raw).expect("valid graph").document
+    }
+
+    fn codes(report: &GraphValidationReport) -> Vec<&str> {
+        report
+            .diagnostics
+            .iter()
+            .map(|row| row.code.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn reports_cycles_missing_nodes_and_bad_ports() {
+        let graph = document(json!({
+            "version": 3,
+            "nodes": [
+                { "id": "a", "type": "turn", "config": {} },
+                { "id": "b", "type": "turn", "config": {} },
+                { "id": "c", "type": "transform", "config": { "mode": "pick" } }
+            ],
+            "edges": [
+                { "from": { "nodeId": "a", "port": "out" }, "to": { "nodeId": "b", "port": "in" } },
+                { "from": { "nodeId": "b", "port": "out" }, "to": { "nodeId": "a", "port": "in" } },
+                { "from": { "nodeId": "c", "port": "in" }, "to": { "nodeId": "ghost", "port": "in" } }
+            ]
+        }));
+
+        let report = validate_graph(&graph);
+        assert!(!report.valid);
+        assert_eq!(
+            codes(&report),
+            vec!["missingConfig", "missingNode", "invalidPort", "cycle"]
+        );
+        assert_eq!(report.diagnostics[3].node_ids, vec!["a", "b"]);
+        assert_eq!(report.diagnostics[0].node_ids, vec!["c"]);
+    }
+
+    #[test]
+    fn enforces_rag_pipeline_order() {
+        let rag = |id: &str, via: &str| json!({ "id": id, "type": "turn", "config": { "executor": "via_flow", "viaNodeType": via } });
+        let graph = document(json!({
+            "version": 3,
+            "nodes": [
+                rag("trigger", "trigger.manual"),
+                rag("news", "source.news"),
+                rag("rank", "transform.rank"),
+                rag("normalize", "transform.normalize"),
+                rag("export", "export.rag")
+            ],
+            "edges": [
+                { "from": { "nodeId": "trigger", "port": "out" }, "to": { "nodeId": "news", "port": "in" } },
+                { "from": { "nodeId": "news", "port": "out" }, "to": { "nodeId": "rank", "port": "in" } },
+                { "from": { "nodeId": "rank", "port": "out" }, "to": { "nodeId": "normalize", "port": "in" } },
+                { "from": { "nodeId": "normalize", "port": "out" }, "to": { "nodeId": "export", "port": "in" } }
+            ]
+        }));
+
+        let report = validate_graph(&graph);
+        assert_eq!(codes(&report), vec!["ragOrder"]);
+        assert_eq!(report.diagnostics[0].node_ids, vec!["rank", "normalize"]);
+    }
+}
@@ -0,0 +1,1474 @@
+pub(crate) mod atomic_file;
+mod bundle;
+mod graph_index;
+mod graph_revisions;
+mod graph_schema;
+mod graph_validation;
+mod recovery;
+mod retention;
+mod run_diff;
+mod run_index;
+mod run_report;
+mod run_store;
+mod search_index;
+mod trash;
+mod watcher;
+mod workspace_roots;
+
+pub(crate) use atomic_file::write_atomic;
+use bundle::{
+    install_bundle_files, read_bundle, write_bundle, BundleConflictPolicy, BundleExportInput,
+    BundleInstallReport, BundleManifest,
+};
+use graph_index::{
+    apply_meta_patch, graph_meta_object, query_graphs, reconcile_graph_index, record_graph_run,
+    remove_graph, rename_graph, upsert_graph, GraphListQuery, GraphMetaPatch, GraphMetadata,
+    GRAPH_META_KEY,
+};
+use graph_revisions::{
+    diff_graph_documents, prune_revisions, revision_id_for, summarize_revision, GraphRevisionDiff,
+    GraphRevisionSummary, GRAPH_REVISION_DIR, MAX_GRAPH_REVISIONS,
+};
+use graph_schema::{migrate_graph, GraphDocument};
+use graph_validation::{validate_graph, GraphValidationReport};
+use recovery::{recover_json_dir, StorageRecoveryReport};
+use retention::{
+    collect_candidates, collect_dir_candidates, plan_retention, RetentionCandidate,
+    RetentionConfig, RetentionPolicy, StorageGcReport,
+};
+use run_diff::{diff_runs, RunDiff};
+use run_index::{query_runs, reconcile_run_index, remove_run, upsert_run, RunQuery, RunQueryPage};
+use run_report::{render_report, ReportFormat};
+use run_store::{
+    add_artifact, list_artifacts, list_run_files, load_run, read_artifact, run_file_path,
+    run_storage_path, write_run, RunArtifact, RUN_MANIFEST_FILE,
+};
+use search_index::{
+    index_search_file, reconcile_search_index, remove_search_file, search_index, SearchQuery,
+    SearchResult,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::async_runtime::channel;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_dialog::DialogExt;
+use trash::{
+    empty_trash, list_trash, move_to_trash, read_trash_entry, restore_from_trash, TrashEntry,
+    WORKSPACE_TRASH_DIR,
+};
+use watcher::{note_self_write, start_watcher, StorageWatcher, WatchTarget};
+pub(crate) use workspace_roots::canonicalize_existing_prefix;
+use workspace_roots::{
+    legacy_cwd_candidate, WorkspacePathError, WorkspaceRoot, WorkspaceRootRegistry,
+};
+
+const GRAPH_BACKUP_DIR: &str = ".backups";
+const RECOVERABLE_DIRS: [&str; 2] = ["graphs", "runs"];
+const SETTINGS_DIR: &str = "settings";
+const RETENTION_CONFIG_NAME: &str = "retention.json";
+const WORKSPACE_ROOTS_NAME: &str = "workspace_roots.json";
+const TRASH_DIR: &str = "trash";
+const SEARCH_DIR: &str = "search";
+const WORKSPACE_TRASH_AREAS: [&str; 2] = ["workspace", "dashboardSnapshots"];
+
+static WORKSPACE_ROOTS_LOCK: Mutex<()> = Mutex::new(());
+
+const EVENT_STORAGE_CHANGED: &str = "storage://changed";
+
+#[derive(Default)]
+pub struct StorageRecoveryState {
+    last_report: Mutex<Option<StorageRecoveryReport>>,
+}
+
+#[derive(Default)]
+pub struct StorageWatcherState {
+    watcher: Mutex<Option<StorageWatcher>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageWatchStatus {
+    pub active: bool,
+    pub paths: Vec<String>,
+}
+
+fn app_data_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("failed to resolve app data dir: {e}"))
+        .or_else(|_| {
+            std::env::current_dir()
+                .map(|dir| dir.join("rail-data"))
+                .map_err(|e| format!("failed to resolve fallback app data dir: {e}"))
+        })
+}
+
+fn ensure_subdir<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    let dir = app_data_dir(app)?.join(name);
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create {name} directory: {e}"))?;
+    Ok(dir)
+}
+
+fn normalize_file_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("file name is required".to_string());
+    }
+    if trimmed.contains("..") || trimmed.contains('/') || trimmed.contains('\\') {
+        return Err("invalid file name".to_string());
+    }
+
+    if trimmed.ends_with(".json") {
+        Ok(trimmed.to_string())
+    } else {
+        Ok(format!("{trimmed}.json"))
+    }
+}
+
+fn normalize_markdown_file_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("file name is required".to_string());
+    }
+    if trimmed.contains("..") || trimmed.contains('/') || trimmed.contains('\\') {
+        return Err("invalid file name".to_string());
+    }
+
+    if trimmed.ends_with(".md") {
+        Ok(trimmed.to_string())
+    } else {
+        Ok(format!("{trimmed}.md"))
+    }
+}
+
+fn normalize_text_file_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("file name is required".to_string());
+    }
+    if trimmed.contains("..") || trimmed.contains('/') || trimmed.contains('\\') {
+        return Err("invalid file name".to_string());
+    }
+    Ok(trimmed.to_string())
+}
+
+fn list_json_files(app: &AppHandle, dir_name: &str) -> Result<Vec<String>, String> {
+    let dir = ensure_subdir(app, dir_name)?;
+    let mut files = Vec::new();
+
+    for entry in
+        fs::read_dir(dir).map_err(|e| format!("failed to read {dir_name} directory: {e}"))?
+    {
+        let entry = entry.map_err(|e| format!("failed to read directory entry: {e}"))?;
+        let path = entry.path();
+        if path.is_file()
+            && path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.eq_ignore_ascii_case("json"))
+                .unwrap_or(false)
+        {
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                files.push(name.to_string());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn write_json_file<R: Runtime>(
+    app: &AppHandle<R>,
+    dir_name: &str,
+    name: &str,
+    data: &Value,
+) -> Result<(), String> {
+    let normalized_name = normalize_file_name(name)?;
+    let dir = ensure_subdir(app, dir_name)?;
+    let path = dir.join(normalized_name);
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("failed to serialize JSON for {dir_name}: {e}"))?;
+    write_atomic(&path, json.as_bytes())
+        .map_err(|e| format!("failed to write {dir_name} file: {e}"))
+}
+
+fn backup_json_file(
+    app: &AppHandle,
+    dir_name: &str,
+    name: &str,
+    label: &str,
+) -> Result<PathBuf, String> {
+    let normalized_name = normalize_file_name(name)?;
+    let dir = ensure_subdir(app, dir_name)?;
+    let backup_dir = dir.join(GRAPH_BACKUP_DIR);
+    fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("failed to create {dir_name} backup directory: {e}"))?;
+
+    let stem = normalized_name.trim_end_matches(".json");
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ");
+    let backup_path = backup_dir.join(format!("{stem}.{label}.{stamp}.json"));
+    let original = fs::read(dir.join(&normalized_name))
+        .map_err(|e| format!("failed to read {dir_name} file for backup: {e}"))?;
+    write_atomic(&backup_path, &original)
+        .map_err(|e| format!("failed to back up {dir_name} file: {e}"))?;
+    Ok(backup_path)
+}
+
+fn read_json_file<R: Runtime>(
+    app: &AppHandle<R>,
+    dir_name: &str,
+    name: &str,
+) -> Result<Value, String> {
+    let normalized_name = normalize_file_name(name)?;
+    let dir = ensure_subdir(app, dir_name)?;
+    let path = dir.join(normalized_name);
+    let raw =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {dir_name} file: {e}"))?;
+    serde_json::from_str(&raw).map_err(|e| format!("invalid JSON in {dir_name} file: {e}"))
+}
+
+fn delete_json_file(app: &AppHandle, dir_name: &str, name: &str) -> Result<(), String> {
+    let normalized_name = normalize_file_name(name)?;
+    let dir = ensure_subdir(app, dir_name)?;
+    let path = dir.join(&normalized_name);
+    if !path.exists() {
+        return Err(format!("{dir_name} file not found"));
+    }
+    trash_file(app, dir_name, &path, "deleted")?;
+    refresh_search_file(app, dir_name, &normalized_name)
+}
+
+fn trash_file(app: &AppHandle, area: &str, path: &Path, reason: &str) -> Result<(), String> {
+    let trash_root = ensure_subdir(app, TRASH_DIR)?;
+    move_to_trash(&trash_root, area, path, reason)
+        .map(|_| ())
+        .map_err(|e| format!("failed to move {area} file to trash: {e}"))
+}
+
+pub(crate) fn move_to_workspace_trash(
+    workspace: &Path,
+    area: &str,
+    path: &Path,
+    reason: &str,
+) -> Result<TrashEntry, String> {
+    move_to_trash(&workspace.join(WORKSPACE_TRASH_DIR), area, path, reason)
+}
+
+fn rename_json_file(
+    app: &AppHandle,
+    dir_name: &str,
+    from_name: &str,
+    to_name: &str,
+) -> Result<String, String> {
+    let from_normalized = normalize_file_name(from_name)?;
+    let to_normalized = normalize_file_name(to_name)?;
+    if from_normalized == to_normalized {
+        return Ok(to_normalized);
+    }
+
+    let dir = ensure_subdir(app, dir_name)?;
+    let from_path = dir.join(&from_normalized);
+    if !from_path.exists() {
+        return Err(format!("{dir_name} file not found"));
+    }
+
+    let to_path = dir.join(&to_normalized);
+    if to_path.exists() {
+        trash_file(app, dir_name, &to_path, "overwrittenByRename")?;
+    }
+
+    note_self_write(&from_path);
+    note_self_write(&to_path);
+    fs::rename(from_path, to_path).map_err(|e| format!("failed to rename {dir_name} file: {e}"))?;
+    refresh_search_file(app, dir_name, &from_normalized)?;
+    refresh_search_file(app, dir_name, &to_normalized)?;
+    Ok(to_normalized)
+}
+
+fn file_stamp(path: &Path) -> Option<String> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    Some(format!("{}:{modified}", meta.len()))
+}
+
+// Keeps the search index in step with a single graph or run file; other
+// directories are not searchable and are ignored.
+fn refresh_search_file(app: &AppHandle, dir_name: &str, file_name: &str) -> Result<(), String> {
+    if dir_name != "graphs" && dir_name != "runs" {
+        return Ok(());
+    }
+    let index_dir = ensure_subdir(app, SEARCH_DIR)?;
+    let Some(stamp) = file_stamp(&stored_file_path(app, dir_name, file_name)?) else {
+        return remove_search_file(&index_dir, dir_name, file_name);
+    };
+    let value = load_stored_file(app, dir_name, file_name)?;
+    index_search_file(&index_dir, dir_name, file_name, &stamp, &value)
+}
+
+// Runs may be stored as `<name>/run.json`; these helpers hide that layout from
+// code that only needs a graph or run by file name.
+fn list_stored_files(app: &AppHandle, dir_name: &str) -> Result<Vec<String>, String> {
+    if dir_name == "runs" {
+        list_run_files(&ensure_subdir(app, "runs")?)
+    } else {
+        list_json_files(app, dir_name)
+    }
+}
+
+fn stored_file_path(app: &AppHandle, dir_name: &str, file_name: &str) -> Result<PathBuf, String> {
+    let dir = ensure_subdir(app, dir_name)?;
+    if dir_name == "runs" {
+        Ok(run_file_path(&dir, file_name))
+    } else {
+        Ok(dir.join(file_name))
+    }
+}
+
+fn load_stored_file(app: &AppHandle, dir_name: &str, file_name: &str) -> Result<Value, String> {
+    if dir_name == "runs" {
+        load_run(
+            &ensure_subdir(app, "runs")?,
+            &normalize_file_name(file_name)?,
+            true,
+        )
+    } else {
+        read_json_file(app, dir_name, file_name)
+    }
+}
+
+fn graph_revision_dir_name(name: &str) -> Result<String, String> {
+    let normalized_name = normalize_file_name(name)?;
+    let stem = normalized_name.trim_end_matches(".json");
+    Ok(format!("graphs/{GRAPH_REVISION_DIR}/{stem}"))
+}
+
+fn record_graph_revision(app: &AppHandle, name: &str, data: &Value) -> Result<(), String> {
+    let dir_name = graph_revision_dir_name(name)?;
+    let revision_id = revision_id_for(chrono::Utc::now());
+    write_json_file(app, &dir_name, &revision_id, data)?;
+    prune_revisions(&ensure_subdir(app, &dir_name)?, MAX_GRAPH_REVISIONS).map(|_| ())
+}
+
+// Writes the graph, records a revision and refreshes its index entry.
+fn persist_graph(
+    app: &AppHandle,
+    name: &str,
+    document: &GraphDocument,
+) -> Result<GraphMetadata, String> {
+    let data = serde_json::to_value(document)
+        .map_err(|e| format!("failed to serialize graph document: {e}"))?;
+    write_json_file(app, "graphs", name, &data)?;
+    record_graph_revision(app, name, &data)?;
+    refresh_search_file(app, "graphs", &normalize_file_name(name)?)?;
+    let graphs_dir = ensure_subdir(app, "graphs")?;
+    upsert_graph(
+        &graphs_dir,
+        &normalize_file_name(name)?,
+        document,
+        &chrono::Utc::now().to_rfc3339(),
+    )
+}
+
+fn load_graph_document(app: &AppHandle, name: &str) -> Result<GraphDocument, String> {
+    Ok(migrate_graph(read_json_file(app, "graphs", name)?)?.document)
+}
+
+fn load_graph_revision(
+    app: &AppHandle,
+    name: &str,
+    revision: &str,
+) -> Result<GraphDocument, String> {
+    let dir_name = graph_revision_dir_name(name)?;
+    let raw = read_json_file(app, &dir_name, revision)?;
+    Ok(migrate_graph(raw)?.document)
+}
+
+fn rename_graph_revisions(app: &AppHandle, from_name: &str, to_name: &str) -> Result<(), String> {
+    let data_dir = app_data_dir(app)?;
+    let from_dir = data_dir.join(graph_revision_dir_name(from_name)?);
+    let to_dir = data_dir.join(graph_revision_dir_name(to_name)?);
+    if from_dir == to_dir || !from_dir.is_dir() {
+        return Ok(());
+    }
+    if to_dir.exists() {
+        trash_file(app, "graphRevisions", &to_dir, "overwrittenByRename")?;
+    }
+    fs::rename(from_dir, to_dir).map_err(|e| format!("failed to rename graph revisions: {e}"))
+}
+
+pub fn recover_storage(app: &AppHandle) -> Result<StorageRecoveryReport, String> {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ").to_string();
+    let mut report = StorageRecoveryReport::default();
+    for dir_name in RECOVERABLE_DIRS {
+        let dir = ensure_subdir(app, dir_name)?;
+        report.merge(recover_json_dir(&dir, &stamp));
+    }
+
+    if let Ok(mut last_report) = app.state::<StorageRecoveryState>().last_report.lock() {
+        *last_report = Some(report.clone());
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn storage_recovery_report(
+    app: AppHandle,
+    state: State<'_, StorageRecoveryState>,
+    rescan: Option<bool>,
+) -> Result<StorageRecoveryReport, String> {
+    if rescan.unwrap_or(false) {
+        return recover_storage(&app);
+    }
+    let last_report = state
+        .last_report
+        .lock()
+        .map_err(|_| "storage recovery state is poisoned".to_string())?;
+    Ok(last_report.clone().unwrap_or_default())
+}
+
+fn watch_status(watcher: Option<&StorageWatcher>) -> StorageWatchStatus {
+    StorageWatchStatus {
+        active: watcher.is_some(),
+        paths: watcher
+            .map(|watcher| {
+                watcher
+                    .targets
+                    .iter()
+                    .map(|target| target.root.to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+// Watches graphs and runs, plus `<workspace>/.rail` when one is given. A new
+// call replaces the previous watcher.
+pub fn start_storage_watch(
+    app: &AppHandle,
+    workspace: Option<PathBuf>,
+) -> Result<StorageWatchStatus, String> {
+    let mut targets = ["graphs", "runs"]
+        .into_iter()
+        .map(|dir_name| {
+            Ok(WatchTarget {
+                scope: dir_name.to_string(),
+                root: ensure_subdir(app, dir_name)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if let Some(workspace) = workspace {
+        let root = workspace.join(".rail");
+        fs::create_dir_all(&root).map_err(|e| format!("failed to create .rail directory: {e}"))?;
+        targets.push(WatchTarget {
+            scope: "workspace".to_string(),
+            root,
+        });
+    }
+    let emitter = app.clone();
+    let watcher = start_watcher(targets, move |batch| {
+        let _ = emitter.emit(EVENT_STORAGE_CHANGED, batch);
+    })?;
+    let status = watch_status(Some(&watcher));
+    let state = app.state::<StorageWatcherState>();
+    let mut current = state
+        .watcher
+        .lock()
+        .map_err(|_| "storage watcher state is poisoned".to_string())?;
+    *current = Some(watcher);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn storage_watch_start(
+    app: AppHandle,
+    cwd: Option<String>,
+) -> Result<StorageWatchStatus, WorkspacePathError> {
+    let workspace = match cwd.filter(|value| !value.trim().is_empty()) {
+        Some(cwd) => Some(resolve_workspace_path(&app, &cwd)?),
+        None => None,
+    };
+    Ok(start_storage_watch(&app, workspace)?)
+}
+
+#[tauri::command]
+pub fn storage_watch_stop(
+    state: State<'_, StorageWatcherState>,
+) -> Result<StorageWatchStatus, String> {
+    let mut current = state
+        .watcher
+        .lock()
+        .map_err(|_| "storage watcher state is poisoned".to_string())?;
+    current.take();
+    Ok(watch_status(None))
+}
+
+#[tauri::command]
+pub fn storage_watch_status(
+    state: State<'_, StorageWatcherState>,
+) -> Result<StorageWatchStatus, String> {
+    let current = state
+        .watcher
+        .lock()
+        .map_err(|_| "storage watcher state is poisoned".to_string())?;
+    Ok(watch_status(current.as_ref()))
+}
+
+fn load_retention_config(app: &AppHandle) -> Result<RetentionConfig, String> {
+    let path = ensure_subdir(app, SETTINGS_DIR)?.join(RETENTION_CONFIG_NAME);
+    if !path.exists() {
+        return Ok(RetentionConfig::default());
+    }
+    let raw = read_json_file(app, SETTINGS_DIR, RETENTION_CONFIG_NAME)?;
+    serde_json::from_value(raw).map_err(|e| format!("failed to parse retention config: {e}"))
+}
+
+pub(crate) fn app_subdir<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<PathBuf, String> {
+    ensure_subdir(app, name)
+}
+
+// Other modules (e.g. engine policy) keep their settings next to ours.
+pub(crate) fn read_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+) -> Result<Option<Value>, String> {
+    let path = ensure_subdir(app, SETTINGS_DIR)?.join(normalize_file_name(name)?);
+    if !path.exists() {
+        return Ok(None);
+    }
+    read_json_file(app, SETTINGS_DIR, name).map(Some)
+}
+
+pub(crate) fn write_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    data: &Value,
+) -> Result<(), String> {
+    write_json_file(app, SETTINGS_DIR, name, data)
+}
+
+fn gc_area(
+    report: &mut StorageGcReport,
+    area: &str,
+    candidates: Result<Vec<RetentionCandidate>, String>,
+    policy: &RetentionPolicy,
+    now_ms: u128,
+) {
+    let candidates = match candidates {
+        Ok(candidates) => candidates,
+        Err(error) => {
+            report.errors.push(error);
+            return;
+        }
+    };
+    report.scanned += candidates.len();
+    let (kept, deleted) = plan_retention(area, candidates, policy, now_ms);
+    report.kept += kept;
+    for item in deleted {
+        if !report.dry_run {
+            let path = Path::new(&item.path);
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            if let Err(error) = removed {
+                report
+                    .errors
+                    .push(format!("failed to delete {}: {error}", item.path));
+                continue;
+            }
+        }
+        report.freed_bytes += item.size_bytes;
+        report.deleted.push(item);
+    }
+}
+
+#[tauri::command]
+pub fn storage_retention_get(app: AppHandle) -> Result<RetentionConfig, String> {
+    load_retention_config(&app)
+}
+
+#[tauri::command]
+pub fn storage_retention_set(app: AppHandle, config: RetentionConfig) -> Result<(), String> {
+    let data = serde_json::to_value(&config)
+        .map_err(|e| format!("failed to serialize retention config: {e}"))?;
+    write_json_file(&app, SETTINGS_DIR, RETENTION_CONFIG_NAME, &data)
+}
+
+// Dry run by default; pass dryRun=false to delete what the report lists.
+#[tauri::command]
+pub fn storage_gc(
+    app: AppHandle,
+    cwd: Option<String>,
+    config: Option<RetentionConfig>,
+    dry_run: Option<bool>,
+) -> Result<StorageGcReport, WorkspacePathError> {
+    let workspace = match cwd.filter(|value| !value.trim().is_empty()) {
+        Some(cwd) => Some(
+            resolve_workspace_path(&app, &cwd)?
+                .to_string_lossy()
+                .to_string(),
+        ),
+        None => None,
+    };
+    let config = match config {
+        Some(config) => config,
+        None => load_retention_config(&app)?,
+    };
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    let mut report = StorageGcReport {
+        dry_run: dry_run.unwrap_or(true),
+        ..StorageGcReport::default()
+    };
+
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    let first_run_item = report.deleted.len();
+    let run_candidates = collect_candidates(&runs_dir, &["json"], &config).and_then(|mut files| {
+        files.extend(collect_dir_candidates(
+            &runs_dir,
+            RUN_MANIFEST_FILE,
+            &config,
+        )?);
+        Ok(files)
+    });
+    gc_area(&mut report, "runs", run_candidates, &config.runs, now_ms);
+    if !report.dry_run {
+        for item in &report.deleted[first_run_item..] {
+            let file_name = Path::new(&item.path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            let removed = normalize_file_name(file_name).and_then(|file_name| {
+                remove_run(&runs_dir, &file_name)?;
+                refresh_search_file(&app, "runs", &file_name)
+            });
+            if let Err(error) = removed {
+                report.errors.push(error);
+            }
+        }
+    }
+
+    if let Some(cwd) = workspace {
+        for dir in crate::dashboard_crawler::dashboard_raw_topic_dirs(&cwd)? {
+            gc_area(
+                &mut report,
+                "dashboardRaw",
+                collect_candidates(&dir, &["md", "json"], &config),
+                &config.dashboard_raw,
+                now_ms,
+            );
+        }
+        for dir in crate::dashboard_crawler::dashboard_snapshot_topic_dirs(&cwd)? {
+            gc_area(
+                &mut report,
+                "dashboardSnapshots",
+                collect_candidates(&dir, &["json"], &config),
+                &config.dashboard_snapshots,
+                now_ms,
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn graph_list(app: AppHandle) -> Result<Vec<String>, String> {
+    list_json_files(&app, "graphs")
+}
+
+#[tauri::command]
+pub fn graph_save(
+    app: AppHandle,
+    name: String,
+    graph: Value,
+    strict: Option<bool>,
+) -> Result<(), String> {
+    let mut document = migrate_graph(graph)?.document;
+    if !document.extra.contains_key(GRAPH_META_KEY) {
+        // The canvas does not send metadata, so keep what was stored before.
+        if let Ok(previous) = load_graph_document(&app, &name) {
+            let meta = graph_meta_object(&previous);
+            if !meta.is_empty() {
+                document
+                    .extra
+                    .insert(GRAPH_META_KEY.to_string(), Value::Object(meta));
+            }
+        }
+    }
+    if strict.unwrap_or(false) {
+        let report = validate_graph(&document);
+        if let Some(error) = report.first_error() {
+            return Err(format!(
+                "graph validation failed with {} error(s): {}",
+                report.error_count, error.message
+            ));
+        }
+    }
+    persist_graph(&app, &name, &document)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn graph_validate(graph: Value) -> Result<GraphValidationReport, String> {
+    let document = migrate_graph(graph)?.document;
+    Ok(validate_graph(&document))
+}
+
+#[tauri::command]
+pub fn graph_load(app: AppHandle, name: String) -> Result<GraphDocument, String> {
+    let raw = read_json_file(&app, "graphs", &name)?;
+    let outcome = migrate_graph(raw)?;
+    if outcome.migrated {
+        backup_json_file(&app, "graphs", &name, &format!("v{}", outcome.from_version))?;
+        let data = serde_json::to_value(&outcome.document)
+            .map_err(|e| format!("failed to serialize graph document: {e}"))?;
+        write_json_file(&app, "graphs", &name, &data)?;
+    }
+    Ok(outcome.document)
+}
+
+#[tauri::command]
+pub fn graph_delete(app: AppHandle, name: String) -> Result<(), String> {
+    delete_json_file(&app, "graphs", &name)?;
+    let graphs_dir = ensure_subdir(&app, "graphs")?;
+    remove_graph(&graphs_dir, &normalize_file_name(&name)?)
+}
+
+#[tauri::command]
+pub fn graph_rename(app: AppHandle, from_name: String, to_name: String) -> Result<String, String> {
+    let renamed = rename_json_file(&app, "graphs", &from_name, &to_name)?;
+    rename_graph_revisions(&app, &from_name, &renamed)?;
+    let graphs_dir = ensure_subdir(&app, "graphs")?;
+    rename_graph(
+        &graphs_dir,
+        &normalize_file_name(&from_name)?,
+        &renamed,
+        &chrono::Utc::now().to_rfc3339(),
+    )?;
+    Ok(renamed)
+}
+
+#[tauri::command]
+pub fn graph_list_meta(
+    app: AppHandle,
+    query: Option<GraphListQuery>,
+) -> Result<Vec<GraphMetadata>, String> {
+    let files = list_json_files(&app, "graphs")?;
+    let graphs_dir = ensure_subdir(&app, "graphs")?;
+    let entries = reconcile_graph_index(&graphs_dir, &files, |file_name| {
+        load_graph_document(&app, file_name).ok()
+    })?;
+    Ok(query_graphs(entries, &query.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub fn graph_meta_update(
+    app: AppHandle,
+    name: String,
+    patch: GraphMetaPatch,
+) -> Result<GraphMetadata, String> {
+    let mut document = load_graph_document(&app, &name)?;
+    apply_meta_patch(&mut document, &patch);
+    persist_graph(&app, &name, &document)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphBundleExportResult {
+    pub path: String,
+    pub manifest: BundleManifest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphBundleImportResult {
+    pub graph_name: String,
+    pub manifest: BundleManifest,
+    pub files: BundleInstallReport,
+}
+
+fn resolve_node_cwd(workspace: &Path, raw: Option<&Value>) -> PathBuf {
+    let value = raw.and_then(Value::as_str).map(str::trim).unwrap_or("");
+    if value.is_empty() || value == "." {
+        return workspace.to_path_buf();
+    }
+    let path = PathBuf::from(value);
+    if path.is_absolute() {
+        path
+    } else {
+        workspace.join(path)
+    }
+}
+
+fn available_graph_name(app: &AppHandle, requested: &str) -> Result<String, String> {
+    let existing = list_json_files(app, "graphs")?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    let normalized = normalize_file_name(requested)?;
+    if !existing.contains(&normalized) {
+        return Ok(normalized);
+    }
+    let stem = normalized.trim_end_matches(".json");
+    let mut index = 2;
+    loop {
+        let candidate = format!("{stem}-{index}.json");
+        if !existing.contains(&candidate) {
+            return Ok(candidate);
+        }
+        index += 1;
+    }
+}
+
+#[tauri::command]
+pub fn graph_export_bundle(
+    app: AppHandle,
+    name: String,
+    cwd: String,
+    target_path: String,
+    allowlist_by_topic: Option<BTreeMap<String, Vec<String>>>,
+) -> Result<GraphBundleExportResult, WorkspacePathError> {
+    let registry = load_workspace_roots(&app)?;
+    let workspace = registry.resolve(&cwd)?;
+    if !workspace.is_dir() {
+        return Err("cwd must be an existing directory".into());
+    }
+    let target = PathBuf::from(target_path.trim());
+    if !target.is_absolute() {
+        return Err("bundle path must be absolute".into());
+    }
+    let is_zip = target
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("zip"))
+        .unwrap_or(false);
+    if !is_zip {
+        return Err("bundle path must end with .zip".into());
+    }
+
+    let document = migrate_graph(read_json_file(&app, "graphs", &name)?)?.document;
+    let rule_dirs = std::iter::once(workspace.clone())
+        .chain(
+            document
+                .nodes
+                .iter()
+                .map(|node| resolve_node_cwd(&workspace, node.config.get("cwd"))),
+        )
+        // Node folders outside the registered roots are not the bundle's to read.
+        .filter_map(|dir| registry.resolve(&dir.to_string_lossy()).ok())
+        .collect::<BTreeSet<_>>();
+    let rule_sets = rule_dirs
+        .into_iter()
+        .filter(|dir| dir.is_dir())
+        .map(|dir| {
+            let paths = crate::engine::agent_rule_paths(&dir);
+            (dir, paths)
+        })
+        .collect::<Vec<_>>();
+
+    let graph_name = normalize_file_name(&name)?;
+    let (bytes, manifest) = write_bundle(BundleExportInput {
+        graph_name: graph_name.trim_end_matches(".json"),
+        document,
+        workspace: &workspace,
+        rule_sets,
+        allowlist_by_topic,
+    })?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create bundle directory: {e}"))?;
+    }
+    write_atomic(&target, &bytes).map_err(|e| format!("failed to write bundle: {e}"))?;
+    Ok(GraphBundleExportResult {
+        path: target.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+// onConflict applies to the graph name and to every bundled file: rename
+// (default) keeps both, overwrite replaces, skip keeps what is already there.
+#[tauri::command]
+pub fn graph_import_bundle(
+    app: AppHandle,
+    path: String,
+    cwd: String,
+    name: Option<String>,
+    on_conflict: Option<String>,
+) -> Result<GraphBundleImportResult, WorkspacePathError> {
+    let policy = BundleConflictPolicy::parse(on_conflict.as_deref())?;
+    if cwd.trim().is_empty() {
+        return Err("cwd is required".into());
+    }
+    let workspace = resolve_workspace_path(&app, &cwd)?;
+    fs::create_dir_all(&workspace)
+        .map_err(|e| format!("failed to create workspace directory: {e}"))?;
+
+    let bytes = fs::read(path.trim()).map_err(|e| format!("failed to read bundle: {e}"))?;
+    let mut contents = read_bundle(&bytes)?;
+    let requested_name = name
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| contents.manifest.graph_name.clone());
+    let normalized_name = normalize_file_name(&requested_name)?;
+    let exists = list_json_files(&app, "graphs")?.contains(&normalized_name);
+    let graph_name = match (exists, policy) {
+        (false, _) | (true, BundleConflictPolicy::Overwrite) => normalized_name,
+        (true, BundleConflictPolicy::Rename) => available_graph_name(&app, &requested_name)?,
+        (true, BundleConflictPolicy::Skip) => {
+            return Err(format!("graph {normalized_name} already exists").into());
+        }
+    };
+
+    let files = install_bundle_files(&mut contents, &workspace, policy)?;
+    persist_graph(&app, &graph_name, &contents.document)?;
+
+    Ok(GraphBundleImportResult {
+        graph_name,
+        manifest: contents.manifest,
+        files,
+    })
+}
+
+#[tauri::command]
+pub fn graph_revision_list(
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<GraphRevisionSummary>, String> {
+    let dir_name = graph_revision_dir_name(&name)?;
+    let mut summaries = Vec::new();
+    for file in list_json_files(&app, &dir_name)?.iter().rev() {
+        let revision_id = file.trim_end_matches(".json");
+        let document = load_graph_revision(&app, &name, revision_id)?;
+        summaries.push(summarize_revision(revision_id, &document));
+    }
+    Ok(summaries)
+}
+
+#[tauri::command]
+pub fn graph_revision_diff(
+    app: AppHandle,
+    name: String,
+    from_revision: String,
+    to_revision: String,
+) -> Result<GraphRevisionDiff, String> {
+    let before = load_graph_revision(&app, &name, &from_revision)?;
+    let after = load_graph_revision(&app, &name, &to_revision)?;
+    Ok(diff_graph_documents(
+        from_revision.trim_end_matches(".json"),
+        &before,
+        to_revision.trim_end_matches(".json"),
+        &after,
+    ))
+}
+
+#[tauri::command]
+pub fn graph_revision_restore(
+    app: AppHandle,
+    name: String,
+    revision: String,
+) -> Result<GraphDocument, String> {
+    let document = load_graph_revision(&app, &name, &revision)?;
+    persist_graph(&app, &name, &document)?;
+    Ok(document)
+}
+
+#[tauri::command]
+pub fn run_save(
+    app: AppHandle,
+    name: String,
+    run: Value,
+    graph_name: Option<String>,
+) -> Result<(), String> {
+    let file_name = normalize_file_name(&name)?;
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    let stored = write_run(&runs_dir, &file_name, &run)?;
+    refresh_search_file(&app, "runs", &file_name)?;
+    let entry = upsert_run(&runs_dir, &file_name, &stored)?;
+    let Some(graph_name) = graph_name
+        .filter(|value| !value.trim().is_empty())
+        .or(entry.graph_name)
+    else {
+        return Ok(());
+    };
+    let at = entry
+        .finished_at
+        .or(entry.started_at)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let graphs_dir = ensure_subdir(&app, "graphs")?;
+    record_graph_run(&graphs_dir, &graph_name, &entry.status, &at)
+}
+
+#[tauri::command]
+pub fn run_list(app: AppHandle) -> Result<Vec<String>, String> {
+    list_stored_files(&app, "runs")
+}
+
+// Large attachments come back as previews unless inlineArtifacts is set.
+#[tauri::command]
+pub fn run_load(
+    app: AppHandle,
+    name: String,
+    inline_artifacts: Option<bool>,
+) -> Result<Value, String> {
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    load_run(
+        &runs_dir,
+        &normalize_file_name(&name)?,
+        inline_artifacts.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub fn run_delete(app: AppHandle, name: String) -> Result<(), String> {
+    let file_name = normalize_file_name(&name)?;
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    let path =
+        run_storage_path(&runs_dir, &file_name).ok_or_else(|| "runs file not found".to_string())?;
+    trash_file(&app, "runs", &path, "deleted")?;
+    refresh_search_file(&app, "runs", &file_name)?;
+    remove_run(&runs_dir, &file_name)
+}
+
+#[tauri::command]
+pub fn run_query(app: AppHandle, query: Option<RunQuery>) -> Result<RunQueryPage, String> {
+    let files = list_stored_files(&app, "runs")?;
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    let entries = reconcile_run_index(&runs_dir, &files)?;
+    Ok(query_runs(entries, &query.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub fn run_diff(app: AppHandle, base_name: String, target_name: String) -> Result<RunDiff, String> {
+    let base = load_stored_file(&app, "runs", &base_name)?;
+    let target = load_stored_file(&app, "runs", &target_name)?;
+    Ok(diff_runs(&base, &target))
+}
+
+#[tauri::command]
+pub fn storage_search(app: AppHandle, query: SearchQuery) -> Result<SearchResult, String> {
+    let index_dir = ensure_subdir(&app, SEARCH_DIR)?;
+    for dir_name in ["graphs", "runs"] {
+        let files = list_stored_files(&app, dir_name)?
+            .into_iter()
+            .filter_map(|file_name| {
+                let path = stored_file_path(&app, dir_name, &file_name).ok()?;
+                file_stamp(&path).map(|stamp| (file_name, stamp))
+            })
+            .collect::<Vec<_>>();
+        reconcile_search_index(&index_dir, dir_name, &files, |file_name| {
+            load_stored_file(&app, dir_name, file_name).ok()
+        })?;
+    }
+    Ok(search_index(&index_dir, &query, |dir_name, file_name| {
+        load_stored_file(&app, dir_name, file_name).ok()
+    }))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReportExportResult {
+    pub path: String,
+    pub format: ReportFormat,
+    pub node_count: usize,
+    pub citation_count: usize,
+}
+
+// Writes into a registered workspace just like workspace_write_markdown.
+#[tauri::command]
+pub fn run_export_report(
+    app: AppHandle,
+    name: String,
+    cwd: String,
+    format: Option<String>,
+    file_name: Option<String>,
+) -> Result<RunReportExportResult, WorkspacePathError> {
+    let format = ReportFormat::parse(format.as_deref())?;
+    let run = load_stored_file(&app, "runs", &name)?;
+    let (report, content) = render_report(&run, format);
+    let extension = format.extension();
+    let requested = file_name
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| {
+            let stem = report
+                .run_id
+                .clone()
+                .unwrap_or_else(|| name.trim().trim_end_matches(".json").to_string());
+            format!("report-{stem}")
+        });
+    let mut normalized_name = normalize_text_file_name(&requested)?;
+    if !normalized_name
+        .to_ascii_lowercase()
+        .ends_with(&format!(".{extension}"))
+    {
+        normalized_name = format!("{normalized_name}.{extension}");
+    }
+    let path = write_workspace_file(&app, &cwd, normalized_name, &content, "report")?;
+    Ok(RunReportExportResult {
+        path,
+        format,
+        node_count: report.nodes.len(),
+        citation_count: report.citations.len(),
+    })
+}
+
+#[tauri::command]
+pub fn run_artifact_add(
+    app: AppHandle,
+    name: String,
+    artifact_name: String,
+    media_type: Option<String>,
+    node_id: Option<String>,
+    bytes: Option<Vec<u8>>,
+    text: Option<String>,
+) -> Result<RunArtifact, String> {
+    let content = match (bytes, text) {
+        (Some(bytes), None) => bytes,
+        (None, Some(text)) => text.into_bytes(),
+        _ => return Err("exactly one of bytes or text is required".to_string()),
+    };
+    let media_type = media_type
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    add_artifact(
+        &runs_dir,
+        &normalize_file_name(&name)?,
+        &artifact_name,
+        &media_type,
+        node_id.as_deref(),
+        &content,
+    )
+}
+
+#[tauri::command]
+pub fn run_artifact_list(app: AppHandle, name: String) -> Result<Vec<RunArtifact>, String> {
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    Ok(list_artifacts(&runs_dir, &normalize_file_name(&name)?))
+}
+
+// Returns raw bytes (an ArrayBuffer on the JS side) instead of a JSON array.
+#[tauri::command]
+pub fn run_artifact_read(
+    app: AppHandle,
+    name: String,
+    id: String,
+) -> Result<tauri::ipc::Response, String> {
+    let runs_dir = ensure_subdir(&app, "runs")?;
+    let (_, bytes) = read_artifact(&runs_dir, &normalize_file_name(&name)?, &id)?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+#[tauri::command]
+pub fn run_directory(app: AppHandle) -> Result<String, String> {
+    let dir = ensure_subdir(&app, "runs")?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+fn load_workspace_roots(app: &AppHandle) -> Result<WorkspaceRootRegistry, String> {
+    let path = ensure_subdir(app, SETTINGS_DIR)?.join(WORKSPACE_ROOTS_NAME);
+    if !path.exists() {
+        return Ok(WorkspaceRootRegistry::default());
+    }
+    let raw = read_json_file(app, SETTINGS_DIR, WORKSPACE_ROOTS_NAME)?;
+    serde_json::from_value(raw).map_err(|e| format!("failed to parse workspace roots: {e}"))
+}
+
+fn update_workspace_roots<T>(
+    app: &AppHandle,
+    update: impl FnOnce(&mut WorkspaceRootRegistry) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = WORKSPACE_ROOTS_LOCK
+        .lock()
+        .map_err(|_| "workspace roots lock is poisoned".to_string())?;
+    let mut registry = load_workspace_roots(app)?;
+    let result = update(&mut registry)?;
+    let data = serde_json::to_value(&registry)
+        .map_err(|e| format!("failed to serialize workspace roots: {e}"))?;
+    write_json_file(app, SETTINGS_DIR, WORKSPACE_ROOTS_NAME, &data)?;
+    Ok(result)
+}
+
+pub(crate) fn resolve_workspace_path(
+    app: &AppHandle,
+    raw: &str,
+) -> Result<PathBuf, WorkspacePathError> {
+    load_workspace_roots(app)?.resolve(raw)
+}
+
+fn write_workspace_file(
+    app: &AppHandle,
+    cwd: &str,
+    normalized_name: String,
+    content: &str,
+    label: &str,
+) -> Result<String, WorkspacePathError> {
+    if cwd.trim().is_empty() {
+        return Err("cwd is required".into());
+    }
+    let path = resolve_workspace_path(app, cwd)?;
+
+    fs::create_dir_all(&path).map_err(|e| format!("failed to create workspace directory: {e}"))?;
+    if !path.is_dir() {
+        return Err("workspace path is not a directory".into());
+    }
+
+    let target = path.join(normalized_name);
+    write_atomic(&target, content.as_bytes())
+        .map_err(|e| format!("failed to write {label} file: {e}"))?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn workspace_root_list(app: AppHandle) -> Result<Vec<WorkspaceRoot>, String> {
+    Ok(load_workspace_roots(&app)?.roots)
+}
+
+// The webview alone knows the legacy cwd, so the user confirms it in the
+// folder picker before it becomes a root.
+#[tauri::command]
+pub async fn workspace_root_adopt_legacy(
+    app: AppHandle,
+    path: String,
+) -> Result<Option<WorkspaceRoot>, String> {
+    if !load_workspace_roots(&app)?.legacy_cwd_pending() {
+        return Ok(None);
+    }
+    let home = app.path().home_dir().ok();
+    let legacy = legacy_cwd_candidate(&path, home.as_deref())?;
+    let (tx, mut rx) = channel::<Option<String>>(1);
+    app.dialog()
+        .file()
+        .set_title("기존 작업 경로 확인")
+        .set_directory(&legacy)
+        .pick_folder(move |picked| {
+            let normalized = picked
+                .and_then(|path| path.into_path().ok())
+                .map(|path| path.to_string_lossy().to_string());
+            let _ = tx.try_send(normalized);
+        });
+    let Some(confirmed) = rx.recv().await else {
+        return Err("작업 경로 선택 대화상자 응답을 받지 못했습니다.".to_string());
+    };
+    update_workspace_roots(&app, |registry| {
+        registry.adopt_legacy_cwd(
+            &path,
+            confirmed.as_deref().unwrap_or_default(),
+            home.as_deref(),
+        )
+    })
+}
+
+#[tauri::command]
+pub fn workspace_root_remove(app: AppHandle, path: String) -> Result<bool, String> {
+    update_workspace_roots(&app, |registry| Ok(registry.remove(&path)))
+}
+
+#[tauri::command]
+pub fn workspace_write_markdown(
+    app: AppHandle,
+    cwd: String,
+    name: String,
+    content: String,
+) -> Result<String, WorkspacePathError> {
+    let normalized_name = normalize_markdown_file_name(&name)?;
+    write_workspace_file(&app, &cwd, normalized_name, &content, "markdown")
+}
+
+#[tauri::command]
+pub fn workspace_write_text(
+    app: AppHandle,
+    cwd: String,
+    name: String,
+    content: String,
+) -> Result<String, WorkspacePathError> {
+    let normalized_name = normalize_text_file_name(&name)?;
+    write_workspace_file(&app, &cwd, normalized_name, &content, "text")
+}
+
+#[tauri::command]
+pub fn workspace_read_text(app: AppHandle, path: String) -> Result<String, WorkspacePathError> {
+    if path.trim().is_empty() {
+        return Err("path is required".into());
+    }
+    let target = resolve_workspace_path(&app, &path)?;
+    if !target.exists() {
+        return Err("file not found".into());
+    }
+    if !target.is_file() {
+        return Err("target is not file".into());
+    }
+    Ok(fs::read_to_string(&target).map_err(|e| format!("failed to read text file: {e}"))?)
+}
+
+#[tauri::command]
+pub fn workspace_delete_file(app: AppHandle, path: String) -> Result<(), WorkspacePathError> {
+    if path.trim().is_empty() {
+        return Err("path is required".into());
+    }
+    let target = resolve_workspace_path(&app, &path)?;
+    if !target.exists() {
+        return Err("file not found".into());
+    }
+    if !target.is_file() {
+        return Err("target is not file".into());
+    }
+    let registry = load_workspace_roots(&app)?;
+    let root = registry
+        .containing_root(&target)
+        .ok_or_else(|| WorkspacePathError::from("workspace root not found"))?;
+    move_to_workspace_trash(&root, "workspace", &target, "deleted")?;
+    Ok(())
+}
+
+// App data trash always applies; a registered `cwd` adds its workspace trash
+// and the trash of the root that contains it.
+fn trash_roots(app: &AppHandle, cwd: Option<&str>) -> Result<Vec<PathBuf>, WorkspacePathError> {
+    let mut roots = vec![ensure_subdir(app, TRASH_DIR)?];
+    let Some(cwd) = cwd.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(roots);
+    };
+    let registry = load_workspace_roots(app)?;
+    let workspace = registry.resolve(cwd)?;
+    for dir in [
+        Some(workspace.clone()),
+        registry.containing_root(&workspace),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        let trash_root = dir.join(WORKSPACE_TRASH_DIR);
+        if !roots.contains(&trash_root) {
+            roots.push(trash_root);
+        }
+    }
+    Ok(roots)
+}
+
+#[tauri::command]
+pub fn trash_list(
+    app: AppHandle,
+    area: Option<String>,
+    cwd: Option<String>,
+) -> Result<Vec<TrashEntry>, WorkspacePathError> {
+    let mut entries = trash_roots(&app, cwd.as_deref())?
+        .iter()
+        .flat_map(|root| list_trash(root, area.as_deref()))
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at).then(b.id.cmp(&a.id)));
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn trash_restore(
+    app: AppHandle,
+    area: String,
+    id: String,
+    cwd: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<TrashEntry, WorkspacePathError> {
+    let roots = trash_roots(&app, cwd.as_deref())?;
+    let Some((index, trash_root, entry)) = roots.iter().enumerate().find_map(|(index, root)| {
+        read_trash_entry(root, &area, &id)
+            .ok()
+            .map(|entry| (index, root, entry))
+    }) else {
+        return Err("trash entry not found".into());
+    };
+    if entry.area != area {
+        return Err(format!("trash entry area mismatch: {}", entry.area).into());
+    }
+    // Workspace trash is writable by anything working in the workspace, so its
+    // metadata is never trusted to pick where a restore may write.
+    let in_workspace_trash = index > 0;
+    if in_workspace_trash || WORKSPACE_TRASH_AREAS.contains(&entry.area.as_str()) {
+        resolve_workspace_path(&app, &entry.original_path)?;
+    }
+    let restored = restore_from_trash(trash_root, &area, &id, overwrite.unwrap_or(false))?;
+    refresh_search_file(&app, &restored.area, &restored.file_name)?;
+    Ok(restored)
+}
+
+#[tauri::command]
+pub fn trash_empty(
+    app: AppHandle,
+    area: Option<String>,
+    cwd: Option<String>,
+    older_than_days: Option<u64>,
+) -> Result<usize, WorkspacePathError> {
+    let mut removed = 0;
+    for root in trash_roots(&app, cwd.as_deref())? {
+        removed += empty_trash(&root, area.as_deref(), older_than_days)?;
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn dialog_pick_directory(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let (tx, mut rx) = channel::<Option<String>>(1);
+    app.dialog()
+        .file()
+        .set_title("작업 경로 선택")
+        .pick_folder(move |picked| {
+            let normalized = picked
+                .and_then(|path| path.into_path().ok())
+                .map(|path| path.to_string_lossy().to_string());
+            let _ = tx.try_send(normalized);
+        });
+
+    match rx.recv().await {
+        Some(Some(path)) => {
+            update_workspace_roots(&app, |registry| registry.register(&path))?;
+            Ok(Some(path))
+        }
+        Some(None) => Ok(None),
+        None => Err("작업 경로 선택 대화상자 응답을 받지 못했습니다.".to_string()),
+    }
+}
+
+#[tauri::command]
+pub async fn dialog_pick_knowledge_files(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let (tx, mut rx) = channel::<Vec<String>>(1);
+    app.dialog()
+        .file()
+        .set_title("첨부 자료 선택")
+        .add_filter(
+            "지원 파일",
+            &[
+                "txt", "md", "json", "csv", "ts", "tsx", "js", "jsx", "py", "rs", "go", "java",
+                "cs", "html", "css", "sql", "yaml", "yml", "pdf", "docx",
+            ],
+        )
+        .pick_files(move |picked| {
+            let unique_paths = picked
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|path| path.into_path().ok())
+                .map(|path| path.to_string_lossy().to_string())
+                .filter(|path| !path.trim().is_empty())
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let _ = tx.try_send(unique_paths);
+        });
+
+    match rx.recv().await {
+        Some(paths) => Ok(paths),
+        None => Err("첨부 자료 선택 대화상자 응답을 받지 못했습니다.".to_string()),
+    }
+}
@@ -0,0 +1,192 @@
+use super::{
+    atomic_file::{is_own_temp_file, is_temp_file},
+    graph_index::GRAPH_INDEX_DIR,
+    graph_revisions::GRAPH_REVISION_DIR,
+    run_store::RUN_ARTIFACTS_DIR,
+};
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const QUARANTINE_DIR: &str = ".quarantine";
+// Derived data, artifact payloads and history are written by the app itself
+// and may legitimately hold non-document JSON, so they are never quarantined.
+const SKIPPED_DIRS: [&str; 5] = [
+    QUARANTINE_DIR,
+    RUN_ARTIFACTS_DIR,
+    GRAPH_INDEX_DIR,
+    GRAPH_REVISION_DIR,
+    "trash",
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRecoveryReport {
+    pub scanned: usize,
+    pub removed_temp_files: Vec<String>,
+    pub quarantined: Vec<QuarantinedFile>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedFile {
+    pub original_path: String,
+    pub quarantine_path: String,
+    pub reason: String,
+}
+
+impl StorageRecoveryReport {
+    pub fn merge(&mut self, other: StorageRecoveryReport) {
+        self.scanned += other.scanned;
+        self.removed_temp_files.extend(other.removed_temp_files);
+        self.quarantined.extend(other.quarantined);
+        self.errors.extend(other.errors);
+    }
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false)
+}
+
+fn quarantine_file(path: &Path, stamp: &str) -> Result<PathBuf, String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent directory", path.display()))?;
+    let quarantine_dir = parent.join(QUARANTINE_DIR);
+    fs::create_dir_all(&quarantine_dir)
+        .map_err(|e| format!("failed to create quarantine directory: {e}"))?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("unknown.json");
+    let target = quarantine_dir.join(format!("{stamp}-{file_name}"));
+    fs::rename(path, &target)
+        .map_err(|e| format!("failed to quarantine {}: {e}", path.display()))?;
+    Ok(target)
+}
+
+pub fn recover_json_dir(dir: &Path, stamp: &str) -> StorageRecoveryReport {
+    let mut report = StorageRecoveryReport::default();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            if dir.exists() {
+                report
+                    .errors
+                    .push(format!("failed to read {}: {error}", dir.display()));
+            }
+            return report;
+        }
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|row| row.path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            let skipped = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| SKIPPED_DIRS.contains(&name))
+                .unwrap_or(false);
+            if !skipped {
+                report.merge(recover_json_dir(&path, stamp));
+            }
+            continue;
+        }
+
+        // Recovery runs while the app is already saving; this process's own
+        // temp files belong to writes that are still in flight.
+        if is_own_temp_file(&path) {
+            continue;
+        }
+        if is_temp_file(&path) {
+            match fs::remove_file(&path) {
+                Ok(_) => report
+                    .removed_temp_files
+                    .push(path.to_string_lossy().to_string()),
+                Err(error) => report.errors.push(format!(
+                    "failed to remove temp file {}: {error}",
+                    path.display()
+                )),
+            }
+            continue;
+        }
+
+        if !is_json_file(&path) {
+            continue;
+        }
+        report.scanned += 1;
+
+        let reason = match fs::read_to_string(&path) {
+            Ok(raw) => match serde_json::from_str::<serde_json::Value>(&raw) {
+                Ok(_) => continue,
+                Err(error) => format!("invalid JSON: {error}"),
+            },
+            Err(error) => format!("unreadable: {error}"),
+        };
+
+        match quarantine_file(&path, stamp) {
+            Ok(target) => report.quarantined.push(QuarantinedFile {
+                original_path: path.to_string_lossy().to_string(),
+                quarantine_path: target.to_string_lossy().to_string(),
+                reason,
+            }),
+            Err(error) => report.errors.push(error),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantines_truncated_json_and_removes_temp_files() {
+        let root = std::env::temp_dir().join(format!(
+            "rail_storage_recovery_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let nested = root.join("run-1");
+        let revisions = root.join(".revisions/sample");
+        let artifacts = nested.join("artifacts");
+        fs::create_dir_all(&revisions).expect("create revision dir");
+        fs::create_dir_all(&artifacts).expect("create artifact dir");
+        fs::write(root.join("good.json"), r#"{"version":3}"#).expect("write good");
+        fs::write(root.join("broken.json"), r#"{"version":3,"nodes":["#).expect("write broken");
+        fs::write(root.join(".good.json.1.2.tmp"), "partial").expect("write temp");
+        let in_flight = root.join(format!(".good.json.{}.3.tmp", std::process::id()));
+        fs::write(&in_flight, "partial").expect("write in-flight temp");
+        fs::write(nested.join("run.json"), "").expect("write empty run");
+        fs::write(revisions.join("20260101T000000000Z.json"), "").expect("write empty revision");
+        fs::write(artifacts.join("payload.json"), "not json").expect("write artifact");
+
+        let report = recover_json_dir(&root, "stamp");
+
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.removed_temp_files.len(), 1);
+        assert!(in_flight.exists());
+        assert_eq!(report.quarantined.len(), 2);
+        assert!(root.join("good.json").exists());
+        assert!(!root.join("broken.json").exists());
+        assert!(root.join(".quarantine/stamp-broken.json").exists());
+        assert!(nested.join(".quarantine/stamp-run.json").exists());
+        assert!(revisions.join("20260101T000000000Z.json").exists());
+        assert!(artifacts.join("payload.json").exists());
+
+        let rerun = recover_json_dir(&root, "later");
+        assert!(rerun.quarantined.is_empty());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+const DAY_MS: u128 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+    #[serde(default = "default_keep_pinned")]
+    pub keep_pinned: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_count: None,
+            max_total_bytes: None,
+            keep_pinned: true,
+        }
+    }
+}
+
+fn default_keep_pinned() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub runs: RetentionPolicy,
+    #[serde(default)]
+    pub dashboard_raw: RetentionPolicy,
+    #[serde(default)]
+    pub dashboard_snapshots: RetentionPolicy,
+    #[serde(default)]
+    pub pinned: Vec<String>,
+}
+
+impl RetentionConfig {
+    pub fn is_pinned(&self, path: &Path) -> bool {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let full_path = path.to_string_lossy();
+        // Runs used to be `<name>.json` files, so older pins name the file.
+        let legacy_names = path
+            .is_dir()
+            .then(|| (format!("{file_name}.json"), format!("{full_path}.json")));
+        self.pinned.iter().map(|row| row.trim()).any(|row| {
+            !row.is_empty()
+                && (row == file_name
+                    || row == full_path
+                    || legacy_names
+                        .as_ref()
+                        .is_some_and(|(name, path)| row == name || row == path))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetentionCandidate {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub modified_ms: u128,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageGcItem {
+    pub area: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageGcReport {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub kept: usize,
+    pub freed_bytes: u64,
+    pub deleted: Vec<StorageGcItem>,
+    pub errors: Vec<String>,
+}
+
+fn modified_ms(meta: &fs::Metadata) -> u128 {
+    meta.modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0)
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        dir_size(&path)
+                    } else {
+                        entry.metadata().map(|meta| meta.len()).unwrap_or(0)
+                    }
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+// Subdirectories containing `marker` (e.g. a run's `run.json`) are candidates
+// as a whole, aged by the marker file.
+pub fn collect_dir_candidates(
+    dir: &Path,
+    marker: &str,
+    config: &RetentionConfig,
+) -> Result<Vec<RetentionCandidate>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| format!("failed to read {}: {e}", dir.display()))? {
+        let entry = entry.map_err(|e| format!("failed to read directory entry: {e}"))?;
+        let path = entry.path();
+        let Ok(meta) = fs::metadata(path.join(marker)) else {
+            continue;
+        };
+        if !path.is_dir() || !meta.is_file() {
+            continue;
+        }
+        out.push(RetentionCandidate {
+            pinned: config.is_pinned(&path),
+            size_bytes: dir_size(&path),
+            modified_ms: modified_ms(&meta),
+            path,
+        });
+    }
+    Ok(out)
+}
+
+pub fn collect_candidates(
+    dir: &Path,
+    extensions: &[&str],
+    config: &RetentionConfig,
+) -> Result<Vec<RetentionCandidate>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| format!("failed to read {}: {e}", dir.display()))? {
+        let entry = entry.map_err(|e| format!("failed to read directory entry: {e}"))?;
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let matches_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| extensions.iter().any(|row| ext.eq_ignore_ascii_case(row)))
+            .unwrap_or(false);
+        if !matches_extension {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        out.push(RetentionCandidate {
+            pinned: config.is_pinned(&path),
+            path,
+            size_bytes: meta.len(),
+            modified_ms: modified_ms(&meta),
+        });
+    }
+    Ok(out)
+}
+
+// Walks newest to oldest; pinned files are kept and do not consume the
+// count or byte budgets of the policy.
+pub fn plan_retention(
+    area: &str,
+    mut candidates: Vec<RetentionCandidate>,
+    policy: &RetentionPolicy,
+    now_ms: u128,
+) -> (usize, Vec<StorageGcItem>) {
+    candidates.sort_by(|a, b| {
+        b.modified_ms
+            .cmp(&a.modified_ms)
+            .then_with(|| b.path.cmp(&a.path))
+    });
+
+    let max_age_ms = policy.max_age_days.map(|days| u128::from(days) * DAY_MS);
+    let mut kept = 0usize;
+    let mut kept_count = 0usize;
+    let mut kept_bytes = 0u64;
+    let mut deleted = Vec::new();
+
+    for candidate in candidates {
+        if candidate.pinned && policy.keep_pinned {
+            kept += 1;
+            continue;
+        }
+
+        let age_ms = now_ms.saturating_sub(candidate.modified_ms);
+        let reason = if max_age_ms.map(|max| age_ms > max).unwrap_or(false) {
+            Some("maxAge")
+        } else if policy
+            .max_count
+            .map(|max| kept_count >= max)
+            .unwrap_or(false)
+        {
+            Some("maxCount")
+        } else if policy
+            .max_total_bytes
+            .map(|max| kept_bytes.saturating_add(candidate.size_bytes) > max)
+            .unwrap_or(false)
+        {
+            Some("maxTotalBytes")
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => deleted.push(StorageGcItem {
+                area: area.to_string(),
+                path: candidate.path.to_string_lossy().to_string(),
+                size_bytes: candidate.size_bytes,
+                reason: reason.to_string(),
+            }),
+            None => {
+                kept += 1;
+                kept_count += 1;
+                kept_bytes = kept_bytes.saturating_add(candidate.size_bytes);
+            }
+        }
+    }
+
+    (kept, deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, size_bytes: u64, age_days: u128, pinned: bool) -> RetentionCandidate {
+        RetentionCandidate {
+            path: PathBuf::from(name),
+            size_bytes,
+            modified_ms: (1000 - age_days) * DAY_MS,
+            pinned,
+        }
+    }
+
+    #[test]
+    fn applies_age_count_and_byte_limits_in_order() {
+        let candidates = vec![
+            candidate("new.json", 10, 0, false),
+            candidate("mid.json", 10, 1, false),
+            candidate("big.json", 500, 2, false),
+            candidate("pinned.json", 10, 50, true),
+            candidate("old.json", 10, 40, false),
+        ];
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            max_count: Some(3),
+            max_total_bytes: Some(100),
+            keep_pinned: true,
+        };
+
+        let (kept, deleted) = plan_retention("runs", candidates, &policy, 1000 * DAY_MS);
+        let reasons = deleted
+            .iter()
+            .map(|row| (row.path.as_str(), row.reason.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(kept, 3);
+        assert_eq!(
+            reasons,
+            vec![("big.json", "maxTotalBytes"), ("old.json", "maxAge")]
+        );
+    }
+
+    #[test]
+    fn empty_policy_keeps_everything() {
+        let candidates = vec![candidate("a.json", 10, 400, false)];
+        let (kept, deleted) = plan_retention(
+            "runs",
+            candidates,
+            &RetentionPolicy::default(),
+            1000 * DAY_MS,
+        );
+        assert_eq!(kept, 1);
+        assert!(deleted.is_empty());
+    }
+
+    #[test]
+    fn matches_pins_by_file_name_or_path() {
+        let config = RetentionConfig {
+            pinned: vec!["keep.json".to_string(), "/tmp/raw/a.md".to_string()],
+            ..RetentionConfig::default()
+        };
+        assert!(config.is_pinned(Path::new("/data/runs/keep.json")));
+        assert!(config.is_pinned(Path::new("/tmp/raw/a.md")));
+        assert!(!config.is_pinned(Path::new("/tmp/raw/b.md")));
+    }
+
+    #[test]
+    fn run_directories_match_legacy_json_pins() {
+        let root = std::env::temp_dir().join(format!(
+            "rail_retention_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let run_dir = root.join("keep");
+        fs::create_dir_all(&run_dir).expect("create run dir");
+        let config = RetentionConfig {
+            pinned: vec!["keep.json".to_string()],
+            ..RetentionConfig::default()
+        };
+        assert!(config.is_pinned(&run_dir));
+        assert!(!config.is_pinned(&root.join("keep.md")));
+        assert!(!config.is_pinned(&root.join("other")));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
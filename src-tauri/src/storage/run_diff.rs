@@ -0,0 +1,374 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+const DIFF_CONTEXT_LINES: usize = 3;
+// Above this many LCS cells the diff falls back to a whole-text replacement.
+const MAX_LCS_CELLS: usize = 4_000_000;
+const TERMINAL_STATUSES: [&str; 5] = ["done", "low_quality", "failed", "skipped", "cancelled"];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDiffLine {
+    pub op: String,
+    pub base_line: Option<usize>,
+    pub target_line: Option<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDiff {
+    pub added: usize,
+    pub removed: usize,
+    pub lines: Vec<TextDiffLine>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunNodeDiff {
+    pub node_id: String,
+    pub changed: bool,
+    pub base_status: Option<String>,
+    pub target_status: Option<String>,
+    pub status_changed: bool,
+    pub base_duration_ms: Option<i64>,
+    pub target_duration_ms: Option<i64>,
+    pub duration_delta_ms: Option<i64>,
+    pub output_changed: bool,
+    pub output_diff: Option<TextDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDiff {
+    pub base_run_id: Option<String>,
+    pub target_run_id: Option<String>,
+    pub base_duration_ms: Option<i64>,
+    pub target_duration_ms: Option<i64>,
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub changed_count: usize,
+    pub nodes: Vec<RunNodeDiff>,
+    pub final_answer_diff: Option<TextDiff>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct NodeSnapshot {
+    pub(super) status: Option<String>,
+    pub(super) started_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub(super) finished_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub(super) duration_ms: Option<i64>,
+    pub(super) output: Option<String>,
+}
+
+fn parse_time(value: Option<&Value>) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_rfc3339(value?.as_str()?.trim()).ok()
+}
+
+fn text_of(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+// Feed posts carry the rendered node result; the markdown attachment is the
+// full output and the summary is the fallback.
+fn feed_post_output(post: &Value) -> Option<String> {
+    let attachments = post
+        .get("attachments")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    attachments
+        .iter()
+        .find(|row| row.get("kind").and_then(Value::as_str) == Some("markdown"))
+        .or_else(|| attachments.first())
+        .and_then(|row| text_of(row.get("content")))
+        .or_else(|| text_of(post.get("summary")))
+}
+
+pub(super) fn collect_nodes(run: &Value) -> BTreeMap<String, NodeSnapshot> {
+    let mut nodes: BTreeMap<String, NodeSnapshot> = BTreeMap::new();
+
+    for node in run
+        .pointer("/graphSnapshot/nodes")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        if let Some(id) = text_of(node.get("id")) {
+            nodes.entry(id).or_default();
+        }
+    }
+
+    for transition in run
+        .get("transitions")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        let Some(node_id) = text_of(transition.get("nodeId")) else {
+            continue;
+        };
+        let status = text_of(transition.get("status"));
+        let at = parse_time(transition.get("at"));
+        let snapshot = nodes.entry(node_id).or_default();
+        match status.as_deref() {
+            Some("running") => {
+                if snapshot.started_at.is_none() {
+                    snapshot.started_at = at;
+                }
+            }
+            Some(value) if TERMINAL_STATUSES.contains(&value) => snapshot.finished_at = at,
+            _ => {}
+        }
+        if status.is_some() {
+            snapshot.status = status;
+        }
+    }
+
+    for post in run
+        .get("feedPosts")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        let Some(node_id) = text_of(post.get("nodeId")) else {
+            continue;
+        };
+        let snapshot = nodes.entry(node_id).or_default();
+        if snapshot.output.is_none() {
+            snapshot.output = feed_post_output(post);
+        }
+        if snapshot.duration_ms.is_none() {
+            snapshot.duration_ms = post.pointer("/evidence/durationMs").and_then(Value::as_i64);
+        }
+        if snapshot.status.is_none() {
+            snapshot.status = text_of(post.get("status"));
+        }
+    }
+
+    for snapshot in nodes.values_mut() {
+        if let (Some(start), Some(end)) = (snapshot.started_at, snapshot.finished_at) {
+            snapshot.duration_ms = Some((end - start).num_milliseconds());
+        }
+    }
+    nodes
+}
+
+pub(super) fn run_duration_ms(run: &Value) -> Option<i64> {
+    let start = parse_time(run.get("startedAt"))?;
+    let end = parse_time(run.get("finishedAt"))?;
+    Some((end - start).num_milliseconds())
+}
+
+fn push_line(
+    lines: &mut Vec<TextDiffLine>,
+    op: &str,
+    base: Option<usize>,
+    target: Option<usize>,
+    text: &str,
+) {
+    lines.push(TextDiffLine {
+        op: op.to_string(),
+        base_line: base,
+        target_line: target,
+        text: text.to_string(),
+    });
+}
+
+// Line diff over the longest common subsequence, keeping a few lines of
+// unchanged context around each change.
+pub fn diff_text(base: &str, target: &str) -> TextDiff {
+    let base_lines = base.lines().collect::<Vec<_>>();
+    let target_lines = target.lines().collect::<Vec<_>>();
+    let (n, m) = (base_lines.len(), target_lines.len());
+
+    let mut ops: Vec<TextDiffLine> = Vec::new();
+    if n.saturating_mul(m) > MAX_LCS_CELLS {
+        for (index, line) in base_lines.iter().enumerate() {
+            push_line(&mut ops, "delete", Some(index + 1), None, line);
+        }
+        for (index, line) in target_lines.iter().enumerate() {
+            push_line(&mut ops, "insert", None, Some(index + 1), line);
+        }
+    } else {
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if base_lines[i] == target_lines[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && base_lines[i] == target_lines[j] {
+                push_line(&mut ops, "equal", Some(i + 1), Some(j + 1), base_lines[i]);
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                push_line(&mut ops, "delete", Some(i + 1), None, base_lines[i]);
+                i += 1;
+            } else {
+                push_line(&mut ops, "insert", None, Some(j + 1), target_lines[j]);
+                j += 1;
+            }
+        }
+    }
+
+    let changed_positions = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| row.op != "equal")
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let keep = |index: usize| {
+        changed_positions
+            .iter()
+            .any(|changed| index.abs_diff(*changed) <= DIFF_CONTEXT_LINES)
+    };
+
+    let mut diff = TextDiff::default();
+    for (index, row) in ops.into_iter().enumerate() {
+        match row.op.as_str() {
+            "insert" => diff.added += 1,
+            "delete" => diff.removed += 1,
+            _ => {}
+        }
+        if row.op != "equal" || keep(index) {
+            diff.lines.push(row);
+        }
+    }
+    diff
+}
+
+pub fn diff_runs(base: &Value, target: &Value) -> RunDiff {
+    let base_nodes = collect_nodes(base);
+    let target_nodes = collect_nodes(target);
+    let ids = base_nodes
+        .keys()
+        .chain(target_nodes.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let mut diff = RunDiff {
+        base_run_id: text_of(base.get("runId")),
+        target_run_id: text_of(target.get("runId")),
+        base_duration_ms: run_duration_ms(base),
+        target_duration_ms: run_duration_ms(target),
+        ..RunDiff::default()
+    };
+
+    for id in ids {
+        let (before, after) = match (base_nodes.get(&id), target_nodes.get(&id)) {
+            (Some(before), Some(after)) => (before, after),
+            (None, Some(_)) => {
+                diff.added_nodes.push(id);
+                continue;
+            }
+            (Some(_), None) => {
+                diff.removed_nodes.push(id);
+                continue;
+            }
+            (None, None) => continue,
+        };
+        let status_changed = before.status != after.status;
+        let output_changed = before.output != after.output;
+        let output_diff = output_changed.then(|| {
+            diff_text(
+                before.output.as_deref().unwrap_or(""),
+                after.output.as_deref().unwrap_or(""),
+            )
+        });
+        let duration_delta_ms = match (before.duration_ms, after.duration_ms) {
+            (Some(before), Some(after)) => Some(after - before),
+            _ => None,
+        };
+        let changed = status_changed || output_changed;
+        if changed {
+            diff.changed_count += 1;
+        }
+        diff.nodes.push(RunNodeDiff {
+            node_id: id,
+            changed,
+            base_status: before.status.clone(),
+            target_status: after.status.clone(),
+            status_changed,
+            base_duration_ms: before.duration_ms,
+            target_duration_ms: after.duration_ms,
+            duration_delta_ms,
+            output_changed,
+            output_diff,
+        });
+    }
+
+    let base_answer = text_of(base.get("finalAnswer")).unwrap_or_default();
+    let target_answer = text_of(target.get("finalAnswer")).unwrap_or_default();
+    if base_answer != target_answer {
+        diff.final_answer_diff = Some(diff_text(&base_answer, &target_answer));
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn text_diff_marks_changed_lines_with_context() {
+        let diff = diff_text("a\nb\nc\nd\ne\nf\ng\nh\ni", "a\nb\nc\nd\nE\nf\ng\nh\ni");
+        assert_eq!(diff.added, 1);
+        assert_eq!(diff.removed, 1);
+        let ops = diff
+            .lines
+            .iter()
+            .map(|row| format!("{}:{}", row.op, row.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ops,
+            vec![
+                "equal:b", "equal:c", "equal:d", "delete:e", "insert:E", "equal:f", "equal:g",
+                "equal:h"
+            ]
+        );
+    }
+
+    #[test]
+    fn aligns_nodes_by_id() {
+        let base = json!({
+            "runId": "r1",
+            "graphSnapshot": { "nodes": [{ "id": "a" }, { "id": "b" }] },
+            "transitions": [
+                { "at": "2026-01-01T00:00:00Z", "nodeId": "a", "status": "running" },
+                { "at": "2026-01-01T00:00:02Z", "nodeId": "a", "status": "done" },
+                { "at": "2026-01-01T00:00:02Z", "nodeId": "b", "status": "failed" }
+            ],
+            "feedPosts": [{ "nodeId": "a", "summary": "old answer" }]
+        });
+        let target = json!({
+            "runId": "r2",
+            "graphSnapshot": { "nodes": [{ "id": "a" }, { "id": "c" }] },
+            "transitions": [
+                { "at": "2026-01-01T00:00:00Z", "nodeId": "a", "status": "running" },
+                { "at": "2026-01-01T00:00:05Z", "nodeId": "a", "status": "done" }
+            ],
+            "feedPosts": [{ "nodeId": "a", "summary": "new answer" }]
+        });
+
+        let diff = diff_runs(&base, &target);
+        assert_eq!(diff.added_nodes, vec!["c"]);
+        assert_eq!(diff.removed_nodes, vec!["b"]);
+        assert_eq!(diff.nodes.len(), 1);
+        let node = &diff.nodes[0];
+        assert!(node.output_changed && !node.status_changed);
+        assert_eq!(node.duration_delta_ms, Some(3000));
+        assert_eq!(node.output_diff.as_ref().map(|row| row.added), Some(1));
+    }
+}
@@ -0,0 +1,441 @@
+use super::atomic_file::write_atomic;
+use super::run_store::run_file_path;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+pub const RUN_INDEX_DIR: &str = ".index";
+const RUN_INDEX_FILE: &str = "manifest.json";
+const RUN_INDEX_VERSION: u64 = 1;
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+const ANSWER_PREVIEW_CHARS: usize = 280;
+
+// Serializes read-modify-write cycles on the manifest across command threads.
+static RUN_INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunIndexEntry {
+    pub file_name: String,
+    pub run_id: String,
+    pub graph_name: Option<String>,
+    pub question: Option<String>,
+    pub status: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub node_count: usize,
+    pub answer_preview: Option<String>,
+    pub size_bytes: u64,
+    pub modified_ms: u128,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunIndexManifest {
+    version: u64,
+    #[serde(default)]
+    entries: BTreeMap<String, RunIndexEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunQuery {
+    pub graph_name: Option<String>,
+    pub status: Option<String>,
+    pub started_after: Option<String>,
+    pub started_before: Option<String>,
+    pub text: Option<String>,
+    pub sort: Option<String>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunQueryPage {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub items: Vec<RunIndexEntry>,
+}
+
+fn manifest_path(runs_dir: &Path) -> PathBuf {
+    runs_dir.join(RUN_INDEX_DIR).join(RUN_INDEX_FILE)
+}
+
+fn load_manifest(runs_dir: &Path) -> RunIndexManifest {
+    fs::read_to_string(manifest_path(runs_dir))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<RunIndexManifest>(&raw).ok())
+        .filter(|manifest| manifest.version == RUN_INDEX_VERSION)
+        .unwrap_or(RunIndexManifest {
+            version: RUN_INDEX_VERSION,
+            entries: BTreeMap::new(),
+        })
+}
+
+fn save_manifest(runs_dir: &Path, manifest: &RunIndexManifest) -> Result<(), String> {
+    let path = manifest_path(runs_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create run index directory: {e}"))?;
+    }
+    let json = serde_json::to_string(manifest)
+        .map_err(|e| format!("failed to serialize run index: {e}"))?;
+    write_atomic(&path, json.as_bytes()).map_err(|e| format!("failed to write run index: {e}"))
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, u128)> {
+    let meta = fs::metadata(path).ok()?;
+    let modified_ms = meta
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis())
+        .unwrap_or(0);
+    Some((meta.len(), modified_ms))
+}
+
+fn non_empty_string(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn derive_run_status(run: &Value) -> String {
+    if let Some(status) = non_empty_string(run.get("status")) {
+        return status;
+    }
+    let transitions = run
+        .get("transitions")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let has_status = |expected: &str| {
+        transitions
+            .iter()
+            .any(|row| row.get("status").and_then(Value::as_str) == Some(expected))
+    };
+    if has_status("failed") {
+        "failed".to_string()
+    } else if has_status("cancelled") {
+        "cancelled".to_string()
+    } else if run.get("finishedAt").and_then(Value::as_str).is_some() {
+        "done".to_string()
+    } else {
+        "running".to_string()
+    }
+}
+
+pub fn summarize_run(
+    file_name: &str,
+    run: &Value,
+    size_bytes: u64,
+    modified_ms: u128,
+) -> RunIndexEntry {
+    let answer_preview = non_empty_string(run.get("finalAnswer")).map(|answer| {
+        answer
+            .chars()
+            .take(ANSWER_PREVIEW_CHARS)
+            .collect::<String>()
+    });
+    RunIndexEntry {
+        file_name: file_name.to_string(),
+        run_id: non_empty_string(run.get("runId"))
+            .unwrap_or_else(|| file_name.trim_end_matches(".json").to_string()),
+        graph_name: non_empty_string(run.get("graphName"))
+            .or_else(|| non_empty_string(run.get("workflowGroupName"))),
+        question: non_empty_string(run.get("question")),
+        status: derive_run_status(run),
+        started_at: non_empty_string(run.get("startedAt")),
+        finished_at: non_empty_string(run.get("finishedAt")),
+        node_count: run
+            .pointer("/graphSnapshot/nodes")
+            .and_then(Value::as_array)
+            .map(Vec::len)
+            .unwrap_or(0),
+        answer_preview,
+        size_bytes,
+        modified_ms,
+    }
+}
+
+pub fn upsert_run(runs_dir: &Path, file_name: &str, run: &Value) -> Result<RunIndexEntry, String> {
+    let _guard = RUN_INDEX_LOCK
+        .lock()
+        .map_err(|_| "run index lock is poisoned".to_string())?;
+    let (size_bytes, modified_ms) =
+        file_stamp(&run_file_path(runs_dir, file_name)).unwrap_or((0, 0));
+    let mut manifest = load_manifest(runs_dir);
+    let entry = summarize_run(file_name, run, size_bytes, modified_ms);
+    manifest
+        .entries
+        .insert(file_name.to_string(), entry.clone());
+    save_manifest(runs_dir, &manifest)?;
+    Ok(entry)
+}
+
+pub fn remove_run(runs_dir: &Path, file_name: &str) -> Result<(), String> {
+    let _guard = RUN_INDEX_LOCK
+        .lock()
+        .map_err(|_| "run index lock is poisoned".to_string())?;
+    let mut manifest = load_manifest(runs_dir);
+    if manifest.entries.remove(file_name).is_some() {
+        save_manifest(runs_dir, &manifest)?;
+    }
+    Ok(())
+}
+
+// Brings the manifest in line with the files on disk, re-reading only runs
+// whose size or mtime changed since they were indexed.
+pub fn reconcile_run_index(
+    runs_dir: &Path,
+    file_names: &[String],
+) -> Result<Vec<RunIndexEntry>, String> {
+    let _guard = RUN_INDEX_LOCK
+        .lock()
+        .map_err(|_| "run index lock is poisoned".to_string())?;
+    let mut manifest = load_manifest(runs_dir);
+    let mut changed = false;
+    let mut next = BTreeMap::new();
+
+    for file_name in file_names {
+        let path = run_file_path(runs_dir, file_name);
+        let Some((size_bytes, modified_ms)) = file_stamp(&path) else {
+            continue;
+        };
+        let existing = manifest.entries.remove(file_name);
+        let entry = match existing {
+            Some(entry) if entry.size_bytes == size_bytes && entry.modified_ms == modified_ms => {
+                entry
+            }
+            _ => {
+                changed = true;
+                let Some(run) = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+                else {
+                    continue;
+                };
+                summarize_run(file_name, &run, size_bytes, modified_ms)
+            }
+        };
+        next.insert(file_name.clone(), entry);
+    }
+
+    if !manifest.entries.is_empty() {
+        changed = true;
+    }
+    manifest.entries = next;
+    if changed {
+        save_manifest(runs_dir, &manifest)?;
+    }
+    Ok(manifest.entries.into_values().collect())
+}
+
+fn timestamp_millis(raw: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(raw.trim())
+        .ok()
+        .map(|value| value.timestamp_millis())
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|value| value.and_utc().timestamp_millis())
+        })
+}
+
+fn matches_query(entry: &RunIndexEntry, query: &RunQuery) -> bool {
+    if let Some(graph_name) = query
+        .graph_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        if entry.graph_name.as_deref() != Some(graph_name) {
+            return false;
+        }
+    }
+    if let Some(status) = query
+        .status
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        if !entry.status.eq_ignore_ascii_case(status) {
+            return false;
+        }
+    }
+
+    let started_ms = entry.started_at.as_deref().and_then(timestamp_millis);
+    if let Some(after) = query.started_after.as_deref().and_then(timestamp_millis) {
+        if started_ms.map(|value| value < after).unwrap_or(true) {
+            return false;
+        }
+    }
+    if let Some(before) = query.started_before.as_deref().and_then(timestamp_millis) {
+        if started_ms.map(|value| value >= before).unwrap_or(true) {
+            return false;
+        }
+    }
+
+    if let Some(text) = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        let needle = text.to_lowercase();
+        let haystacks = [
+            Some(entry.run_id.as_str()),
+            Some(entry.file_name.as_str()),
+            entry.graph_name.as_deref(),
+            entry.question.as_deref(),
+            entry.answer_preview.as_deref(),
+        ];
+        if !haystacks
+            .into_iter()
+            .flatten()
+            .any(|value| value.to_lowercase().contains(&needle))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn query_runs(entries: Vec<RunIndexEntry>, query: &RunQuery) -> RunQueryPage {
+    let mut rows = entries
+        .into_iter()
+        .filter(|entry| matches_query(entry, query))
+        .collect::<Vec<_>>();
+
+    let started = |entry: &RunIndexEntry| {
+        entry
+            .started_at
+            .as_deref()
+            .and_then(timestamp_millis)
+            .unwrap_or(i64::MIN)
+    };
+    match query.sort.as_deref().unwrap_or("startedAtDesc") {
+        "startedAtAsc" => rows.sort_by(|a, b| {
+            started(a)
+                .cmp(&started(b))
+                .then_with(|| a.file_name.cmp(&b.file_name))
+        }),
+        "nameAsc" => rows.sort_by(|a, b| a.file_name.cmp(&b.file_name)),
+        "nameDesc" => rows.sort_by(|a, b| b.file_name.cmp(&a.file_name)),
+        _ => rows.sort_by(|a, b| {
+            started(b)
+                .cmp(&started(a))
+                .then_with(|| b.file_name.cmp(&a.file_name))
+        }),
+    }
+
+    let total = rows.len();
+    let offset = query.offset.unwrap_or(0).min(total);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let items = rows.into_iter().skip(offset).take(limit).collect();
+    RunQueryPage {
+        total,
+        offset,
+        limit,
+        items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(file_name: &str, graph: &str, status: &str, started_at: &str) -> RunIndexEntry {
+        summarize_run(
+            file_name,
+            &json!({
+                "runId": file_name.trim_end_matches(".json"),
+                "workflowGroupName": graph,
+                "status": status,
+                "startedAt": started_at,
+                "question": format!("question for {file_name}")
+            }),
+            0,
+            0,
+        )
+    }
+
+    #[test]
+    fn derives_status_from_transitions() {
+        let run = json!({
+            "runId": "r1",
+            "finishedAt": "2026-01-01T00:00:00Z",
+            "transitions": [{ "nodeId": "a", "status": "failed", "at": "x" }]
+        });
+        assert_eq!(summarize_run("r1.json", &run, 0, 0).status, "failed");
+        let done =
+            json!({ "runId": "r2", "finishedAt": "2026-01-01T00:00:00Z", "transitions": [] });
+        assert_eq!(summarize_run("r2.json", &done, 0, 0).status, "done");
+    }
+
+    #[test]
+    fn filters_sorts_and_pages_runs() {
+        let entries = vec![
+            entry("a.json", "market", "done", "2026-01-01T00:00:00Z"),
+            entry("b.json", "market", "failed", "2026-01-02T00:00:00Z"),
+            entry("c.json", "news", "done", "2026-01-03T00:00:00Z"),
+            entry("d.json", "market", "done", "2026-01-04T00:00:00Z"),
+        ];
+
+        let page = query_runs(
+            entries.clone(),
+            &RunQuery {
+                graph_name: Some("market".to_string()),
+                status: Some("done".to_string()),
+                ..RunQuery::default()
+            },
+        );
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items[0].file_name, "d.json");
+
+        let ranged = query_runs(
+            entries.clone(),
+            &RunQuery {
+                started_after: Some("2026-01-02".to_string()),
+                started_before: Some("2026-01-04T00:00:00Z".to_string()),
+                sort: Some("startedAtAsc".to_string()),
+                ..RunQuery::default()
+            },
+        );
+        let names = ranged
+            .items
+            .iter()
+            .map(|row| row.file_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["b.json", "c.json"]);
+
+        let paged = query_runs(
+            entries,
+            &RunQuery {
+                text: Some("QUESTION".to_string()),
+                offset: Some(3),
+                limit: Some(2),
+                ..RunQuery::default()
+            },
+        );
+        assert_eq!(paged.total, 4);
+        assert_eq!(paged.items.len(), 1);
+        assert_eq!(paged.items[0].file_name, "a.json");
+    }
+}
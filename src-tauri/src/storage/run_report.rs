@@ -0,0 +1,858 @@
+use super::run_diff::{collect_nodes, run_duration_ms};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(str::trim).unwrap_or("markdown") {
+            "" | "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            other => Err(format!("unsupported report format: {other}")),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportCitation {
+    pub number: usize,
+    pub kind: String,
+    pub label: String,
+    pub url: Option<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportNode {
+    pub id: String,
+    pub label: String,
+    pub node_type: Option<String>,
+    pub status: Option<String>,
+    pub started_at: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub output: Option<String>,
+    pub citations: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub title: String,
+    pub run_id: Option<String>,
+    pub question: Option<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub final_answer: Option<String>,
+    pub nodes: Vec<ReportNode>,
+    pub citations: Vec<ReportCitation>,
+}
+
+fn text_of(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn array_of(value: Option<&Value>) -> &[Value] {
+    value
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+#[derive(Default)]
+struct CitationTable {
+    citations: Vec<ReportCitation>,
+    by_key: BTreeMap<String, usize>,
+    by_node: BTreeMap<String, Vec<usize>>,
+}
+
+impl CitationTable {
+    fn cite(&mut self, node_id: &str, key: String, build: impl FnOnce(usize) -> ReportCitation) {
+        let number = match self.by_key.get(&key) {
+            Some(number) => *number,
+            None => {
+                let number = self.citations.len() + 1;
+                self.citations.push(build(number));
+                self.by_key.insert(key, number);
+                number
+            }
+        };
+        let refs = self.by_node.entry(node_id.to_string()).or_default();
+        if !refs.contains(&number) {
+            refs.push(number);
+        }
+    }
+}
+
+// Knowledge snippets come from knowledgeTrace; crawled sources from the
+// normalized evidence citations of each node.
+fn collect_citations(run: &Value) -> CitationTable {
+    let mut table = CitationTable::default();
+    for entry in array_of(run.get("knowledgeTrace")) {
+        let (Some(node_id), Some(file_name)) =
+            (text_of(entry.get("nodeId")), text_of(entry.get("fileName")))
+        else {
+            continue;
+        };
+        let chunk = entry.get("chunkIndex").and_then(Value::as_u64).unwrap_or(0);
+        let file_id = text_of(entry.get("fileId")).unwrap_or_else(|| file_name.clone());
+        table.cite(&node_id, format!("knowledge:{file_id}:{chunk}"), |number| {
+            ReportCitation {
+                number,
+                kind: "knowledge".to_string(),
+                label: file_name.clone(),
+                url: None,
+                detail: Some(format!("chunk {}", chunk + 1)),
+            }
+        });
+    }
+
+    let evidence = run
+        .get("normalizedEvidenceByNodeId")
+        .and_then(Value::as_object);
+    for (node_id, envelopes) in evidence.into_iter().flatten() {
+        for citation in array_of(Some(envelopes))
+            .iter()
+            .flat_map(|envelope| array_of(envelope.get("citations")))
+        {
+            let url = text_of(citation.get("url"));
+            let title = text_of(citation.get("title"));
+            let source = text_of(citation.get("source"));
+            let Some(label) = title
+                .clone()
+                .or_else(|| source.clone())
+                .or_else(|| url.clone())
+            else {
+                continue;
+            };
+            let key = format!(
+                "web:{}",
+                url.clone()
+                    .unwrap_or_else(|| format!("{label}|{}", source.clone().unwrap_or_default()))
+            );
+            let date = text_of(citation.get("date"));
+            table.cite(node_id, key, |number| ReportCitation {
+                number,
+                kind: "web".to_string(),
+                label,
+                url,
+                detail: match (source, date) {
+                    (Some(source), Some(date)) => Some(format!("{source}, {date}")),
+                    (source, date) => source.or(date),
+                },
+            });
+        }
+    }
+    table
+}
+
+pub fn build_report(run: &Value) -> RunReport {
+    let mut snapshots = collect_nodes(run);
+    let mut citations = collect_citations(run);
+
+    let mut graph_nodes: BTreeMap<String, (usize, Option<String>)> = BTreeMap::new();
+    for (index, node) in array_of(run.pointer("/graphSnapshot/nodes"))
+        .iter()
+        .enumerate()
+    {
+        if let Some(id) = text_of(node.get("id")) {
+            graph_nodes.insert(id, (index, text_of(node.get("type"))));
+        }
+    }
+    let mut labels: BTreeMap<String, String> = BTreeMap::new();
+    for post in array_of(run.get("feedPosts")) {
+        let Some(node_id) = text_of(post.get("nodeId")) else {
+            continue;
+        };
+        if let Some(label) =
+            text_of(post.get("roleLabel")).or_else(|| text_of(post.get("agentName")))
+        {
+            labels.entry(node_id).or_insert(label);
+        }
+    }
+
+    let mut ids = snapshots.keys().cloned().collect::<Vec<_>>();
+    ids.sort_by_key(|id| {
+        let started = snapshots.get(id).and_then(|snapshot| snapshot.started_at);
+        let graph_index = graph_nodes.get(id).map(|(index, _)| *index);
+        (started.is_none(), started, graph_index, id.clone())
+    });
+    let nodes = ids
+        .into_iter()
+        .filter_map(|id| {
+            let snapshot = snapshots.remove(&id)?;
+            let node_type = graph_nodes.get(&id).and_then(|(_, kind)| kind.clone());
+            Some(ReportNode {
+                label: labels.remove(&id).unwrap_or_else(|| id.clone()),
+                node_type,
+                status: snapshot.status,
+                started_at: snapshot.started_at.map(|at| at.to_rfc3339()),
+                duration_ms: snapshot.duration_ms,
+                output: snapshot.output,
+                citations: citations.by_node.remove(&id).unwrap_or_default(),
+                id,
+            })
+        })
+        .collect();
+
+    let run_id = text_of(run.get("runId"));
+    let question = text_of(run.get("question"));
+    RunReport {
+        title: question
+            .as_deref()
+            .and_then(|text| text.lines().next())
+            .map(str::to_string)
+            .or_else(|| run_id.as_ref().map(|id| format!("Run {id}")))
+            .unwrap_or_else(|| "Run report".to_string()),
+        run_id,
+        question,
+        started_at: text_of(run.get("startedAt")),
+        finished_at: text_of(run.get("finishedAt")),
+        duration_ms: run_duration_ms(run),
+        final_answer: text_of(run.get("finalAnswer")),
+        nodes,
+        citations: citations.citations,
+    }
+}
+
+fn format_duration(duration_ms: Option<i64>) -> String {
+    match duration_ms {
+        Some(ms) if ms >= 60_000 => {
+            format!("{}m {:.1}s", ms / 60_000, (ms % 60_000) as f64 / 1000.0)
+        }
+        Some(ms) if ms >= 1000 => format!("{:.1}s", ms as f64 / 1000.0),
+        Some(ms) => format!("{ms}ms"),
+        None => "-".to_string(),
+    }
+}
+
+fn anchor(node_id: &str) -> String {
+    let slug = node_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("node-{slug}")
+}
+
+// One anchor per node, in order. Ids that slug alike (`a.b`, `a-b`, `A_B`)
+// get a numbered suffix so every link reaches its own section.
+fn node_anchors(nodes: &[ReportNode]) -> Vec<String> {
+    let mut used = HashSet::new();
+    nodes
+        .iter()
+        .map(|node| {
+            let base = anchor(&node.id);
+            let mut candidate = base.clone();
+            let mut suffix = 2;
+            while !used.insert(candidate.clone()) {
+                candidate = format!("{base}-{suffix}");
+                suffix += 1;
+            }
+            candidate
+        })
+        .collect()
+}
+
+fn citation_refs(numbers: &[usize]) -> String {
+    numbers
+        .iter()
+        .map(|number| format!("[{number}]"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Titles and labels are plain text, so markdown punctuation is escaped and
+// line breaks folded; otherwise a label could end a heading, a link or a
+// table cell of its own.
+fn markdown_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\r' | '\n' => out.push(' '),
+            '\\' | '`' | '*' | '_' | '[' | ']' | '(' | ')' | '#' | '<' | '>' | '|' | '!' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+// Link targets go through the same filter as the HTML report; spaces and
+// parentheses would end a markdown link early.
+fn markdown_href(url: &str) -> Option<String> {
+    safe_href(url).map(|href| {
+        href.replace(' ', "%20")
+            .replace('(', "%28")
+            .replace(')', "%29")
+    })
+}
+
+// Embedded markdown sits under the report's own section headings, so its
+// headings move down the same three levels the HTML report uses.
+fn shift_headings(text: &str) -> String {
+    let mut in_code = false;
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") {
+                in_code = !in_code;
+                return line.to_string();
+            }
+            let level = trimmed.chars().take_while(|ch| *ch == '#').count();
+            if in_code || !(1..=6).contains(&level) || !trimmed[level..].starts_with(' ') {
+                return line.to_string();
+            }
+            format!("{}{}", "#".repeat((level + 3).min(6)), &trimmed[level..])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn render_markdown(report: &RunReport) -> String {
+    let mut out = format!("# {}\n\n", markdown_text(&report.title));
+    if let Some(run_id) = &report.run_id {
+        out.push_str(&format!("- Run: `{run_id}`\n"));
+    }
+    if let Some(started_at) = &report.started_at {
+        out.push_str(&format!("- Started: {started_at}\n"));
+    }
+    if let Some(finished_at) = &report.finished_at {
+        out.push_str(&format!("- Finished: {finished_at}\n"));
+    }
+    out.push_str(&format!(
+        "- Duration: {}\n\n",
+        format_duration(report.duration_ms)
+    ));
+
+    out.push_str("## Contents\n\n");
+    if report.question.is_some() {
+        out.push_str("- [Question](#question)\n");
+    }
+    if report.final_answer.is_some() {
+        out.push_str("- [Final answer](#final-answer)\n");
+    }
+    out.push_str("- [Execution timing](#execution-timing)\n");
+    let anchors = node_anchors(&report.nodes);
+    for (node, anchor) in report.nodes.iter().zip(&anchors) {
+        out.push_str(&format!(
+            "  - [{}](#{anchor})\n",
+            markdown_text(&node.label)
+        ));
+    }
+    if !report.citations.is_empty() {
+        out.push_str("- [Sources](#sources)\n");
+    }
+    out.push('\n');
+
+    if let Some(question) = &report.question {
+        out.push_str(&format!(
+            "<a id=\"question\"></a>\n\n## Question\n\n{}\n\n",
+            shift_headings(question)
+        ));
+    }
+    if let Some(answer) = &report.final_answer {
+        out.push_str(&format!(
+            "<a id=\"final-answer\"></a>\n\n## Final answer\n\n{}\n\n",
+            shift_headings(answer)
+        ));
+    }
+
+    out.push_str("<a id=\"execution-timing\"></a>\n\n## Execution timing\n\n");
+    out.push_str("| Node | Type | Status | Started | Duration |\n|---|---|---|---|---|\n");
+    for node in &report.nodes {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            markdown_text(&node.label),
+            node.node_type.as_deref().unwrap_or("-"),
+            node.status.as_deref().unwrap_or("-"),
+            node.started_at.as_deref().unwrap_or("-"),
+            format_duration(node.duration_ms)
+        ));
+    }
+    out.push('\n');
+
+    for (node, anchor) in report.nodes.iter().zip(&anchors) {
+        out.push_str(&format!(
+            "<a id=\"{anchor}\"></a>\n\n### {}\n\n",
+            markdown_text(&node.label)
+        ));
+        out.push_str(&format!(
+            "_Node `{}` · {} · {}_\n\n",
+            node.id,
+            node.status.as_deref().unwrap_or("unknown"),
+            format_duration(node.duration_ms)
+        ));
+        if let Some(output) = &node.output {
+            out.push_str(&shift_headings(output));
+            out.push_str("\n\n");
+        }
+        if !node.citations.is_empty() {
+            out.push_str(&format!("Sources: {}\n\n", citation_refs(&node.citations)));
+        }
+    }
+
+    if !report.citations.is_empty() {
+        out.push_str("<a id=\"sources\"></a>\n\n## Sources\n\n");
+        for citation in &report.citations {
+            let label = match citation.url.as_deref().and_then(markdown_href) {
+                Some(href) => format!("[{}]({href})", markdown_text(&citation.label)),
+                None => markdown_text(&citation.label),
+            };
+            let detail = citation
+                .detail
+                .as_ref()
+                .map(|detail| format!(" — {detail}"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "{}. {label} ({}){detail}\n",
+                citation.number, citation.kind
+            ));
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn safe_href(url: &str) -> Option<String> {
+    let trimmed = url.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    (lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with('#'))
+        .then(|| escape_html(trimmed))
+}
+
+// Inline markdown subset: `code`, **bold** and [text](http link). Everything
+// else is escaped.
+fn render_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('`') {
+            if let Some(end) = after.find('`') {
+                out.push_str(&format!("<code>{}</code>", escape_html(&after[..end])));
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+        if let Some(after) = rest.strip_prefix("**") {
+            if let Some(end) = after.find("**") {
+                out.push_str(&format!("<strong>{}</strong>", escape_html(&after[..end])));
+                rest = &after[end + 2..];
+                continue;
+            }
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            if let Some((label, tail)) = after.split_once("](") {
+                if let Some(end) = tail.find(')') {
+                    if let Some(href) = safe_href(&tail[..end]) {
+                        out.push_str(&format!("<a href=\"{href}\">{}</a>", escape_html(label)));
+                        rest = &tail[end + 1..];
+                        continue;
+                    }
+                }
+            }
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push_str(&escape_html(&ch.to_string()));
+        rest = &rest[ch.len_utf8()..];
+    }
+    out
+}
+
+fn ordered_item(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..].strip_prefix(". ")
+}
+
+// Block markdown subset used by node outputs: headings, lists, fenced code,
+// quotes and paragraphs. Node headings are shifted below the section heading.
+fn render_markdown_html(text: &str) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut list: Option<&str> = None;
+    let mut code: Option<Vec<&str>> = None;
+
+    fn flush_paragraph(out: &mut String, paragraph: &mut Vec<&str>) {
+        if !paragraph.is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", render_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    }
+    fn close_list(out: &mut String, list: &mut Option<&str>) {
+        if let Some(tag) = list.take() {
+            out.push_str(&format!("</{tag}>\n"));
+        }
+    }
+
+    for line in text.lines() {
+        if let Some(lines) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                out.push_str(&format!(
+                    "<pre><code>{}</code></pre>\n",
+                    escape_html(&lines.join("\n"))
+                ));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            flush_paragraph(&mut out, &mut paragraph);
+            close_list(&mut out, &mut list);
+            code = Some(Vec::new());
+            continue;
+        }
+        if trimmed.is_empty() {
+            flush_paragraph(&mut out, &mut paragraph);
+            close_list(&mut out, &mut list);
+            continue;
+        }
+        let level = trimmed.chars().take_while(|ch| *ch == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            flush_paragraph(&mut out, &mut paragraph);
+            close_list(&mut out, &mut list);
+            let tag = (level + 3).min(6);
+            out.push_str(&format!(
+                "<h{tag}>{}</h{tag}>\n",
+                render_inline(trimmed[level..].trim())
+            ));
+            continue;
+        }
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .map(|item| ("ul", item))
+            .or_else(|| ordered_item(trimmed).map(|item| ("ol", item)));
+        if let Some((tag, item)) = item {
+            flush_paragraph(&mut out, &mut paragraph);
+            if list != Some(tag) {
+                close_list(&mut out, &mut list);
+                out.push_str(&format!("<{tag}>\n"));
+                list = Some(tag);
+            }
+            out.push_str(&format!("<li>{}</li>\n", render_inline(item)));
+            continue;
+        }
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            flush_paragraph(&mut out, &mut paragraph);
+            close_list(&mut out, &mut list);
+            out.push_str(&format!(
+                "<blockquote>{}</blockquote>\n",
+                render_inline(quote.trim())
+            ));
+            continue;
+        }
+        close_list(&mut out, &mut list);
+        paragraph.push(trimmed);
+    }
+    if let Some(lines) = code {
+        out.push_str(&format!(
+            "<pre><code>{}</code></pre>\n",
+            escape_html(&lines.join("\n"))
+        ));
+    }
+    flush_paragraph(&mut out, &mut paragraph);
+    close_list(&mut out, &mut list);
+    out
+}
+
+const REPORT_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;max-width:860px;margin:40px auto;padding:0 20px;line-height:1.6;color:#1f2328}\
+table{border-collapse:collapse;width:100%}th,td{border:1px solid #d0d7de;padding:6px 10px;text-align:left}\
+pre{background:#f6f8fa;padding:12px;overflow:auto}code{font-family:ui-monospace,monospace}\
+.meta{color:#59636e}.cite{font-size:0.9em;color:#59636e}section{margin-top:32px}";
+
+pub fn render_html(report: &RunReport) -> String {
+    let mut body = format!(
+        "<h1>{}</h1>\n<ul class=\"meta\">\n",
+        escape_html(&report.title)
+    );
+    if let Some(run_id) = &report.run_id {
+        body.push_str(&format!(
+            "<li>Run: <code>{}</code></li>\n",
+            escape_html(run_id)
+        ));
+    }
+    if let Some(started_at) = &report.started_at {
+        body.push_str(&format!("<li>Started: {}</li>\n", escape_html(started_at)));
+    }
+    if let Some(finished_at) = &report.finished_at {
+        body.push_str(&format!(
+            "<li>Finished: {}</li>\n",
+            escape_html(finished_at)
+        ));
+    }
+    body.push_str(&format!(
+        "<li>Duration: {}</li>\n</ul>\n",
+        format_duration(report.duration_ms)
+    ));
+
+    body.push_str("<nav>\n<h2>Contents</h2>\n<ul>\n");
+    if report.question.is_some() {
+        body.push_str("<li><a href=\"#question\">Question</a></li>\n");
+    }
+    if report.final_answer.is_some() {
+        body.push_str("<li><a href=\"#final-answer\">Final answer</a></li>\n");
+    }
+    body.push_str("<li><a href=\"#execution-timing\">Execution timing</a>\n<ul>\n");
+    let anchors = node_anchors(&report.nodes);
+    for (node, anchor) in report.nodes.iter().zip(&anchors) {
+        body.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a></li>\n",
+            anchor,
+            escape_html(&node.label)
+        ));
+    }
+    body.push_str("</ul>\n</li>\n");
+    if !report.citations.is_empty() {
+        body.push_str("<li><a href=\"#sources\">Sources</a></li>\n");
+    }
+    body.push_str("</ul>\n</nav>\n");
+
+    if let Some(question) = &report.question {
+        body.push_str(&format!(
+            "<section id=\"question\">\n<h2>Question</h2>\n{}</section>\n",
+            render_markdown_html(question)
+        ));
+    }
+    if let Some(answer) = &report.final_answer {
+        body.push_str(&format!(
+            "<section id=\"final-answer\">\n<h2>Final answer</h2>\n{}</section>\n",
+            render_markdown_html(answer)
+        ));
+    }
+
+    body.push_str("<section id=\"execution-timing\">\n<h2>Execution timing</h2>\n<table>\n");
+    body.push_str(
+        "<tr><th>Node</th><th>Type</th><th>Status</th><th>Started</th><th>Duration</th></tr>\n",
+    );
+    for (node, anchor) in report.nodes.iter().zip(&anchors) {
+        body.push_str(&format!(
+            "<tr><td><a href=\"#{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            anchor,
+            escape_html(&node.label),
+            escape_html(node.node_type.as_deref().unwrap_or("-")),
+            escape_html(node.status.as_deref().unwrap_or("-")),
+            escape_html(node.started_at.as_deref().unwrap_or("-")),
+            format_duration(node.duration_ms)
+        ));
+    }
+    body.push_str("</table>\n");
+    for (node, anchor) in report.nodes.iter().zip(&anchors) {
+        body.push_str(&format!(
+            "<section id=\"{}\">\n<h3>{}</h3>\n<p class=\"meta\">Node <code>{}</code> · {} · {}</p>\n",
+            anchor,
+            escape_html(&node.label),
+            escape_html(&node.id),
+            escape_html(node.status.as_deref().unwrap_or("unknown")),
+            format_duration(node.duration_ms)
+        ));
+        if let Some(output) = &node.output {
+            body.push_str(&render_markdown_html(output));
+        }
+        if !node.citations.is_empty() {
+            let refs = node
+                .citations
+                .iter()
+                .map(|number| format!("<a href=\"#cite-{number}\">[{number}]</a>"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            body.push_str(&format!("<p class=\"cite\">Sources: {refs}</p>\n"));
+        }
+        body.push_str("</section>\n");
+    }
+    body.push_str("</section>\n");
+
+    if !report.citations.is_empty() {
+        body.push_str("<section id=\"sources\">\n<h2>Sources</h2>\n<ol>\n");
+        for citation in &report.citations {
+            let label = match citation.url.as_deref().and_then(safe_href) {
+                Some(href) => format!("<a href=\"{href}\">{}</a>", escape_html(&citation.label)),
+                None => escape_html(&citation.label),
+            };
+            let detail = citation
+                .detail
+                .as_ref()
+                .map(|detail| format!(" — {}", escape_html(detail)))
+                .unwrap_or_default();
+            body.push_str(&format!(
+                "<li id=\"cite-{}\">{label} <span class=\"meta\">({})</span>{detail}</li>\n",
+                citation.number, citation.kind
+            ));
+        }
+        body.push_str("</ol>\n</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{REPORT_STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(&report.title)
+    )
+}
+
+pub fn render_report(run: &Value, format: ReportFormat) -> (RunReport, String) {
+    let report = build_report(run);
+    let content = match format {
+        ReportFormat::Markdown => render_markdown(&report),
+        ReportFormat::Html => render_html(&report),
+    };
+    (report, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_run() -> Value {
+        json!({
+            "runId": "r1",
+            "question": "Summarize <market> news",
+            "startedAt": "2026-01-01T00:00:00Z",
+            "finishedAt": "2026-01-01T00:00:05Z",
+            "finalAnswer": "Markets are **stable**.",
+            "graphSnapshot": { "nodes": [
+                { "id": "search", "type": "turn" },
+                { "id": "writer", "type": "turn" }
+            ] },
+            "transitions": [
+                { "nodeId": "search", "status": "running", "at": "2026-01-01T00:00:00Z" },
+                { "nodeId": "search", "status": "done", "at": "2026-01-01T00:00:02Z" },
+                { "nodeId": "writer", "status": "running", "at": "2026-01-01T00:00:02Z" },
+                { "nodeId": "writer", "status": "done", "at": "2026-01-01T00:00:05Z" }
+            ],
+            "feedPosts": [
+                { "nodeId": "writer", "roleLabel": "Writer", "attachments": [
+                    { "kind": "markdown", "content": "## Draft\n- point <one>\n<script>alert(1)</script>" }
+                ] }
+            ],
+            "knowledgeTrace": [
+                { "nodeId": "writer", "fileId": "f1", "fileName": "notes.md", "chunkIndex": 0, "score": 0.9 }
+            ],
+            "normalizedEvidenceByNodeId": {
+                "search": [{ "citations": [
+                    { "url": "https://example.com/a", "title": "Example A", "source": "example" },
+                    { "url": "javascript:alert(1)", "title": "Bad link" }
+                ] }]
+            }
+        })
+    }
+
+    #[test]
+    fn builds_report_with_timing_and_citations() {
+        let report = build_report(&sample_run());
+        assert_eq!(report.duration_ms, Some(5000));
+        assert_eq!(
+            report
+                .nodes
+                .iter()
+                .map(|node| node.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["search", "writer"]
+        );
+        assert_eq!(report.nodes[1].label, "Writer");
+        assert_eq!(report.nodes[1].duration_ms, Some(3000));
+        assert_eq!(report.citations.len(), 3);
+        assert_eq!(report.nodes[1].citations, vec![1]);
+        assert_eq!(report.nodes[0].citations, vec![2, 3]);
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("- [Execution timing](#execution-timing)"));
+        assert!(markdown.contains("  - [Writer](#node-writer)"));
+        assert!(markdown.contains("1. notes.md (knowledge) — chunk 1"));
+        assert!(markdown.contains("2. [Example A](https://example.com/a) (web) — example"));
+        assert!(markdown.contains("3. Bad link (web)"));
+        assert!(!markdown.contains("javascript:"));
+        assert!(markdown.contains("##### Draft"));
+    }
+
+    #[test]
+    fn node_anchors_stay_unique() {
+        let report = build_report(&json!({
+            "graphSnapshot": { "nodes": [
+                { "id": "a.b", "type": "turn" },
+                { "id": "a-b", "type": "turn" },
+                { "id": "a-b-2", "type": "turn" }
+            ] }
+        }));
+        assert_eq!(
+            node_anchors(&report.nodes),
+            vec!["node-a-b", "node-a-b-2", "node-a-b-2-2"]
+        );
+
+        let html = render_html(&report);
+        assert!(html.contains("<section id=\"node-a-b-2\">"));
+        assert!(html.contains("<section id=\"node-a-b-2-2\">"));
+    }
+
+    #[test]
+    fn escapes_node_labels_in_markdown() {
+        let report = build_report(&json!({
+            "graphSnapshot": { "nodes": [{ "id": "n1", "type": "turn" }] },
+            "feedPosts": [{ "nodeId": "n1", "roleLabel": "A](http://x) #1\n# B|C" }]
+        }));
+        let markdown = render_markdown(&report);
+        let escaped = "A\\]\\(http://x\\) \\#1 \\# B\\|C";
+        assert!(markdown.contains(&format!("  - [{escaped}](#node-n1)\n")));
+        assert!(markdown.contains(&format!("### {escaped}\n")));
+        assert!(markdown.contains(&format!("| {escaped} | turn |")));
+        assert!(!markdown.contains("\n# B"));
+    }
+
+    #[test]
+    fn renders_escaped_self_contained_html() {
+        let (_, html) = render_report(&sample_run(), ReportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Summarize &lt;market&gt; news</title>"));
+        assert!(html.contains("<h5>Draft</h5>"));
+        assert!(html.contains("<li>point &lt;one&gt;</li>"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<a href=\"https://example.com/a\">Example A</a>"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<strong>stable</strong>"));
+    }
+}
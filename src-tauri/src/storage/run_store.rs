@@ -0,0 +1,478 @@
+use super::atomic_file::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const RUN_MANIFEST_FILE: &str = "run.json";
+pub const RUN_ARTIFACTS_DIR: &str = "artifacts";
+const ARTIFACT_MANIFEST_FILE: &str = "manifest.json";
+const ARTIFACT_MANIFEST_VERSION: u64 = 1;
+const ARTIFACT_FILE_EXTENSION: &str = "artifact";
+// Attachment bodies above this size are moved out of run.json on save.
+const INLINE_CONTENT_MAX_BYTES: usize = 64 * 1024;
+const CONTENT_PREVIEW_CHARS: usize = 4_000;
+
+static RUN_ARTIFACT_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunArtifact {
+    pub id: String,
+    pub name: String,
+    pub media_type: String,
+    pub size_bytes: u64,
+    pub node_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunArtifactManifest {
+    version: u64,
+    #[serde(default)]
+    artifacts: Vec<RunArtifact>,
+}
+
+fn run_stem(file_name: &str) -> &str {
+    file_name.trim_end_matches(".json")
+}
+
+pub fn run_dir(runs_dir: &Path, file_name: &str) -> PathBuf {
+    runs_dir.join(run_stem(file_name))
+}
+
+fn legacy_run_path(runs_dir: &Path, file_name: &str) -> PathBuf {
+    runs_dir.join(format!("{}.json", run_stem(file_name)))
+}
+
+// Directory runs win over a legacy `<name>.json` left next to them.
+pub fn run_file_path(runs_dir: &Path, file_name: &str) -> PathBuf {
+    let manifest = run_dir(runs_dir, file_name).join(RUN_MANIFEST_FILE);
+    let legacy = legacy_run_path(runs_dir, file_name);
+    if !manifest.is_file() && legacy.is_file() {
+        legacy
+    } else {
+        manifest
+    }
+}
+
+// The path to move or delete as a whole: the run directory or the legacy file.
+pub fn run_storage_path(runs_dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let dir = run_dir(runs_dir, file_name);
+    if dir.join(RUN_MANIFEST_FILE).is_file() {
+        return Some(dir);
+    }
+    let legacy = legacy_run_path(runs_dir, file_name);
+    legacy.is_file().then_some(legacy)
+}
+
+// Lists runs by their `<name>.json` identity regardless of layout.
+pub fn list_run_files(runs_dir: &Path) -> Result<Vec<String>, String> {
+    let mut files = BTreeSet::new();
+    for entry in
+        fs::read_dir(runs_dir).map_err(|e| format!("failed to read runs directory: {e}"))?
+    {
+        let entry = entry.map_err(|e| format!("failed to read directory entry: {e}"))?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            if path.join(RUN_MANIFEST_FILE).is_file() {
+                files.insert(format!("{name}.json"));
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false)
+        {
+            files.insert(name.to_string());
+        }
+    }
+    Ok(files.into_iter().collect())
+}
+
+fn artifacts_dir(runs_dir: &Path, file_name: &str) -> PathBuf {
+    run_dir(runs_dir, file_name).join(RUN_ARTIFACTS_DIR)
+}
+
+fn artifact_path(runs_dir: &Path, file_name: &str, id: &str) -> Result<PathBuf, String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if !valid {
+        return Err("invalid artifact id".to_string());
+    }
+    Ok(artifacts_dir(runs_dir, file_name).join(format!("{id}.{ARTIFACT_FILE_EXTENSION}")))
+}
+
+fn load_artifact_manifest(runs_dir: &Path, file_name: &str) -> RunArtifactManifest {
+    fs::read_to_string(artifacts_dir(runs_dir, file_name).join(ARTIFACT_MANIFEST_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<RunArtifactManifest>(&raw).ok())
+        .unwrap_or(RunArtifactManifest {
+            version: ARTIFACT_MANIFEST_VERSION,
+            artifacts: Vec::new(),
+        })
+}
+
+fn save_artifact_manifest(
+    runs_dir: &Path,
+    file_name: &str,
+    manifest: &RunArtifactManifest,
+) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| format!("failed to serialize artifact manifest: {e}"))?;
+    write_atomic(
+        &artifacts_dir(runs_dir, file_name).join(ARTIFACT_MANIFEST_FILE),
+        &json,
+    )
+    .map_err(|e| format!("failed to write artifact manifest: {e}"))
+}
+
+// Moves a legacy single-file run into `<name>/run.json` so artifacts can sit
+// beside it.
+fn ensure_run_dir(runs_dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    let dir = run_dir(runs_dir, file_name);
+    let manifest = dir.join(RUN_MANIFEST_FILE);
+    if manifest.is_file() {
+        return Ok(dir);
+    }
+    let legacy = legacy_run_path(runs_dir, file_name);
+    if !legacy.is_file() {
+        return Err("run file not found".to_string());
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create run directory: {e}"))?;
+    fs::rename(&legacy, &manifest).map_err(|e| format!("failed to migrate run file: {e}"))?;
+    Ok(dir)
+}
+
+fn sanitize_artifact_name(name: &str) -> String {
+    let cleaned = name
+        .trim()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|ch| !ch.is_control())
+        .collect::<String>();
+    if cleaned.is_empty() || cleaned == "." || cleaned == ".." {
+        "artifact".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn new_artifact_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    format!("a{nanos}")
+}
+
+// Writes (or replaces, when `id` is given) one artifact. Callers must hold
+// RUN_ARTIFACT_LOCK.
+fn put_artifact(
+    runs_dir: &Path,
+    file_name: &str,
+    id: Option<&str>,
+    name: &str,
+    media_type: &str,
+    node_id: Option<&str>,
+    bytes: &[u8],
+) -> Result<RunArtifact, String> {
+    let id = id.map(str::to_string).unwrap_or_else(new_artifact_id);
+    let path = artifact_path(runs_dir, file_name, &id)?;
+    fs::create_dir_all(artifacts_dir(runs_dir, file_name))
+        .map_err(|e| format!("failed to create artifacts directory: {e}"))?;
+    write_atomic(&path, bytes).map_err(|e| format!("failed to write artifact: {e}"))?;
+    let mut manifest = load_artifact_manifest(runs_dir, file_name);
+    let artifact = RunArtifact {
+        id: id.clone(),
+        name: sanitize_artifact_name(name),
+        media_type: media_type.trim().to_string(),
+        size_bytes: bytes.len() as u64,
+        node_id: node_id
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    manifest.version = ARTIFACT_MANIFEST_VERSION;
+    manifest.artifacts.retain(|row| row.id != id);
+    manifest.artifacts.push(artifact.clone());
+    save_artifact_manifest(runs_dir, file_name, &manifest)?;
+    Ok(artifact)
+}
+
+pub fn add_artifact(
+    runs_dir: &Path,
+    file_name: &str,
+    name: &str,
+    media_type: &str,
+    node_id: Option<&str>,
+    bytes: &[u8],
+) -> Result<RunArtifact, String> {
+    let _guard = RUN_ARTIFACT_LOCK
+        .lock()
+        .map_err(|_| "run artifact lock is poisoned".to_string())?;
+    ensure_run_dir(runs_dir, file_name)?;
+    put_artifact(runs_dir, file_name, None, name, media_type, node_id, bytes)
+}
+
+pub fn list_artifacts(runs_dir: &Path, file_name: &str) -> Vec<RunArtifact> {
+    load_artifact_manifest(runs_dir, file_name).artifacts
+}
+
+pub fn read_artifact(
+    runs_dir: &Path,
+    file_name: &str,
+    id: &str,
+) -> Result<(RunArtifact, Vec<u8>), String> {
+    let artifact = list_artifacts(runs_dir, file_name)
+        .into_iter()
+        .find(|row| row.id == id)
+        .ok_or_else(|| "artifact not found".to_string())?;
+    let bytes = fs::read(artifact_path(runs_dir, file_name, id)?)
+        .map_err(|e| format!("failed to read artifact: {e}"))?;
+    Ok((artifact, bytes))
+}
+
+fn attachment_artifact_id(post: &Value, post_index: usize, attachment_index: usize) -> String {
+    let post_key = post
+        .get("id")
+        .and_then(Value::as_str)
+        .map(|id| {
+            id.chars()
+                .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+                .collect::<String>()
+        })
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| post_index.to_string());
+    format!("feed-{post_key}-{attachment_index}")
+}
+
+fn attachment_media_type(attachment: &Value) -> &'static str {
+    match attachment.get("kind").and_then(Value::as_str) {
+        Some("markdown") => "text/markdown",
+        Some("json") => "application/json",
+        _ => "text/plain",
+    }
+}
+
+// Moves oversized feed attachment bodies into artifacts, leaving a preview and
+// `artifactId` behind. Attachments that are already previews are left alone.
+fn externalize_attachments(
+    runs_dir: &Path,
+    file_name: &str,
+    run: &mut Value,
+) -> Result<(), String> {
+    let Some(posts) = run.get_mut("feedPosts").and_then(Value::as_array_mut) else {
+        return Ok(());
+    };
+    for (post_index, post) in posts.iter_mut().enumerate() {
+        let node_id = post
+            .get("nodeId")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let post_snapshot = post.clone();
+        let Some(attachments) = post.get_mut("attachments").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for (attachment_index, attachment) in attachments.iter_mut().enumerate() {
+            let is_preview = attachment
+                .get("contentTruncated")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let Some(content) = attachment.get("content").and_then(Value::as_str) else {
+                continue;
+            };
+            if is_preview {
+                continue;
+            }
+            if content.len() <= INLINE_CONTENT_MAX_BYTES {
+                if let Some(object) = attachment.as_object_mut() {
+                    object.remove("artifactId");
+                    object.remove("contentBytes");
+                }
+                continue;
+            }
+            let id = attachment
+                .get("artifactId")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    attachment_artifact_id(&post_snapshot, post_index, attachment_index)
+                });
+            let extension = if attachment_media_type(attachment) == "application/json" {
+                "json"
+            } else {
+                "md"
+            };
+            let artifact = put_artifact(
+                runs_dir,
+                file_name,
+                Some(&id),
+                &format!("{id}.{extension}"),
+                attachment_media_type(attachment),
+                node_id.as_deref(),
+                content.as_bytes(),
+            )?;
+            let preview = content
+                .chars()
+                .take(CONTENT_PREVIEW_CHARS)
+                .collect::<String>();
+            if let Some(object) = attachment.as_object_mut() {
+                object.insert("content".to_string(), Value::String(preview));
+                object.insert("artifactId".to_string(), Value::String(artifact.id));
+                object.insert("contentTruncated".to_string(), Value::Bool(true));
+                object.insert("contentBytes".to_string(), Value::from(artifact.size_bytes));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn inline_attachments(runs_dir: &Path, file_name: &str, run: &mut Value) {
+    let Some(posts) = run.get_mut("feedPosts").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for attachment in posts
+        .iter_mut()
+        .filter_map(|post| post.get_mut("attachments").and_then(Value::as_array_mut))
+        .flatten()
+    {
+        let Some(id) = attachment
+            .get("artifactId")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let Ok((_, bytes)) = read_artifact(runs_dir, file_name, &id) else {
+            continue;
+        };
+        if let Some(object) = attachment.as_object_mut() {
+            object.insert(
+                "content".to_string(),
+                Value::String(String::from_utf8_lossy(&bytes).to_string()),
+            );
+            object.remove("contentTruncated");
+        }
+    }
+}
+
+// Always writes the directory layout; a legacy file for the same run is
+// migrated and then removed.
+pub fn write_run(runs_dir: &Path, file_name: &str, run: &Value) -> Result<Value, String> {
+    let _guard = RUN_ARTIFACT_LOCK
+        .lock()
+        .map_err(|_| "run artifact lock is poisoned".to_string())?;
+    let dir = run_dir(runs_dir, file_name);
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create run directory: {e}"))?;
+    let mut stored = run.clone();
+    externalize_attachments(runs_dir, file_name, &mut stored)?;
+    let json = serde_json::to_vec_pretty(&stored)
+        .map_err(|e| format!("failed to serialize runs file: {e}"))?;
+    write_atomic(&dir.join(RUN_MANIFEST_FILE), &json)
+        .map_err(|e| format!("failed to write runs file: {e}"))?;
+    let legacy = legacy_run_path(runs_dir, file_name);
+    if legacy.is_file() {
+        fs::remove_file(&legacy).map_err(|e| format!("failed to remove legacy run file: {e}"))?;
+    }
+    Ok(stored)
+}
+
+pub fn load_run(runs_dir: &Path, file_name: &str, inline_artifacts: bool) -> Result<Value, String> {
+    let raw = fs::read_to_string(run_file_path(runs_dir, file_name))
+        .map_err(|e| format!("failed to read runs file: {e}"))?;
+    let mut run: Value =
+        serde_json::from_str(&raw).map_err(|e| format!("invalid JSON in runs file: {e}"))?;
+    if inline_artifacts {
+        inline_attachments(runs_dir, file_name, &mut run);
+    }
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn stores_runs_as_directories_and_externalizes_large_attachments() {
+        let runs_dir = std::env::temp_dir().join(format!(
+            "rail_run_store_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        fs::create_dir_all(&runs_dir).expect("create runs dir");
+        fs::write(runs_dir.join("run-legacy.json"), r#"{"runId":"legacy"}"#)
+            .expect("write legacy run");
+
+        let large = "x".repeat(INLINE_CONTENT_MAX_BYTES + 10);
+        let run = json!({
+            "runId": "big",
+            "feedPosts": [{
+                "id": "post:1",
+                "nodeId": "turn-1",
+                "attachments": [{ "kind": "markdown", "content": large }]
+            }]
+        });
+        write_run(&runs_dir, "run-big.json", &run).expect("write run");
+        assert_eq!(
+            list_run_files(&runs_dir).expect("list runs"),
+            vec!["run-big.json".to_string(), "run-legacy.json".to_string()]
+        );
+
+        let lazy = load_run(&runs_dir, "run-big.json", false).expect("load lazy");
+        let attachment = &lazy["feedPosts"][0]["attachments"][0];
+        assert_eq!(attachment["artifactId"], "feed-post_1-0");
+        assert_eq!(attachment["contentTruncated"], true);
+        assert_eq!(
+            attachment["content"].as_str().map(str::len),
+            Some(CONTENT_PREVIEW_CHARS)
+        );
+
+        // Saving a lazily loaded run must not clobber the full artifact.
+        write_run(&runs_dir, "run-big.json", &lazy).expect("resave preview");
+        let full = load_run(&runs_dir, "run-big.json", true).expect("load full");
+        assert_eq!(
+            full["feedPosts"][0]["attachments"][0]["content"].as_str(),
+            Some(large.as_str())
+        );
+
+        let image = add_artifact(
+            &runs_dir,
+            "run-legacy.json",
+            "../chart.png",
+            "image/png",
+            Some("turn-2"),
+            &[0x89, 0x50, 0x4e, 0x47, 0x00, 0xff],
+        )
+        .expect("add artifact to legacy run");
+        assert_eq!(image.name, "chart.png");
+        assert!(!runs_dir.join("run-legacy.json").exists());
+        assert_eq!(
+            load_run(&runs_dir, "run-legacy.json", false).expect("load migrated")["runId"],
+            "legacy"
+        );
+        let (_, bytes) =
+            read_artifact(&runs_dir, "run-legacy.json", &image.id).expect("read artifact");
+        assert_eq!(bytes, vec![0x89, 0x50, 0x4e, 0x47, 0x00, 0xff]);
+        assert!(read_artifact(&runs_dir, "run-legacy.json", "../run").is_err());
+
+        let _ = fs::remove_dir_all(runs_dir);
+    }
+}
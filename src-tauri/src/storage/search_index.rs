@@ -0,0 +1,684 @@
+use super::atomic_file::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const LEGACY_INDEX_FILE: &str = "index.json";
+const SEARCH_INDEX_VERSION: u64 = 2;
+const SEARCH_AREAS: [&str; 2] = ["graphs", "runs"];
+const MAX_FIELD_CHARS: usize = 20_000;
+const EXCERPT_CHARS: usize = 160;
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const MAX_SEARCH_LIMIT: usize = 200;
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+static SEARCH_INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+// One shard per indexed file under `<area>/<file name>`, so saving a file
+// rewrites only its own shard. Shards keep term counts and a short snippet per
+// field; excerpts are cut from the source file when a query runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchShard {
+    version: u64,
+    stamp: String,
+    docs: Vec<SearchDoc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchDoc {
+    // Index into the file's extracted fields, used to find the source text.
+    position: usize,
+    node_id: Option<String>,
+    field: String,
+    length: usize,
+    snippet: String,
+    terms: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    pub text: String,
+    pub area: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub area: String,
+    pub file_name: String,
+    pub node_id: Option<String>,
+    pub field: String,
+    pub score: f64,
+    pub excerpt: String,
+    pub highlights: Vec<[usize; 2]>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+struct SearchField {
+    node_id: Option<String>,
+    field: String,
+    text: String,
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{1100}'..='\u{11FF}'
+            | '\u{3040}'..='\u{30FF}'
+            | '\u{3130}'..='\u{318F}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7A3}'
+            | '\u{F900}'..='\u{FAFF}'
+    )
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    if word.chars().count() >= 2 {
+        tokens.push(word.clone());
+    }
+    word.clear();
+}
+
+// CJK text has no reliable word boundaries (and Korean attaches particles to
+// nouns), so runs of those scripts are indexed as overlapping bigrams.
+fn flush_cjk(run: &mut Vec<char>, tokens: &mut Vec<String>) {
+    match run.len() {
+        0 => {}
+        1 => tokens.push(run[0].to_string()),
+        _ => tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>())),
+    }
+    run.clear();
+}
+
+pub fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk = Vec::new();
+
+    for ch in input.chars() {
+        if is_cjk(ch) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(ch);
+            continue;
+        }
+        flush_cjk(&mut cjk, &mut tokens);
+        if ch.is_alphanumeric() {
+            word.extend(ch.to_lowercase());
+            continue;
+        }
+        flush_word(&mut word, &mut tokens);
+    }
+
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+    tokens
+}
+
+fn text_of(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn collect_strings(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(text) if !text.trim().is_empty() => {
+            out.push((prefix.to_string(), text.clone()))
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_strings(prefix, item, out);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                collect_strings(&format!("{prefix}.{key}"), item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn graph_fields(graph: &Value) -> Vec<SearchField> {
+    let mut fields = Vec::new();
+    for node in graph
+        .get("nodes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let node_id = text_of(node.get("id"));
+        let mut strings = Vec::new();
+        if let Some(config) = node.get("config") {
+            collect_strings("config", config, &mut strings);
+        }
+        fields.extend(strings.into_iter().map(|(field, text)| SearchField {
+            node_id: node_id.clone(),
+            field,
+            text,
+        }));
+    }
+    fields
+}
+
+fn run_fields(run: &Value) -> Vec<SearchField> {
+    let mut fields = Vec::new();
+    for key in ["question", "finalAnswer"] {
+        if let Some(text) = text_of(run.get(key)) {
+            fields.push(SearchField {
+                node_id: None,
+                field: key.to_string(),
+                text,
+            });
+        }
+    }
+    let summary_logs = run
+        .get("summaryLogs")
+        .and_then(Value::as_array)
+        .map(|rows| rows.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+    if !summary_logs.is_empty() {
+        fields.push(SearchField {
+            node_id: None,
+            field: "summaryLogs".to_string(),
+            text: summary_logs.join("\n"),
+        });
+    }
+    for (node_id, logs) in run
+        .get("nodeLogs")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let lines = logs
+            .as_array()
+            .map(|rows| rows.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if !lines.is_empty() {
+            fields.push(SearchField {
+                node_id: Some(node_id.clone()),
+                field: "logs".to_string(),
+                text: lines.join("\n"),
+            });
+        }
+    }
+    for post in run
+        .get("feedPosts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let node_id = text_of(post.get("nodeId"));
+        if let Some(text) = text_of(post.get("summary")) {
+            fields.push(SearchField {
+                node_id: node_id.clone(),
+                field: "summary".to_string(),
+                text,
+            });
+        }
+        for attachment in post
+            .get("attachments")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(text) = text_of(attachment.get("content")) {
+                fields.push(SearchField {
+                    node_id: node_id.clone(),
+                    field: "output".to_string(),
+                    text,
+                });
+            }
+        }
+    }
+    fields
+}
+
+fn extract_fields(area: &str, value: &Value) -> Vec<SearchField> {
+    match area {
+        "graphs" => graph_fields(value),
+        "runs" => run_fields(value),
+        _ => Vec::new(),
+    }
+}
+
+fn shard_path(index_dir: &Path, area: &str, file_name: &str) -> PathBuf {
+    index_dir.join(area).join(file_name)
+}
+
+fn load_shard(index_dir: &Path, area: &str, file_name: &str) -> Option<SearchShard> {
+    fs::read_to_string(shard_path(index_dir, area, file_name))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<SearchShard>(&raw).ok())
+        .filter(|shard| shard.version == SEARCH_INDEX_VERSION)
+}
+
+fn load_area_shards(index_dir: &Path, area: &str) -> Vec<(String, SearchShard)> {
+    let Ok(entries) = fs::read_dir(index_dir.join(area)) else {
+        return Vec::new();
+    };
+    let mut shards = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter_map(|file_name| {
+            load_shard(index_dir, area, &file_name).map(|shard| (file_name, shard))
+        })
+        .collect::<Vec<_>>();
+    shards.sort_by(|a, b| a.0.cmp(&b.0));
+    shards
+}
+
+fn write_shard(
+    index_dir: &Path,
+    area: &str,
+    file_name: &str,
+    stamp: &str,
+    value: &Value,
+) -> Result<(), String> {
+    let mut docs = Vec::new();
+    for (position, field) in extract_fields(area, value).into_iter().enumerate() {
+        let text = field.text.chars().take(MAX_FIELD_CHARS).collect::<String>();
+        let tokens = tokenize(&text);
+        if tokens.is_empty() {
+            continue;
+        }
+        let mut terms: BTreeMap<String, u32> = BTreeMap::new();
+        for token in &tokens {
+            *terms.entry(token.clone()).or_insert(0) += 1;
+        }
+        docs.push(SearchDoc {
+            position,
+            node_id: field.node_id,
+            field: field.field,
+            length: tokens.len(),
+            snippet: text.chars().take(EXCERPT_CHARS).collect(),
+            terms,
+        });
+    }
+    let shard = SearchShard {
+        version: SEARCH_INDEX_VERSION,
+        stamp: stamp.to_string(),
+        docs,
+    };
+    let area_dir = index_dir.join(area);
+    fs::create_dir_all(&area_dir)
+        .map_err(|e| format!("failed to create search index directory: {e}"))?;
+    let json = serde_json::to_string(&shard)
+        .map_err(|e| format!("failed to serialize search index: {e}"))?;
+    write_atomic(&area_dir.join(file_name), json.as_bytes())
+        .map_err(|e| format!("failed to write search index: {e}"))
+}
+
+fn drop_shard(index_dir: &Path, area: &str, file_name: &str) -> Result<(), String> {
+    match fs::remove_file(shard_path(index_dir, area, file_name)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("failed to remove search index entry: {e}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn lock_index() -> Result<std::sync::MutexGuard<'static, ()>, String> {
+    SEARCH_INDEX_LOCK
+        .lock()
+        .map_err(|_| "search index lock is poisoned".to_string())
+}
+
+pub fn index_search_file(
+    index_dir: &Path,
+    area: &str,
+    file_name: &str,
+    stamp: &str,
+    value: &Value,
+) -> Result<(), String> {
+    let _guard = lock_index()?;
+    write_shard(index_dir, area, file_name, stamp, value)
+}
+
+pub fn remove_search_file(index_dir: &Path, area: &str, file_name: &str) -> Result<(), String> {
+    let _guard = lock_index()?;
+    drop_shard(index_dir, area, file_name)
+}
+
+// Re-indexes files whose stamp changed since they were indexed (or that were
+// never indexed) and drops files that no longer exist in `area`. Only shards
+// of changed files are written.
+pub fn reconcile_search_index(
+    index_dir: &Path,
+    area: &str,
+    files: &[(String, String)],
+    load: impl Fn(&str) -> Option<Value>,
+) -> Result<(), String> {
+    let _guard = lock_index()?;
+    // Version 1 kept the whole corpus in a single file.
+    let _ = fs::remove_file(index_dir.join(LEGACY_INDEX_FILE));
+    let present = files
+        .iter()
+        .map(|(file_name, _)| file_name.as_str())
+        .collect::<BTreeSet<_>>();
+    let indexed = fs::read_dir(index_dir.join(area))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    for file_name in indexed {
+        if !present.contains(file_name.as_str()) {
+            drop_shard(index_dir, area, &file_name)?;
+        }
+    }
+    for (file_name, stamp) in files {
+        if load_shard(index_dir, area, file_name).map(|shard| shard.stamp) == Some(stamp.clone()) {
+            continue;
+        }
+        match load(file_name) {
+            Some(value) => write_shard(index_dir, area, file_name, stamp, &value)?,
+            None => drop_shard(index_dir, area, file_name)?,
+        }
+    }
+    Ok(())
+}
+
+// Char ranges are used so the frontend can slice the excerpt without caring
+// about UTF-8 byte offsets.
+fn excerpt_with_highlights(text: &str, terms: &[String]) -> (String, Vec<[usize; 2]>) {
+    let chars = text.chars().collect::<Vec<_>>();
+    let lower = chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect::<Vec<_>>();
+    let term_chars = terms
+        .iter()
+        .map(|term| term.chars().collect::<Vec<_>>())
+        .filter(|term| !term.is_empty())
+        .collect::<Vec<_>>();
+    let mut matches = Vec::new();
+    let mut index = 0;
+    while index < lower.len() {
+        let found = term_chars
+            .iter()
+            .filter(|term| lower[index..].starts_with(term))
+            .map(Vec::len)
+            .max();
+        match found {
+            Some(len) => {
+                matches.push([index, index + len]);
+                index += len;
+            }
+            None => index += 1,
+        }
+    }
+
+    let first = matches.first().map(|range| range[0]).unwrap_or(0);
+    let start = first.saturating_sub(EXCERPT_CHARS / 4);
+    let end = (start + EXCERPT_CHARS).min(chars.len());
+    let start = end.saturating_sub(EXCERPT_CHARS).min(start);
+    let lead = if start > 0 { "…" } else { "" };
+    let trail = if end < chars.len() { "…" } else { "" };
+    let offset = lead.chars().count();
+    let excerpt = format!(
+        "{lead}{}{trail}",
+        chars[start..end].iter().collect::<String>()
+    );
+    let highlights = matches
+        .into_iter()
+        .filter(|range| range[0] >= start && range[1] <= end)
+        .map(|range| [range[0] - start + offset, range[1] - start + offset])
+        .collect();
+    (excerpt, highlights)
+}
+
+// The field text a hit was indexed from, or its stored snippet when the
+// source file is gone or no longer has that field.
+fn source_text(fields: Option<&Vec<SearchField>>, doc: &SearchDoc) -> String {
+    fields
+        .and_then(|fields| fields.get(doc.position))
+        .filter(|field| field.field == doc.field && field.node_id == doc.node_id)
+        .map(|field| field.text.chars().take(MAX_FIELD_CHARS).collect())
+        .unwrap_or_else(|| doc.snippet.clone())
+}
+
+// BM25 over indexed fields; every query token must match for a field to hit.
+// `load` reads a source file by area and name for the returned excerpts.
+pub fn search_index(
+    index_dir: &Path,
+    query: &SearchQuery,
+    load: impl Fn(&str, &str) -> Option<Value>,
+) -> SearchResult {
+    let terms = tokenize(&query.text)
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let areas = match query.area.as_deref().map(str::trim) {
+        Some(area) if !area.is_empty() => vec![area],
+        _ => SEARCH_AREAS.to_vec(),
+    };
+    let shards = areas
+        .into_iter()
+        .flat_map(|area| {
+            load_area_shards(index_dir, area)
+                .into_iter()
+                .map(move |(file_name, shard)| (area, file_name, shard))
+        })
+        .collect::<Vec<_>>();
+    let docs = shards
+        .iter()
+        .flat_map(|(area, file_name, shard)| {
+            shard
+                .docs
+                .iter()
+                .map(move |doc| (*area, file_name.as_str(), doc))
+        })
+        .collect::<Vec<_>>();
+    if terms.is_empty() || docs.is_empty() {
+        return SearchResult {
+            total: 0,
+            hits: Vec::new(),
+        };
+    }
+
+    let doc_count = docs.len() as f64;
+    let avg_length = docs.iter().map(|(_, _, doc)| doc.length).sum::<usize>() as f64 / doc_count;
+    let idfs = terms
+        .iter()
+        .map(|term| {
+            let df = docs
+                .iter()
+                .filter(|(_, _, doc)| doc.terms.contains_key(term))
+                .count() as f64;
+            ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln()
+        })
+        .collect::<Vec<_>>();
+    let mut ranked = docs
+        .into_iter()
+        .filter_map(|(area, file_name, doc)| {
+            let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * doc.length as f64 / avg_length);
+            let mut score = 0.0;
+            for (term, idf) in terms.iter().zip(&idfs) {
+                let tf = f64::from(*doc.terms.get(term)?);
+                score += idf * tf * (BM25_K1 + 1.0) / (tf + norm);
+            }
+            Some((area, file_name, doc, score))
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.3.total_cmp(&a.3).then_with(|| a.1.cmp(b.1)));
+    let total = ranked.len();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let mut sources: BTreeMap<(&str, &str), Option<Vec<SearchField>>> = BTreeMap::new();
+    let hits = ranked
+        .into_iter()
+        .take(limit)
+        .map(|(area, file_name, doc, score)| {
+            let fields = sources
+                .entry((area, file_name))
+                .or_insert_with(|| load(area, file_name).map(|value| extract_fields(area, &value)));
+            let text = source_text(fields.as_ref(), doc);
+            let (excerpt, highlights) = excerpt_with_highlights(&text, &terms);
+            SearchHit {
+                area: area.to_string(),
+                file_name: file_name.to_string(),
+                node_id: doc.node_id.clone(),
+                field: doc.field.clone(),
+                score,
+                excerpt,
+                highlights,
+            }
+        })
+        .collect();
+    SearchResult { total, hits }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tokenizes_cjk_as_bigrams() {
+        assert_eq!(
+            tokenize("Rail 검색기능 a 東京"),
+            vec!["rail", "검색", "색기", "기능", "東京"]
+        );
+        assert_eq!(tokenize("ひらがな"), vec!["ひら", "らが", "がな"]);
+    }
+
+    fn temp_index_dir(label: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rail_search_{label}_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ))
+    }
+
+    #[test]
+    fn indexes_incrementally_and_ranks_hits() {
+        let index_dir = temp_index_dir("index");
+        let graph = json!({
+            "nodes": [{ "id": "turn-1", "config": { "prompt": "시장 동향을 요약해 주세요" } }]
+        });
+        let run = json!({
+            "question": "market trends",
+            "feedPosts": [{
+                "nodeId": "turn-1",
+                "summary": "Market trends look stable",
+                "attachments": [{ "kind": "markdown", "content": "# Report\n최근 시장 동향은 안정적입니다." }]
+            }]
+        });
+        let load = |area: &str, _: &str| match area {
+            "graphs" => Some(graph.clone()),
+            _ => Some(run.clone()),
+        };
+        index_search_file(&index_dir, "graphs", "alpha.json", "1", &graph).expect("index graph");
+        index_search_file(&index_dir, "runs", "run-1.json", "1", &run).expect("index run");
+
+        let korean = search_index(
+            &index_dir,
+            &SearchQuery {
+                text: "시장 동향".to_string(),
+                ..SearchQuery::default()
+            },
+            load,
+        );
+        assert_eq!(korean.total, 2);
+        let output = korean
+            .hits
+            .iter()
+            .find(|hit| hit.area == "runs")
+            .expect("run hit");
+        assert_eq!(output.node_id.as_deref(), Some("turn-1"));
+        assert_eq!(output.field, "output");
+        let [start, end] = output.highlights[0];
+        let marked = output
+            .excerpt
+            .chars()
+            .skip(start)
+            .take(end - start)
+            .collect::<String>();
+        assert_eq!(marked, "시장");
+
+        let runs_only = search_index(
+            &index_dir,
+            &SearchQuery {
+                text: "market".to_string(),
+                area: Some("runs".to_string()),
+                limit: None,
+            },
+            load,
+        );
+        assert_eq!(runs_only.total, 2);
+
+        reconcile_search_index(&index_dir, "runs", &[], |_| None).expect("reconcile");
+        let removed = search_index(
+            &index_dir,
+            &SearchQuery {
+                text: "market".to_string(),
+                ..SearchQuery::default()
+            },
+            load,
+        );
+        assert_eq!(removed.total, 0);
+        remove_search_file(&index_dir, "graphs", "alpha.json").expect("remove graph");
+        assert!(load_area_shards(&index_dir, "graphs").is_empty());
+
+        let _ = fs::remove_dir_all(index_dir);
+    }
+
+    #[test]
+    fn shards_keep_snippets_and_excerpts_come_from_the_source() {
+        let index_dir = temp_index_dir("shards");
+        let long_answer = format!("{} needle at the end", "filler words ".repeat(200));
+        let run = json!({ "finalAnswer": long_answer });
+        index_search_file(&index_dir, "runs", "run-1.json", "1", &run).expect("index run");
+        index_search_file(
+            &index_dir,
+            "runs",
+            "run-2.json",
+            "1",
+            &json!({ "question": "other" }),
+        )
+        .expect("index second run");
+
+        let shard = fs::read_to_string(shard_path(&index_dir, "runs", "run-1.json")).unwrap();
+        assert!(!shard.contains("needle at the end"));
+
+        let query = SearchQuery {
+            text: "needle".to_string(),
+            ..SearchQuery::default()
+        };
+        let from_source = search_index(&index_dir, &query, |_, _| Some(run.clone()));
+        assert_eq!(from_source.total, 1);
+        assert!(from_source.hits[0].excerpt.contains("needle at the end"));
+        assert_eq!(from_source.hits[0].highlights.len(), 1);
+
+        let without_source = search_index(&index_dir, &query, |_, _| None);
+        assert_eq!(without_source.total, 1);
+        assert!(without_source.hits[0].excerpt.starts_with("filler words"));
+        assert!(without_source.hits[0].highlights.is_empty());
+
+        let _ = fs::remove_dir_all(index_dir);
+    }
+}
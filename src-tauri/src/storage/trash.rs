@@ -0,0 +1,233 @@
+use super::atomic_file::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const WORKSPACE_TRASH_DIR: &str = ".rail/trash";
+const TRASH_META_FILE: &str = "meta.json";
+const TRASH_PAYLOAD_DIR: &str = "payload";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub id: String,
+    pub area: String,
+    pub file_name: String,
+    pub original_path: String,
+    pub trashed_at: String,
+    pub reason: String,
+    pub size_bytes: u64,
+}
+
+fn valid_segment(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+}
+
+fn entry_dir(trash_root: &Path, area: &str, id: &str) -> Result<PathBuf, String> {
+    if !valid_segment(area) || !valid_segment(id) {
+        return Err("invalid trash entry".to_string());
+    }
+    Ok(trash_root.join(area).join(id))
+}
+
+fn entry_size(path: &Path) -> u64 {
+    if !path.is_dir() {
+        return fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+// Rename first; fall back to copy + remove when the trash lives on another
+// device. Directories (run folders) are only ever renamed.
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        return Err(format!("failed to move directory {}", from.display()));
+    }
+    fs::copy(from, to).map_err(|e| format!("failed to copy {}: {e}", from.display()))?;
+    fs::remove_file(from).map_err(|e| format!("failed to remove {}: {e}", from.display()))
+}
+
+pub fn move_to_trash(
+    trash_root: &Path,
+    area: &str,
+    path: &Path,
+    reason: &str,
+) -> Result<TrashEntry, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    if !meta.is_file() && !meta.is_dir() {
+        return Err("only files and directories can be moved to trash".to_string());
+    }
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| "trash target has no file name".to_string())?
+        .to_string();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    let id = format!("{nanos}-{}", std::process::id());
+    let dir = entry_dir(trash_root, area, &id)?;
+    super::watcher::note_self_write(path);
+    fs::create_dir_all(dir.join(TRASH_PAYLOAD_DIR))
+        .map_err(|e| format!("failed to create trash directory: {e}"))?;
+
+    let entry = TrashEntry {
+        id,
+        area: area.to_string(),
+        file_name: file_name.clone(),
+        original_path: path.to_string_lossy().to_string(),
+        trashed_at: chrono::Utc::now().to_rfc3339(),
+        reason: reason.to_string(),
+        size_bytes: entry_size(path),
+    };
+    let json = serde_json::to_vec_pretty(&entry)
+        .map_err(|e| format!("failed to serialize trash entry: {e}"))?;
+    write_atomic(&dir.join(TRASH_META_FILE), &json)
+        .map_err(|e| format!("failed to write trash entry: {e}"))?;
+    if let Err(error) = move_file(path, &dir.join(TRASH_PAYLOAD_DIR).join(&file_name)) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(error);
+    }
+    Ok(entry)
+}
+
+pub fn list_trash(trash_root: &Path, area: Option<&str>) -> Vec<TrashEntry> {
+    let areas = match area {
+        Some(area) => vec![trash_root.join(area)],
+        None => fs::read_dir(trash_root)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|row| row.path()))
+                    .filter(|path| path.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    let mut out = areas
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok().map(|row| row.path())))
+        .filter_map(|dir| fs::read_to_string(dir.join(TRASH_META_FILE)).ok())
+        .filter_map(|raw| serde_json::from_str::<TrashEntry>(&raw).ok())
+        .collect::<Vec<_>>();
+    out.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at).then(b.id.cmp(&a.id)));
+    out
+}
+
+pub fn read_trash_entry(trash_root: &Path, area: &str, id: &str) -> Result<TrashEntry, String> {
+    let dir = entry_dir(trash_root, area, id)?;
+    let raw = fs::read_to_string(dir.join(TRASH_META_FILE))
+        .map_err(|e| format!("trash entry not found: {e}"))?;
+    serde_json::from_str(&raw).map_err(|e| format!("invalid trash entry: {e}"))
+}
+
+// Moves the payload back to its original path. An existing file at that path
+// is itself moved to trash when `overwrite` is set, otherwise restore fails.
+pub fn restore_from_trash(
+    trash_root: &Path,
+    area: &str,
+    id: &str,
+    overwrite: bool,
+) -> Result<TrashEntry, String> {
+    let entry = read_trash_entry(trash_root, area, id)?;
+    let dir = entry_dir(trash_root, area, id)?;
+    let target = PathBuf::from(&entry.original_path);
+    if target.exists() {
+        if !overwrite {
+            return Err(format!(
+                "restore target already exists: {}",
+                entry.original_path
+            ));
+        }
+        move_to_trash(trash_root, area, &target, "overwrittenByRestore")?;
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create restore directory: {e}"))?;
+    }
+    move_file(&dir.join(TRASH_PAYLOAD_DIR).join(&entry.file_name), &target)?;
+    let _ = fs::remove_dir_all(&dir);
+    Ok(entry)
+}
+
+pub fn empty_trash(
+    trash_root: &Path,
+    area: Option<&str>,
+    older_than_days: Option<u64>,
+) -> Result<usize, String> {
+    let cutoff = older_than_days
+        .map(|days| chrono::Utc::now() - chrono::Duration::days(days.min(36_500) as i64));
+    let mut removed = 0;
+    for entry in list_trash(trash_root, area) {
+        let expired = match cutoff {
+            Some(cutoff) => chrono::DateTime::parse_from_rfc3339(&entry.trashed_at)
+                .map(|at| at < cutoff)
+                .unwrap_or(true),
+            None => true,
+        };
+        if !expired {
+            continue;
+        }
+        let dir = entry_dir(trash_root, &entry.area, &entry.id)?;
+        fs::remove_dir_all(&dir)
+            .map_err(|e| format!("failed to empty trash entry {}: {e}", entry.id))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trashes_restores_and_empties_files() {
+        let root = std::env::temp_dir().join(format!(
+            "rail_trash_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let trash_root = root.join("trash");
+        let data_dir = root.join("graphs");
+        fs::create_dir_all(&data_dir).expect("create data dir");
+        let original = data_dir.join("alpha.json");
+        fs::write(&original, "{\"version\":3}").expect("write original");
+
+        let entry = move_to_trash(&trash_root, "graphs", &original, "deleted").expect("trash");
+        assert!(!original.exists());
+        assert_eq!(list_trash(&trash_root, Some("graphs")).len(), 1);
+
+        fs::write(&original, "{\"version\":1}").expect("write replacement");
+        assert!(restore_from_trash(&trash_root, "graphs", &entry.id, false).is_err());
+        restore_from_trash(&trash_root, "graphs", &entry.id, true).expect("restore");
+        assert_eq!(
+            fs::read_to_string(&original).expect("read restored"),
+            "{\"version\":3}"
+        );
+
+        let remaining = list_trash(&trash_root, None);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].reason, "overwrittenByRestore");
+        assert_eq!(empty_trash(&trash_root, None, None).expect("empty"), 1);
+        assert!(list_trash(&trash_root, None).is_empty());
+        assert!(read_trash_entry(&trash_root, "graphs", "../escape").is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,369 @@
+use super::atomic_file::is_temp_file;
+use notify::{event::ModifyKind, event::RenameMode, EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+    sync::{
+        mpsc::{channel, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEBOUNCE: Duration = Duration::from_millis(300);
+// Continuous writes still flush at least this often.
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+const WORKSPACE_IGNORED_DIRS: [&str; 1] = ["trash"];
+// Long enough to cover delayed platform events for a write the app just made.
+const SELF_WRITE_WINDOW: Duration = Duration::from_secs(3);
+
+// Paths the app wrote, moved or deleted itself. Their events are dropped so
+// the UI is not told to reload what it just saved.
+static SELF_WRITES: Mutex<BTreeMap<PathBuf, Instant>> = Mutex::new(BTreeMap::new());
+
+pub fn note_self_write(path: &Path) {
+    if let Ok(mut writes) = SELF_WRITES.lock() {
+        writes.retain(|_, at| at.elapsed() < SELF_WRITE_WINDOW);
+        writes.insert(path.to_path_buf(), Instant::now());
+    }
+}
+
+fn is_self_write(path: &Path) -> bool {
+    SELF_WRITES
+        .lock()
+        .map(|writes| {
+            // A moved or deleted run directory covers the files inside it.
+            path.ancestors().any(|path| {
+                writes
+                    .get(path)
+                    .is_some_and(|at| at.elapsed() < SELF_WRITE_WINDOW)
+            })
+        })
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+    pub scope: String,
+    pub kind: StorageChangeKind,
+    pub path: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChangeBatch {
+    pub changes: Vec<StorageChange>,
+    pub refresh: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchTarget {
+    pub scope: String,
+    pub root: PathBuf,
+}
+
+pub struct StorageWatcher {
+    _watcher: notify::RecommendedWatcher,
+    pub targets: Vec<WatchTarget>,
+}
+
+fn relative_parts(target: &WatchTarget, path: &Path) -> Option<Vec<String>> {
+    let relative = path.strip_prefix(&target.root).ok()?;
+    let parts = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => part.to_str().map(str::to_string),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then_some(parts)
+}
+
+// Maps a raw path to the item the UI knows about: `<name>.json` for graphs and
+// runs (run directories included), the `.rail`-relative path for workspaces.
+// Indexes, backups, temp files and trash are internal and yield None.
+pub fn classify_path(target: &WatchTarget, path: &Path) -> Option<String> {
+    if is_temp_file(path) {
+        return None;
+    }
+    let parts = relative_parts(target, path)?;
+    if parts.iter().any(|part| part.starts_with('.')) {
+        return None;
+    }
+    match target.scope.as_str() {
+        "graphs" => (parts.len() == 1 && parts[0].ends_with(".json")).then(|| parts[0].clone()),
+        "runs" if parts.len() == 1 && parts[0].ends_with(".json") => Some(parts[0].clone()),
+        "runs" => Some(format!("{}.json", parts[0])),
+        _ => (!WORKSPACE_IGNORED_DIRS.contains(&parts[0].as_str())).then(|| parts.join("/")),
+    }
+}
+
+fn refresh_hint(scope: &str, name: &str) -> &'static str {
+    match scope {
+        "graphs" => "graphList",
+        "runs" => "runList",
+        _ if name.starts_with("dashboard/snapshots/") => "dashboardSnapshotList",
+        _ if name.starts_with("dashboard/raw/") => "dashboardRawList",
+        _ => "workspace",
+    }
+}
+
+// Decides the reported kind from the first event seen for a path and whether
+// it still exists once the burst settles; create-then-delete is dropped.
+pub fn coalesce_kind(first: StorageChangeKind, exists_now: bool) -> Option<StorageChangeKind> {
+    match (first, exists_now) {
+        (StorageChangeKind::Created, true) => Some(StorageChangeKind::Created),
+        (StorageChangeKind::Created, false) => None,
+        (_, true) => Some(StorageChangeKind::Modified),
+        (_, false) => Some(StorageChangeKind::Deleted),
+    }
+}
+
+fn event_kinds(event: &notify::Event) -> Vec<(PathBuf, StorageChangeKind)> {
+    let kind = match event.kind {
+        EventKind::Create(_) => StorageChangeKind::Created,
+        EventKind::Remove(_) => StorageChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            return vec![
+                (event.paths[0].clone(), StorageChangeKind::Deleted),
+                (event.paths[1].clone(), StorageChangeKind::Created),
+            ];
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => StorageChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => StorageChangeKind::Created,
+        EventKind::Modify(_) | EventKind::Any => StorageChangeKind::Modified,
+        EventKind::Access(_) | EventKind::Other => return Vec::new(),
+    };
+    event
+        .paths
+        .iter()
+        .map(|path| (path.clone(), kind))
+        .collect()
+}
+
+#[derive(Default)]
+pub struct ChangeBatcher {
+    pending: BTreeMap<(String, String), (PathBuf, StorageChangeKind)>,
+}
+
+impl ChangeBatcher {
+    pub fn record(&mut self, targets: &[WatchTarget], path: &Path, kind: StorageChangeKind) {
+        if is_self_write(path) {
+            return;
+        }
+        let Some((target, name)) = targets
+            .iter()
+            .find_map(|target| classify_path(target, path).map(|name| (target, name)))
+        else {
+            return;
+        };
+        // Events inside a run directory stand for the run itself.
+        let item_path = if target.scope == "runs" && !path.starts_with(target.root.join(&name)) {
+            target.root.join(name.trim_end_matches(".json"))
+        } else {
+            path.to_path_buf()
+        };
+        let kind = if item_path != path {
+            StorageChangeKind::Modified
+        } else {
+            kind
+        };
+        self.pending
+            .entry((target.scope.clone(), name))
+            .or_insert((item_path, kind));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn flush(&mut self, exists: impl Fn(&Path) -> bool) -> StorageChangeBatch {
+        let mut refresh = BTreeSet::new();
+        let mut changes = Vec::new();
+        for ((scope, name), (path, first)) in std::mem::take(&mut self.pending) {
+            let Some(kind) = coalesce_kind(first, exists(&path)) else {
+                continue;
+            };
+            refresh.insert(refresh_hint(&scope, &name).to_string());
+            changes.push(StorageChange {
+                scope,
+                kind,
+                path: path.to_string_lossy().to_string(),
+                name,
+            });
+        }
+        StorageChangeBatch {
+            changes,
+            refresh: refresh.into_iter().collect(),
+        }
+    }
+}
+
+pub fn start_watcher(
+    targets: Vec<WatchTarget>,
+    emit: impl Fn(StorageChangeBatch) + Send + 'static,
+) -> Result<StorageWatcher, String> {
+    let (tx, rx) = channel::<notify::Event>();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| format!("failed to create storage watcher: {e}"))?;
+    for target in &targets {
+        watcher
+            .watch(&target.root, RecursiveMode::Recursive)
+            .map_err(|e| format!("failed to watch {}: {e}", target.root.display()))?;
+    }
+
+    let thread_targets = targets.clone();
+    thread::spawn(move || {
+        let mut batcher = ChangeBatcher::default();
+        let mut batch_started: Option<Instant> = None;
+        loop {
+            let disconnected = match rx.recv_timeout(DEBOUNCE) {
+                Ok(event) => {
+                    for (path, kind) in event_kinds(&event) {
+                        batcher.record(&thread_targets, &path, kind);
+                    }
+                    if !batcher.is_empty() && batch_started.is_none() {
+                        batch_started = Some(Instant::now());
+                    }
+                    let overdue = batch_started
+                        .map(|started| started.elapsed() >= MAX_BATCH_DELAY)
+                        .unwrap_or(false);
+                    if !overdue {
+                        continue;
+                    }
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => true,
+            };
+            if !batcher.is_empty() {
+                let batch = batcher.flush(|path| path.exists());
+                batch_started = None;
+                if !batch.changes.is_empty() {
+                    emit(batch);
+                }
+            }
+            if disconnected {
+                break;
+            }
+        }
+    });
+
+    Ok(StorageWatcher {
+        _watcher: watcher,
+        targets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_and_coalesces_changes() {
+        let targets = vec![
+            WatchTarget {
+                scope: "graphs".to_string(),
+                root: PathBuf::from("/data/graphs"),
+            },
+            WatchTarget {
+                scope: "runs".to_string(),
+                root: PathBuf::from("/data/runs"),
+            },
+            WatchTarget {
+                scope: "workspace".to_string(),
+                root: PathBuf::from("/ws/.rail"),
+            },
+        ];
+        let mut batcher = ChangeBatcher::default();
+        let record = |batcher: &mut ChangeBatcher, path: &str, kind| {
+            batcher.record(&targets, Path::new(path), kind)
+        };
+        record(
+            &mut batcher,
+            "/data/graphs/a.json",
+            StorageChangeKind::Modified,
+        );
+        record(
+            &mut batcher,
+            "/data/graphs/a.json",
+            StorageChangeKind::Deleted,
+        );
+        record(
+            &mut batcher,
+            "/data/graphs/.index/manifest.json",
+            StorageChangeKind::Modified,
+        );
+        record(
+            &mut batcher,
+            "/data/graphs/b.json.tmp",
+            StorageChangeKind::Created,
+        );
+        record(
+            &mut batcher,
+            "/data/runs/run-1/artifacts/x.artifact",
+            StorageChangeKind::Created,
+        );
+        record(
+            &mut batcher,
+            "/data/runs/run-2.json",
+            StorageChangeKind::Created,
+        );
+        record(
+            &mut batcher,
+            "/ws/.rail/dashboard/snapshots/t/s.json",
+            StorageChangeKind::Created,
+        );
+        record(
+            &mut batcher,
+            "/ws/.rail/trash/workspace/1/meta.json",
+            StorageChangeKind::Created,
+        );
+        note_self_write(Path::new("/data/graphs/saved.json"));
+        record(
+            &mut batcher,
+            "/data/graphs/saved.json",
+            StorageChangeKind::Modified,
+        );
+
+        let batch = batcher.flush(|path| path != Path::new("/data/runs/run-2.json"));
+        let summary = batch
+            .changes
+            .iter()
+            .map(|change| (change.scope.as_str(), change.name.as_str(), change.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("graphs", "a.json", StorageChangeKind::Modified),
+                ("runs", "run-1.json", StorageChangeKind::Modified),
+                (
+                    "workspace",
+                    "dashboard/snapshots/t/s.json",
+                    StorageChangeKind::Created
+                ),
+            ]
+        );
+        assert_eq!(
+            batch.refresh,
+            vec!["dashboardSnapshotList", "graphList", "runList"]
+        );
+        assert!(batcher.is_empty());
+    }
+}
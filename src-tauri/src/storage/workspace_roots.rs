@@ -0,0 +1,370 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspacePathErrorKind {
+    InvalidPath,
+    NotRegistered,
+    OutsideRoot,
+    SymlinkEscape,
+    Io,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspacePathError {
+    pub kind: WorkspacePathErrorKind,
+    pub message: String,
+    pub path: Option<String>,
+    pub root: Option<String>,
+}
+
+impl WorkspacePathError {
+    fn new(kind: WorkspacePathErrorKind, message: String, path: &Path) -> Self {
+        Self {
+            kind,
+            message,
+            path: Some(path.to_string_lossy().to_string()),
+            root: None,
+        }
+    }
+}
+
+impl fmt::Display for WorkspacePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for WorkspacePathError {
+    fn from(message: String) -> Self {
+        Self {
+            kind: WorkspacePathErrorKind::Io,
+            message,
+            path: None,
+            root: None,
+        }
+    }
+}
+
+impl From<&str> for WorkspacePathError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRoot {
+    pub path: String,
+    pub canonical_path: String,
+    pub registered_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRootRegistry {
+    #[serde(default)]
+    pub roots: Vec<WorkspaceRoot>,
+    // Set once the cwd saved by versions without roots has been carried over.
+    #[serde(default)]
+    pub legacy_cwd_adopted: bool,
+}
+
+fn lexical_normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    Some(out)
+}
+
+// Canonicalizes the longest existing ancestor so paths that are about to be
+// created can be checked too; the missing tail cannot contain symlinks.
+pub fn canonicalize_existing_prefix(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path.to_path_buf();
+    let mut tail = Vec::new();
+    loop {
+        if fs::symlink_metadata(&existing).is_ok() {
+            break;
+        }
+        match (existing.file_name(), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                tail.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => break,
+        }
+    }
+    let mut resolved = fs::canonicalize(&existing)?;
+    for name in tail.into_iter().rev() {
+        resolved.push(name);
+    }
+    Ok(resolved)
+}
+
+// The legacy cwd comes from the webview, so only an existing project folder
+// qualifies: never the filesystem root, the home directory or its parents.
+pub fn legacy_cwd_candidate(raw: &str, home: Option<&Path>) -> Result<PathBuf, String> {
+    let path = PathBuf::from(raw.trim());
+    if raw.trim().is_empty() || !path.is_absolute() {
+        return Err("legacy cwd must be an absolute path".to_string());
+    }
+    let canonical =
+        fs::canonicalize(&path).map_err(|e| format!("failed to resolve legacy cwd: {e}"))?;
+    if !canonical.is_dir() {
+        return Err("legacy cwd is not a directory".to_string());
+    }
+    if canonical.parent().is_none() {
+        return Err("legacy cwd cannot be the filesystem root".to_string());
+    }
+    if let Some(home) = home.and_then(|home| fs::canonicalize(home).ok()) {
+        if home.starts_with(&canonical) {
+            return Err(
+                "legacy cwd cannot be the home directory or one of its parents".to_string(),
+            );
+        }
+    }
+    Ok(canonical)
+}
+
+impl WorkspaceRootRegistry {
+    pub fn register(&mut self, raw: &str) -> Result<WorkspaceRoot, String> {
+        let path = PathBuf::from(raw.trim());
+        if raw.trim().is_empty() || !path.is_absolute() {
+            return Err("workspace root must be an absolute path".to_string());
+        }
+        let canonical = fs::canonicalize(&path)
+            .map_err(|e| format!("failed to resolve workspace root: {e}"))?;
+        if !canonical.is_dir() {
+            return Err("workspace root is not a directory".to_string());
+        }
+        let canonical_path = canonical.to_string_lossy().to_string();
+        if let Some(existing) = self
+            .roots
+            .iter()
+            .find(|root| root.canonical_path == canonical_path)
+        {
+            return Ok(existing.clone());
+        }
+        let root = WorkspaceRoot {
+            path: path.to_string_lossy().to_string(),
+            canonical_path,
+            registered_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.roots.push(root.clone());
+        Ok(root)
+    }
+
+    pub fn legacy_cwd_pending(&self) -> bool {
+        !self.legacy_cwd_adopted && self.roots.is_empty()
+    }
+
+    // One-time migration for the cwd earlier versions persisted without a
+    // registered root. `confirmed` is what the user picked in the folder
+    // picker; anything other than the legacy cwd itself registers nothing.
+    pub fn adopt_legacy_cwd(
+        &mut self,
+        raw: &str,
+        confirmed: &str,
+        home: Option<&Path>,
+    ) -> Result<Option<WorkspaceRoot>, String> {
+        if !self.legacy_cwd_pending() {
+            self.legacy_cwd_adopted = true;
+            return Ok(None);
+        }
+        let legacy = legacy_cwd_candidate(raw, home)?;
+        self.legacy_cwd_adopted = true;
+        if fs::canonicalize(confirmed.trim()).ok().as_deref() != Some(legacy.as_path()) {
+            return Ok(None);
+        }
+        self.register(raw).map(Some)
+    }
+
+    pub fn remove(&mut self, raw: &str) -> bool {
+        let trimmed = raw.trim();
+        let canonical = fs::canonicalize(trimmed)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let before = self.roots.len();
+        self.roots
+            .retain(|root| root.path != trimmed && root.canonical_path != canonical);
+        self.roots.len() != before
+    }
+
+    pub fn containing_root(&self, canonical: &Path) -> Option<PathBuf> {
+        self.roots
+            .iter()
+            .filter(|root| canonical.starts_with(&root.canonical_path))
+            .max_by_key(|root| root.canonical_path.len())
+            .map(|root| PathBuf::from(&root.canonical_path))
+    }
+
+    // Returns the canonical form of `raw` when it lives under a registered root.
+    pub fn resolve(&self, raw: &str) -> Result<PathBuf, WorkspacePathError> {
+        let requested = PathBuf::from(raw.trim());
+        if raw.trim().is_empty() || !requested.is_absolute() {
+            return Err(WorkspacePathError::new(
+                WorkspacePathErrorKind::InvalidPath,
+                "workspace path must be absolute".to_string(),
+                &requested,
+            ));
+        }
+        let Some(lexical) = lexical_normalize(&requested) else {
+            return Err(WorkspacePathError::new(
+                WorkspacePathErrorKind::InvalidPath,
+                "workspace path is not valid".to_string(),
+                &requested,
+            ));
+        };
+        if self.roots.is_empty() {
+            return Err(WorkspacePathError::new(
+                WorkspacePathErrorKind::NotRegistered,
+                "no workspace root is registered; choose the workspace folder first".to_string(),
+                &requested,
+            ));
+        }
+
+        let canonical = canonicalize_existing_prefix(&lexical).map_err(|e| {
+            WorkspacePathError::new(
+                WorkspacePathErrorKind::Io,
+                format!("failed to resolve workspace path: {e}"),
+                &requested,
+            )
+        })?;
+        if self
+            .roots
+            .iter()
+            .any(|root| canonical.starts_with(&root.canonical_path))
+        {
+            return Ok(canonical);
+        }
+
+        let lexical_root = self.roots.iter().find(|root| {
+            lexical.starts_with(&root.path) || lexical.starts_with(&root.canonical_path)
+        });
+        match lexical_root {
+            Some(root) => Err(WorkspacePathError {
+                kind: WorkspacePathErrorKind::SymlinkEscape,
+                message: format!(
+                    "workspace path resolves outside its root through a symlink: {}",
+                    canonical.display()
+                ),
+                path: Some(requested.to_string_lossy().to_string()),
+                root: Some(root.path.clone()),
+            }),
+            None => Err(WorkspacePathError::new(
+                WorkspacePathErrorKind::OutsideRoot,
+                format!(
+                    "workspace path is outside every registered root: {}",
+                    requested.display()
+                ),
+                &requested,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confines_paths_to_registered_roots() {
+        let base = std::env::temp_dir().join(format!(
+            "rail_workspace_roots_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let workspace = base.join("workspace");
+        let outside = base.join("outside");
+        fs::create_dir_all(workspace.join("notes")).expect("create workspace");
+        fs::create_dir_all(&outside).expect("create outside");
+        fs::write(outside.join("secret.txt"), "secret").expect("write secret");
+
+        let mut registry = WorkspaceRootRegistry::default();
+        let unregistered = registry
+            .resolve(&workspace.join("notes/a.md").to_string_lossy())
+            .expect_err("no roots yet");
+        assert_eq!(unregistered.kind, WorkspacePathErrorKind::NotRegistered);
+
+        let adopted = registry
+            .adopt_legacy_cwd(
+                &workspace.to_string_lossy(),
+                &workspace.to_string_lossy(),
+                Some(&base),
+            )
+            .expect("adopt legacy cwd");
+        assert!(adopted.is_some());
+        assert!(registry
+            .adopt_legacy_cwd(
+                &outside.to_string_lossy(),
+                &outside.to_string_lossy(),
+                Some(&base),
+            )
+            .expect("second adoption")
+            .is_none());
+        assert_eq!(registry.roots.len(), 1);
+        assert!(registry
+            .resolve(&workspace.join("notes/new/file.md").to_string_lossy())
+            .is_ok());
+
+        let escaped = registry
+            .resolve(&workspace.join("../outside/secret.txt").to_string_lossy())
+            .expect_err("parent traversal");
+        assert_eq!(escaped.kind, WorkspacePathErrorKind::OutsideRoot);
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, workspace.join("link")).expect("symlink");
+            let linked = registry
+                .resolve(&workspace.join("link/secret.txt").to_string_lossy())
+                .expect_err("symlink escape");
+            assert_eq!(linked.kind, WorkspacePathErrorKind::SymlinkEscape);
+        }
+
+        let _ = fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn legacy_cwd_must_be_a_confirmed_project_folder() {
+        let base = std::env::temp_dir().join(format!(
+            "rail_legacy_cwd_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+        ));
+        let home = base.join("home");
+        let project = home.join("project");
+        fs::create_dir_all(&project).expect("create project");
+
+        assert!(legacy_cwd_candidate("/", None).is_err());
+        assert!(legacy_cwd_candidate(&home.to_string_lossy(), Some(&home)).is_err());
+        assert!(legacy_cwd_candidate(&base.to_string_lossy(), Some(&home)).is_err());
+        assert!(
+            legacy_cwd_candidate(&home.join("missing").to_string_lossy(), Some(&home)).is_err()
+        );
+
+        let mut registry = WorkspaceRootRegistry::default();
+        assert!(registry.adopt_legacy_cwd("/", "/", Some(&home)).is_err());
+        assert!(registry.legacy_cwd_pending());
+
+        let declined = registry
+            .adopt_legacy_cwd(&project.to_string_lossy(), "", Some(&home))
+            .expect("declined adoption");
+        assert!(declined.is_none());
+        assert!(registry.roots.is_empty());
+        assert!(!registry.legacy_cwd_pending());
+
+        let _ = fs::remove_dir_all(base);
+    }
+}
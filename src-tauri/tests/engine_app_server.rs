@@ -0,0 +1,516 @@
+use rail_lib::engine_testing::{
+    save_approval_rules, start_runtime, AppServerTransport, EngineManager, Incoming, RpcConnection,
+    TestRuntime, EVENT_APPROVAL_REQUEST, EVENT_LIFECYCLE, EVENT_NOTIFICATION,
+};
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Stdio,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{
+    test::{mock_app, MockRuntime},
+    App, Listener, Manager,
+};
+use tokio::{
+    process::Command,
+    sync::{
+        mpsc::{self, UnboundedReceiver},
+        Mutex,
+    },
+    time::{sleep, timeout, Duration},
+};
+
+const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Runtime tests point process-wide variables (RAIL_CODEX_BIN, the data dirs)
+// at their own directory, so they take turns.
+static RUNTIME_ENV: Mutex<()> = Mutex::const_new(());
+
+// A per-test scratch directory, removed however the test ends.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "rail_fake_app_server_{name}_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_nanos())
+                .unwrap_or(0)
+        ));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        Self(dir)
+    }
+
+    fn write_script(&self, script: &Value) -> PathBuf {
+        let path = self.0.join("script.json");
+        fs::write(&path, script.to_string()).expect("write script");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn start(name: &str, script: Value) -> (Arc<RpcConnection>, UnboundedReceiver<Incoming>, TempDir) {
+    let dir = TempDir::new(name);
+    let child = Command::new(env!("CARGO_BIN_EXE_fake_app_server"))
+        .args(["app-server", "--listen", "stdio://"])
+        .env("RAIL_FAKE_APP_SERVER_SCRIPT", dir.write_script(&script))
+        .kill_on_drop(true)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn fake app-server");
+    let transport = AppServerTransport::from_child(child).expect("transport");
+    let (tx, rx) = mpsc::unbounded_channel();
+    (Arc::new(RpcConnection::start(transport, tx, None)), rx, dir)
+}
+
+// Next message that is not stderr output, whose timing relative to stdout is not defined.
+async fn recv(rx: &mut UnboundedReceiver<Incoming>) -> Incoming {
+    loop {
+        let message = timeout(RECV_TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for app-server")
+            .expect("incoming channel closed");
+        if !matches!(message, Incoming::Stderr(_)) {
+            return message;
+        }
+    }
+}
+
+#[tokio::test]
+async fn handshake_round_trips_initialize() {
+    let (rpc, mut rx, _dir) = start(
+        "handshake",
+        json!({
+            "onStart": [{ "notify": { "method": "fake/booted", "params": {} } }],
+            "methods": {
+                "initialized": [{ "notify": { "method": "fake/ready", "params": { "ok": true } } }]
+            }
+        }),
+    );
+
+    let result = rpc
+        .request(
+            "initialize",
+            json!({ "clientInfo": { "name": "rail" } }),
+            RECV_TIMEOUT,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result["userAgent"], "rail-fake-app-server");
+    rpc.notify("initialized", json!({})).await.unwrap();
+
+    assert_eq!(
+        recv(&mut rx).await,
+        Incoming::Notification {
+            method: "fake/booted".to_string(),
+            params: json!({})
+        }
+    );
+    assert_eq!(
+        recv(&mut rx).await,
+        Incoming::Notification {
+            method: "fake/ready".to_string(),
+            params: json!({ "ok": true })
+        }
+    );
+    rpc.shutdown().await;
+}
+
+#[tokio::test]
+async fn timed_out_requests_are_forgotten() {
+    let (rpc, _rx, _dir) = start(
+        "timeout",
+        json!({ "methods": { "slow": [{ "delayMs": 400 }] } }),
+    );
+
+    let err = rpc
+        .request("slow", json!({}), Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert_eq!(err, "request timed out: slow");
+    assert_eq!(rpc.pending_count().await, 0);
+
+    // The late answer to "slow" arrives first and must not be taken for this one.
+    let echo = rpc
+        .request("ping", json!({ "n": 1 }), RECV_TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(echo, json!({ "method": "ping", "params": { "n": 1 } }));
+    rpc.shutdown().await;
+}
+
+#[tokio::test]
+async fn errors_and_garbage_are_reported() {
+    let (rpc, mut rx, _dir) = start(
+        "errors",
+        json!({
+            "methods": {
+                "boom": [{ "error": { "code": -32000, "message": "boom" } }],
+                "garbage": [{ "raw": "not json" }]
+            }
+        }),
+    );
+
+    let err = rpc
+        .request("boom", json!({}), RECV_TIMEOUT)
+        .await
+        .unwrap_err();
+    assert_eq!(err, "rpc error -32000: boom");
+
+    rpc.request("garbage", json!({}), RECV_TIMEOUT)
+        .await
+        .unwrap();
+    assert!(matches!(recv(&mut rx).await, Incoming::ParseError(_)));
+    rpc.shutdown().await;
+}
+
+#[tokio::test]
+async fn approval_requests_round_trip() {
+    let approval_params = json!({ "threadId": "th1", "itemId": "i1", "command": "ls" });
+    let (rpc, mut rx, _dir) = start(
+        "approval",
+        json!({
+            "methods": {
+                "turn/start": [
+                    {
+                        "request": {
+                            "method": "item/commandExecution/requestApproval",
+                            "params": approval_params
+                        }
+                    },
+                    { "respond": { "turn": { "id": "t1" } } }
+                ]
+            }
+        }),
+    );
+
+    let turn = rpc
+        .request("turn/start", json!({ "threadId": "th1" }), RECV_TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(turn["turn"]["id"], "t1");
+
+    let Incoming::Request { id, method, params } = recv(&mut rx).await else {
+        panic!("expected an approval request");
+    };
+    assert_eq!(method, "item/commandExecution/requestApproval");
+    assert_eq!(params, approval_params);
+
+    rpc.respond(id, json!({ "decision": "accept" }))
+        .await
+        .unwrap();
+    let Incoming::Notification { method, params } = recv(&mut rx).await else {
+        panic!("expected the server to acknowledge the response");
+    };
+    assert_eq!(method, "fake/response");
+    assert_eq!(params["id"], id);
+    assert_eq!(params["result"]["decision"], "accept");
+    rpc.shutdown().await;
+}
+
+#[tokio::test]
+async fn shutdown_fails_pending_requests() {
+    let (rpc, _rx, _dir) = start("shutdown", json!({ "methods": { "hang": ["noResponse"] } }));
+
+    let pending = {
+        let rpc = rpc.clone();
+        tokio::spawn(async move { rpc.request("hang", json!({}), RECV_TIMEOUT).await })
+    };
+    while rpc.pending_count().await == 0 {
+        sleep(Duration::from_millis(5)).await;
+    }
+
+    rpc.shutdown().await;
+    assert_eq!(pending.await.unwrap().unwrap_err(), "engine stopped");
+    assert!(rpc.has_exited().await);
+}
+
+#[tokio::test]
+async fn cancel_fails_the_request_and_notifies_the_server() {
+    let (rpc, mut rx, _dir) = start(
+        "cancel",
+        json!({
+            "methods": {
+                "research": ["noResponse"],
+                "$/cancelRequest": [{ "notify": { "method": "fake/cancelled", "params": {} } }]
+            }
+        }),
+    );
+
+    let id = rpc.next_request_id();
+    let pending = {
+        let rpc = rpc.clone();
+        tokio::spawn(async move {
+            rpc.request_with_id(id, "research", json!({}), RECV_TIMEOUT)
+                .await
+        })
+    };
+    while rpc.pending_count().await == 0 {
+        sleep(Duration::from_millis(5)).await;
+    }
+
+    assert!(rpc.cancel(id).await.unwrap());
+    assert_eq!(pending.await.unwrap().unwrap_err(), "request cancelled");
+    let Incoming::Notification { method, .. } = recv(&mut rx).await else {
+        panic!("expected the server to see the cancellation");
+    };
+    assert_eq!(method, "fake/cancelled");
+    assert!(!rpc.cancel(id).await.unwrap());
+    rpc.shutdown().await;
+}
+
+#[tokio::test]
+async fn crash_fails_pending_requests_and_disconnects() {
+    let (rpc, mut rx, _dir) = start(
+        "crash",
+        json!({ "methods": { "crash": [{ "stderr": "panic: boom" }, { "exit": 3 }] } }),
+    );
+
+    let err = rpc
+        .request("crash", json!({}), RECV_TIMEOUT)
+        .await
+        .unwrap_err();
+    assert_eq!(err, "engine output stream closed");
+
+    let mut seen = Vec::new();
+    while let Some(message) = timeout(RECV_TIMEOUT, rx.recv())
+        .await
+        .expect("timed out waiting for app-server")
+    {
+        seen.push(message);
+    }
+    assert!(seen.contains(&Incoming::Stderr("panic: boom".to_string())));
+    assert!(seen.contains(&Incoming::Disconnected));
+    // Pipes close slightly before the process can be reaped.
+    timeout(RECV_TIMEOUT, async {
+        while !rpc.has_exited().await {
+            sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("fake app-server did not exit");
+}
+
+// Engine events seen by a mock app; ones a test has not asked for yet are kept.
+struct Events {
+    rx: UnboundedReceiver<(String, Value)>,
+    seen: Vec<(String, Value)>,
+}
+
+impl Events {
+    async fn expect(&mut self, event: &str, matches: impl Fn(&Value) -> bool) -> Value {
+        if let Some(index) = self
+            .seen
+            .iter()
+            .position(|(name, payload)| name == event && matches(payload))
+        {
+            return self.seen.remove(index).1;
+        }
+        loop {
+            let (name, payload) = timeout(RECV_TIMEOUT, self.rx.recv())
+                .await
+                .unwrap_or_else(|_| panic!("timed out waiting for {event}"))
+                .expect("event channel closed");
+            if name == event && matches(&payload) {
+                return payload;
+            }
+            self.seen.push((name, payload));
+        }
+    }
+
+    fn saw(&mut self, event: &str) -> bool {
+        while let Ok(received) = self.rx.try_recv() {
+            self.seen.push(received);
+        }
+        self.seen.iter().any(|(name, _)| name == event)
+    }
+}
+
+// A mock app whose data dir, CODEX_HOME and workspace live in `dir`, with
+// RAIL_CODEX_BIN pointing at the fake app-server.
+fn runtime_app(dir: &TempDir, script: &Value) -> (App<MockRuntime>, Events) {
+    let fake_app_server = env!("CARGO_BIN_EXE_fake_app_server");
+    env::set_var("RAIL_CODEX_BIN", fake_app_server);
+    env::set_var("RAIL_NODE_BIN", fake_app_server);
+    env::set_var("RAIL_CODEX_HOME", dir.0.join("codex-home"));
+    env::set_var("RAIL_FAKE_APP_SERVER_SCRIPT", dir.write_script(script));
+    env::set_var("XDG_DATA_HOME", dir.0.join("data"));
+    env::set_var("HOME", &dir.0);
+    fs::create_dir_all(dir.0.join("workspace")).expect("create workspace");
+
+    let app = mock_app();
+    app.manage(EngineManager::default());
+    let (tx, rx) = mpsc::unbounded_channel();
+    for event in [EVENT_NOTIFICATION, EVENT_LIFECYCLE, EVENT_APPROVAL_REQUEST] {
+        let tx = tx.clone();
+        app.listen_any(event, move |emitted| {
+            let payload = serde_json::from_str(emitted.payload()).unwrap_or(Value::Null);
+            let _ = tx.send((event.to_string(), payload));
+        });
+    }
+    (
+        app,
+        Events {
+            rx,
+            seen: Vec::new(),
+        },
+    )
+}
+
+async fn start_in(
+    app: &App<MockRuntime>,
+    dir: &TempDir,
+) -> Result<TestRuntime<MockRuntime>, String> {
+    let workspace = dir.0.join("workspace");
+    start_runtime(app.handle(), "test", &workspace.to_string_lossy()).await
+}
+
+fn approval_script(command: &str) -> Value {
+    json!({
+        "methods": {
+            "turn/start": [
+                {
+                    "request": {
+                        "method": "item/commandExecution/requestApproval",
+                        "params": { "threadId": "th1", "itemId": "i1", "command": command }
+                    }
+                },
+                { "respond": { "turn": { "id": "t1" } } }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn runtime_start_completes_the_handshake() {
+    let _env = RUNTIME_ENV.lock().await;
+    let dir = TempDir::new("runtime_handshake");
+    let (app, mut events) = runtime_app(
+        &dir,
+        &json!({
+            "methods": {
+                "initialized": [{ "notify": { "method": "fake/ready", "params": {} } }]
+            }
+        }),
+    );
+
+    let runtime = start_in(&app, &dir).await.unwrap();
+    events
+        .expect(EVENT_LIFECYCLE, |payload| payload["state"] == "starting")
+        .await;
+    events
+        .expect(EVENT_LIFECYCLE, |payload| payload["state"] == "ready")
+        .await;
+    // The server only hears "initialized" after answering "initialize".
+    events
+        .expect(EVENT_NOTIFICATION, |payload| {
+            payload["method"] == "fake/ready"
+        })
+        .await;
+    let echo = runtime.request("ping", json!({ "n": 1 })).await.unwrap();
+    assert_eq!(echo, json!({ "method": "ping", "params": { "n": 1 } }));
+    runtime.stop().await;
+}
+
+#[tokio::test]
+async fn runtime_start_fails_when_initialize_is_refused() {
+    let _env = RUNTIME_ENV.lock().await;
+    let dir = TempDir::new("runtime_refused");
+    let (app, mut events) = runtime_app(
+        &dir,
+        &json!({
+            "methods": {
+                "initialize": [{ "error": { "code": -32600, "message": "unsupported client" } }]
+            }
+        }),
+    );
+
+    let Err(err) = start_in(&app, &dir).await else {
+        panic!("expected the handshake to fail");
+    };
+    assert_eq!(err, "rpc error -32600: unsupported client");
+    events
+        .expect(EVENT_LIFECYCLE, |payload| payload["state"] == "stopped")
+        .await;
+    assert!(!events.saw(EVENT_APPROVAL_REQUEST));
+}
+
+#[tokio::test]
+async fn runtime_routes_approval_requests_to_the_user() {
+    let _env = RUNTIME_ENV.lock().await;
+    let dir = TempDir::new("runtime_approval");
+    let (app, mut events) = runtime_app(&dir, &approval_script("rm -rf build"));
+
+    let runtime = start_in(&app, &dir).await.unwrap();
+    runtime
+        .request("turn/start", json!({ "threadId": "th1" }))
+        .await
+        .unwrap();
+    let request = events
+        .expect(EVENT_APPROVAL_REQUEST, |payload| {
+            payload["method"] == "item/commandExecution/requestApproval"
+        })
+        .await;
+    assert_eq!(request["params"]["command"], "rm -rf build");
+    let request_id = request["requestId"].as_u64().unwrap();
+    assert_eq!(runtime.pending_approvals().await, vec![request_id]);
+
+    runtime
+        .respond(request_id, json!({ "decision": "decline" }))
+        .await
+        .unwrap();
+    let ack = events
+        .expect(EVENT_NOTIFICATION, |payload| {
+            payload["method"] == "fake/response"
+        })
+        .await;
+    assert_eq!(ack["params"]["id"], request_id);
+    assert_eq!(ack["params"]["result"]["decision"], "decline");
+    assert!(runtime.pending_approvals().await.is_empty());
+    runtime.stop().await;
+}
+
+#[tokio::test]
+async fn runtime_auto_approves_requests_covered_by_a_rule() {
+    let _env = RUNTIME_ENV.lock().await;
+    let dir = TempDir::new("runtime_rules");
+    let (app, mut events) = runtime_app(&dir, &approval_script("ls -la"));
+    save_approval_rules(
+        app.handle(),
+        json!({ "enabled": true, "commandAllow": [{ "prefix": "ls" }] }),
+    )
+    .unwrap();
+
+    let runtime = start_in(&app, &dir).await.unwrap();
+    runtime
+        .request("turn/start", json!({ "threadId": "th1" }))
+        .await
+        .unwrap();
+    let resolved = events
+        .expect(EVENT_NOTIFICATION, |payload| {
+            payload["method"] == "engine/approvalAutoResolved"
+        })
+        .await;
+    assert_eq!(resolved["params"]["decision"], "accept");
+    assert_eq!(resolved["params"]["summary"], "ls -la");
+    let ack = events
+        .expect(EVENT_NOTIFICATION, |payload| {
+            payload["method"] == "fake/response"
+        })
+        .await;
+    assert_eq!(ack["params"]["result"]["decision"], "accept");
+    assert!(!events.saw(EVENT_APPROVAL_REQUEST));
+    assert!(runtime.pending_approvals().await.is_empty());
+    runtime.stop().await;
+}
@@ -235,6 +235,7 @@ import {
   WEB_TURN_FLOATING_MARGIN,
   WEB_TURN_FLOATING_MIN_VISIBLE_HEIGHT,
   WEB_TURN_FLOATING_MIN_VISIBLE_WIDTH,
+  type TurnUsageScope,
 } from "./main";
 import WorkflowCanvasPane from "./main/presentation/WorkflowCanvasPane";
 import WorkflowInspectorPane from "./main/presentation/WorkflowInspectorPane";
@@ -854,11 +855,11 @@ function App() {
     webTurnRunHandlers.clearDetachedWebTurnResolver(reason);
   }
 
-  async function executeTurnNode(node: GraphNode, input: unknown) {
+  async function executeTurnNode(node: GraphNode, input: unknown, usageScope?: TurnUsageScope) {
     if (!webTurnRunHandlers) {
       return { ok: false, error: "턴 실행 핸들러가 초기화되지 않았습니다." };
     }
-    return webTurnRunHandlers.executeTurnNode(node, input);
+    return webTurnRunHandlers.executeTurnNode(node, input, usageScope);
   }
 
   async function saveRunRecord(runRecord: RunRecord) {
@@ -1123,6 +1124,9 @@ function App() {
   });
 
   useMainAppRuntimeEffects({
+    hasTauriRuntime,
+    cwd,
+    legacyCwd: defaultCwd,
     webBridgeStageWarnTimerRef,
     reportSoftError,
     refreshGraphFiles,
@@ -1647,6 +1651,7 @@ function App() {
     collectingRunRef,
     createRunNodeStateSnapshot,
     graph: graphForCanvas,
+    graphFileName,
     runLogCollectorRef,
     setNodeStates,
     createRunRecord: (params: Parameters<typeof createRunRecord>[0]) =>
@@ -7,12 +7,14 @@ import {
   buildDashboardFallbackSnapshot,
 } from "../../features/dashboard/intelligence";
 import {
+  loadDashboardRawPaths,
   loadDashboardSnapshots,
   runDashboardCrawlerOnly,
   runDashboardTopicIntelligence,
   type RunDashboardTopicResult,
 } from "../main/runtime/dashboardIntelligenceRunner";
 import type { DashboardTopicRunState } from "../../features/dashboard/intelligence";
+import { subscribeStorageChanges } from "../main/runtime/storageChanges";
 
 type InvokeFn = <T>(command: string, args?: Record<string, unknown>) => Promise<T>;
 
@@ -64,9 +66,42 @@ export function useDashboardIntelligenceRunner(params: UseDashboardIntelligenceR
     }
   }, [cwd, hasTauriRuntime, invokeFn, setError]);
 
+  const [rawPathsByTopic, setRawPathsByTopic] = useState<Partial<Record<DashboardTopicId, string[]>>>({});
+
+  const refreshRawPaths = useCallback(async () => {
+    if (!hasTauriRuntime) {
+      return;
+    }
+    if (!String(cwd ?? "").trim()) {
+      return;
+    }
+    try {
+      setRawPathsByTopic(await loadDashboardRawPaths({ cwd, invokeFn }));
+    } catch (error) {
+      setError(`대시보드 원본 목록 로드 실패: ${String(error)}`);
+    }
+  }, [cwd, hasTauriRuntime, invokeFn, setError]);
+
   useEffect(() => {
     void refreshSnapshots();
-  }, [refreshSnapshots]);
+    void refreshRawPaths();
+  }, [refreshSnapshots, refreshRawPaths]);
+
+  // Snapshots and raw files written outside this window (crawler runs, other
+  // instances, manual edits) arrive through the storage watcher.
+  useEffect(() => {
+    if (!hasTauriRuntime) {
+      return;
+    }
+    return subscribeStorageChanges((batch) => {
+      if (batch.refresh.includes("dashboardSnapshotList")) {
+        void refreshSnapshots();
+      }
+      if (batch.refresh.includes("dashboardRawList")) {
+        void refreshRawPaths();
+      }
+    });
+  }, [hasTauriRuntime, refreshSnapshots, refreshRawPaths]);
 
   const runTopic = useCallback(
     async (
@@ -229,6 +264,8 @@ export function useDashboardIntelligenceRunner(params: UseDashboardIntelligenceR
     snapshotsByTopic,
     setSnapshotsByTopic,
     refreshSnapshots,
+    rawPathsByTopic,
+    refreshRawPaths,
     runTopic,
     runAll,
     runCrawlerOnly,
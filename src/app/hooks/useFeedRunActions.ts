@@ -2,6 +2,7 @@ import { useCallback } from "react";
 import type { Dispatch, MutableRefObject, SetStateAction } from "react";
 import { invoke } from "../../shared/tauri";
 import { buildFeedShareText } from "../main";
+import { loadFullFeedAttachments } from "../main/runtime/feedArtifacts";
 import { hideFeedRunId } from "../main/runtime/feedHiddenRuns";
 import type { FeedViewPost, RunGroupKind, RunRecord } from "../main";
 import type { PresetKind } from "../../features/workflow/domain";
@@ -55,9 +56,10 @@ export function useFeedRunActions(params: UseFeedRunActionsParams) {
   } = params;
 
   const onShareFeedPost = useCallback(
-    async (post: FeedViewPost, mode: "clipboard" | "json") => {
+    async (listedPost: FeedViewPost, mode: "clipboard" | "json") => {
       setError("");
       setFeedShareMenuPostId(null);
+      const post = await loadFullFeedAttachments(invoke, listedPost);
       const run = await ensureFeedRunRecord(post.sourceFile);
       const shareText = buildFeedShareText(post, run);
       try {
@@ -1,4 +1,6 @@
-import { useRef, useState } from "react";
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "../../shared/tauri";
+import { hasFeedAttachmentPreviews, loadFullFeedAttachments } from "../main/runtime/feedArtifacts";
 import type { PresetKind } from "../../features/workflow/domain";
 import type { GraphNode } from "../../features/workflow/types";
 import type {
@@ -49,6 +51,27 @@ export function useFeedState() {
   const pendingNodeRequestsRef = useRef<Record<string, string[]>>({});
   const agentRulesCacheRef = useRef<Record<string, { loadedAt: number; docs: AgentRuleDoc[] }>>({});
   const feedReplyFeedbackClearTimerRef = useRef<Record<string, number>>({});
+  const feedArtifactRequestedRef = useRef(new WeakSet<FeedViewPost>());
+
+  // An expanded card shows the whole document, so its attachment previews are
+  // swapped for the stored artifacts. Each loaded post object is tried once.
+  useEffect(() => {
+    const targets = feedPosts.filter(
+      (post) =>
+        feedExpandedByPost[post.id] === true &&
+        hasFeedAttachmentPreviews(post) &&
+        !feedArtifactRequestedRef.current.has(post),
+    );
+    for (const post of targets) {
+      feedArtifactRequestedRef.current.add(post);
+      void loadFullFeedAttachments(invoke, post).then((fullPost) => {
+        if (fullPost === post) {
+          return;
+        }
+        setFeedPosts((prev) => prev.map((row) => (row === post ? fullPost : row)));
+      });
+    }
+  }, [feedExpandedByPost, feedPosts]);
 
   return {
     feedPosts,
@@ -377,6 +377,11 @@ describe("dashboard intelligence runner", () => {
     const turnStartArgs = (turnStartCall?.[1] ?? {}) as { text?: string };
     expect(turnStartArgs.text ?? "").toContain("[Additional User Request]");
     expect(turnStartArgs.text ?? "").toContain("변동성 항목을 더 강조해줘");
+    expect((turnStartCall?.[1] as Record<string, unknown>)?.usageScope).toEqual({
+      graphId: "dashboard",
+      runId: undefined,
+      nodeId: "marketSummary",
+    });
   });
 
   it("emits progress updates while running topic intelligence", async () => {
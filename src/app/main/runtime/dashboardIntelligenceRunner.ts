@@ -7,7 +7,7 @@ import {
   type DashboardTopicId,
   type DashboardTopicSnapshot,
 } from "../../../features/dashboard/intelligence";
-import type { KnowledgeRetrieveResult, ThreadStartResult } from "../types";
+import type { KnowledgeRetrieveResult, ThreadStartResult, TurnUsageScope } from "../types";
 
 type InvokeFn = <T>(command: string, args?: Record<string, unknown>) => Promise<T>;
 
@@ -84,11 +84,13 @@ async function startDashboardTurn(params: {
   invokeFn: InvokeFn;
   threadId: string;
   text: string;
+  usageScope: TurnUsageScope;
 }): Promise<unknown> {
   try {
     return await params.invokeFn<unknown>("turn_start_blocking", {
       threadId: params.threadId,
       text: params.text,
+      usageScope: params.usageScope,
     });
   } catch (error) {
     const message = String(error ?? "").toLowerCase();
@@ -98,6 +100,7 @@ async function startDashboardTurn(params: {
     return params.invokeFn<unknown>("turn_start", {
       threadId: params.threadId,
       text: params.text,
+      usageScope: params.usageScope,
     });
   }
 }
@@ -659,6 +662,12 @@ export async function runDashboardTopicIntelligence(params: RunDashboardTopicPar
 
   let snapshot: DashboardTopicSnapshot;
   let codexResponseState: DashboardRunQualityGate["codexResponseState"] = "ok";
+  // Dashboard turns are ledgered under one pseudo-graph, one node per topic.
+  const usageScope: TurnUsageScope = {
+    graphId: "dashboard",
+    runId: String(params.runId ?? "").trim() || undefined,
+    nodeId: params.topic,
+  };
   try {
     const threadStart = await startCodexThreadOrThrow(params);
     emitProgress(params, "codex_turn", "Codex 응답 생성 중");
@@ -666,6 +675,7 @@ export async function runDashboardTopicIntelligence(params: RunDashboardTopicPar
       invokeFn: params.invokeFn,
       threadId: threadStart.threadId,
       text: prompt,
+      usageScope,
     });
     let responseText = resolveResponseText(turnStartResponse);
     let responseSnapshot = buildSnapshotFromRawObject({
@@ -678,6 +688,7 @@ export async function runDashboardTopicIntelligence(params: RunDashboardTopicPar
       const retryResponse = await startDashboardTurn({
         invokeFn: params.invokeFn,
         threadId: threadStart.threadId,
+        usageScope,
         text: buildEmergencyCodexRetryPrompt({
           topic: params.topic,
           model: params.config.model,
@@ -833,3 +844,19 @@ export async function loadDashboardSnapshots(params: {
   }
   return out;
 }
+
+export async function loadDashboardRawPaths(params: {
+  cwd: string;
+  invokeFn: InvokeFn;
+}): Promise<Partial<Record<DashboardTopicId, string[]>>> {
+  const out: Partial<Record<DashboardTopicId, string[]>> = {};
+  for (const topic of DASHBOARD_TOPIC_IDS) {
+    // eslint-disable-next-line no-await-in-loop
+    const rows = await params.invokeFn<unknown[]>("dashboard_raw_list", { cwd: params.cwd, topic });
+    const paths = Array.isArray(rows) ? rows.map((row) => String(row ?? "").trim()).filter(Boolean) : [];
+    if (paths.length > 0) {
+      out[topic] = paths;
+    }
+  }
+  return out;
+}
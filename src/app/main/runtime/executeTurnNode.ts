@@ -39,6 +39,7 @@ import type {
   InternalMemoryTraceEntry,
   KnowledgeTraceEntry,
   ThreadStartResult,
+  TurnUsageScope,
   UsageStats,
   WebProviderRunResult,
 } from "../types";
@@ -104,6 +105,7 @@ export type ExecuteTurnNodeContext = {
   invokeFn: InvokeFn;
   openUrlFn: (url: string) => Promise<void>;
   t: (key: string) => string;
+  usageScope?: TurnUsageScope;
 };
 export async function executeTurnNodeWithContext(
   node: GraphNode,
@@ -430,7 +432,11 @@ export async function executeTurnNodeWithContext(
   });
   let turnStartResponse: unknown;
   try {
-    turnStartResponse = await ctx.invokeFn<unknown>("turn_start", { threadId: activeThreadId, text: textToSend });
+    turnStartResponse = await ctx.invokeFn<unknown>("turn_start", {
+      threadId: activeThreadId,
+      text: textToSend,
+      usageScope: ctx.usageScope,
+    });
   } catch (error) {
     if (ctx.turnTerminalResolverRef.current) {
       ctx.turnTerminalResolverRef.current = null;
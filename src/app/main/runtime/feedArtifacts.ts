@@ -0,0 +1,38 @@
+import type { FeedAttachment, FeedViewPost } from "../types";
+
+type InvokeFn = <T>(command: string, args?: Record<string, unknown>) => Promise<T>;
+
+function isArtifactPreview(attachment: FeedAttachment | null | undefined): boolean {
+  return Boolean(attachment?.contentTruncated && String(attachment.artifactId ?? "").trim());
+}
+
+export function hasFeedAttachmentPreviews(post: FeedViewPost | null | undefined): boolean {
+  return Array.isArray(post?.attachments) && post.attachments.some(isArtifactPreview);
+}
+
+// Runs are loaded with previews of oversized attachments; the full body is a
+// run artifact. Attachments that cannot be read keep their preview.
+export async function loadFullFeedAttachments(invokeFn: InvokeFn, post: FeedViewPost): Promise<FeedViewPost> {
+  const sourceFile = String(post.sourceFile ?? "").trim();
+  if (!sourceFile || !hasFeedAttachmentPreviews(post)) {
+    return post;
+  }
+  const attachments = await Promise.all(
+    post.attachments.map(async (attachment) => {
+      if (!isArtifactPreview(attachment)) {
+        return attachment;
+      }
+      try {
+        const bytes = await invokeFn<ArrayBuffer>("run_artifact_read", {
+          name: sourceFile,
+          id: attachment.artifactId,
+        });
+        const content = new TextDecoder().decode(bytes);
+        return { ...attachment, content, contentTruncated: false };
+      } catch {
+        return attachment;
+      }
+    }),
+  );
+  return { ...post, attachments };
+}
@@ -29,7 +29,7 @@ export async function ensureFeedRunRecordFromCache(params: {
     return cached;
   }
   try {
-    const loaded = await params.invokeFn<RunRecord>("run_load", { name: target });
+    const loaded = await params.invokeFn<RunRecord>("run_load", { name: target, inlineArtifacts: true });
     const normalized = params.normalizeRunRecordFn(loaded);
     params.feedRunCacheRef.current[target] = normalized;
     return normalized;
@@ -1,5 +1,7 @@
-import { openPath, revealItemInDir } from "../../../shared/tauri";
+import { revealItemInDir } from "../../../shared/tauri";
+import { toErrorText } from "../../mainAppUtils";
 import type { KnowledgeFileRef } from "../../../features/workflow/types";
+import { createOpenFeedMarkdownFile } from "./feedMarkdownFile";
 import { isFeedRunIdHidden, readHiddenFeedRunIds } from "./feedHiddenRuns";
 import type { FeedViewPost, RunRecord } from "../types";
 
@@ -198,7 +200,7 @@ export function createFeedKnowledgeHandlers(params: any) {
       const files = (await params.invokeFn("graph_list")) as string[];
       params.setGraphFiles(files);
     } catch (e) {
-      params.setError(String(e));
+      params.setError(toErrorText(e));
     }
   }
 
@@ -279,7 +281,7 @@ export function createFeedKnowledgeHandlers(params: any) {
       params.feedRunCacheRef.current = nextCache;
       params.setFeedPosts(nextPosts);
     } catch (e) {
-      params.setError(`피드 로드 실패: ${String(e)}`);
+      params.setError(`피드 로드 실패: ${toErrorText(e)}`);
     } finally {
       params.setFeedLoading(false);
     }
@@ -296,73 +298,7 @@ export function createFeedKnowledgeHandlers(params: any) {
     }
   }
 
-  async function onOpenFeedMarkdownFile(post: FeedViewPost) {
-    params.setError("");
-    const attachments = Array.isArray(post.attachments) ? post.attachments : [];
-    const markdownAttachment = attachments.find((attachment) => attachment.kind === "markdown");
-    let filePath = String(markdownAttachment?.filePath ?? "").trim();
-    let materializedFilePath = "";
-    if (!filePath) {
-      const markdownRawKey = String(
-        post?.rawAttachmentRef?.markdownKey ??
-          params.feedAttachmentRawKeyFn?.(String(post?.id ?? "").trim(), "markdown") ??
-          "",
-      ).trim();
-      const rawMarkdown =
-        markdownRawKey && params.feedRawAttachmentRef?.current
-          ? String(params.feedRawAttachmentRef.current[markdownRawKey] ?? "").trim()
-          : "";
-      const fallbackMarkdown = rawMarkdown || String(markdownAttachment?.content ?? "").trim();
-      const normalizedCwd = String(params.cwd ?? "").trim();
-      if (!fallbackMarkdown || !normalizedCwd || !params.hasTauriRuntime) {
-        params.setError("문서 파일 경로를 찾지 못했습니다.");
-        return;
-      }
-      const normalizedRunId = String(post?.runId ?? "").trim() || "feed";
-      const normalizedPostId =
-        String(post?.id ?? "post")
-          .trim()
-          .replace(/[^a-zA-Z0-9._-]+/g, "_")
-          .slice(0, 64) || "post";
-      try {
-        const runDir = `${normalizedCwd.replace(/[\\/]+$/, "")}/.rail/runs/${normalizedRunId}`;
-        filePath = await params.invokeFn("workspace_write_text", {
-          cwd: runDir,
-          name: `feed_${normalizedPostId}.md`,
-          content: fallbackMarkdown,
-        });
-        materializedFilePath = String(filePath ?? "").trim();
-      } catch (error) {
-        params.setError(`문서 파일 생성 실패: ${String(error)}`);
-        return;
-      }
-    }
-    if (materializedFilePath) {
-      params.setFeedPosts((prev: FeedViewPost[]) =>
-        (Array.isArray(prev) ? prev : []).map((row) => {
-          if (row.id !== post.id) {
-            return row;
-          }
-          const attachments = Array.isArray(row.attachments) ? row.attachments : [];
-          const nextAttachments = attachments.map((attachment) =>
-            attachment.kind === "markdown" ? { ...attachment, filePath: materializedFilePath } : attachment,
-          );
-          return { ...row, attachments: nextAttachments };
-        }),
-      );
-    }
-    try {
-      await openPath(filePath);
-      params.setStatus("문서 파일 열림");
-    } catch (error) {
-      try {
-        await revealItemInDir(filePath);
-        params.setStatus("문서 파일 위치 열림");
-      } catch {
-        params.setError(`문서 파일 열기 실패: ${String(error)}`);
-      }
-    }
-  }
+  const onOpenFeedMarkdownFile = createOpenFeedMarkdownFile(params);
 
   async function ensureFeedRunRecord(sourceFile: string): Promise<RunRecord | null> {
     return params.ensureFeedRunRecordFromCacheFn({
@@ -446,7 +382,7 @@ export function createFeedKnowledgeHandlers(params: any) {
       });
       params.setStatus(`첨부 자료 ${uniquePaths.length}개 추가됨`);
     } catch (error) {
-      params.setError(`첨부 자료 추가 실패: ${String(error)}`);
+      params.setError(`첨부 자료 추가 실패: ${toErrorText(error)}`);
     }
   }
 
@@ -458,7 +394,7 @@ export function createFeedKnowledgeHandlers(params: any) {
       }
       await attachKnowledgeFiles(selectedPaths);
     } catch (error) {
-      params.setError(`첨부 파일 선택 실패: ${String(error)}`);
+      params.setError(`첨부 파일 선택 실패: ${toErrorText(error)}`);
     }
   }
 
@@ -0,0 +1,75 @@
+import { openPath, revealItemInDir } from "../../../shared/tauri";
+import { toErrorText } from "../../mainAppUtils";
+import type { FeedViewPost } from "../types";
+import { loadFullFeedAttachments } from "./feedArtifacts";
+
+export function createOpenFeedMarkdownFile(params: any) {
+  return async function onOpenFeedMarkdownFile(listedPost: FeedViewPost) {
+    params.setError("");
+    const post = params.hasTauriRuntime ? await loadFullFeedAttachments(params.invokeFn, listedPost) : listedPost;
+    const attachments = Array.isArray(post.attachments) ? post.attachments : [];
+    const markdownAttachment = attachments.find((attachment) => attachment.kind === "markdown");
+    let filePath = String(markdownAttachment?.filePath ?? "").trim();
+    let materializedFilePath = "";
+    if (!filePath) {
+      const markdownRawKey = String(
+        post?.rawAttachmentRef?.markdownKey ??
+          params.feedAttachmentRawKeyFn?.(String(post?.id ?? "").trim(), "markdown") ??
+          "",
+      ).trim();
+      const rawMarkdown =
+        markdownRawKey && params.feedRawAttachmentRef?.current
+          ? String(params.feedRawAttachmentRef.current[markdownRawKey] ?? "").trim()
+          : "";
+      const fallbackMarkdown = rawMarkdown || String(markdownAttachment?.content ?? "").trim();
+      const normalizedCwd = String(params.cwd ?? "").trim();
+      if (!fallbackMarkdown || !normalizedCwd || !params.hasTauriRuntime) {
+        params.setError("문서 파일 경로를 찾지 못했습니다.");
+        return;
+      }
+      const normalizedRunId = String(post?.runId ?? "").trim() || "feed";
+      const normalizedPostId =
+        String(post?.id ?? "post")
+          .trim()
+          .replace(/[^a-zA-Z0-9._-]+/g, "_")
+          .slice(0, 64) || "post";
+      try {
+        const runDir = `${normalizedCwd.replace(/[\\/]+$/, "")}/.rail/runs/${normalizedRunId}`;
+        filePath = await params.invokeFn("workspace_write_text", {
+          cwd: runDir,
+          name: `feed_${normalizedPostId}.md`,
+          content: fallbackMarkdown,
+        });
+        materializedFilePath = String(filePath ?? "").trim();
+      } catch (error) {
+        params.setError(`문서 파일 생성 실패: ${toErrorText(error)}`);
+        return;
+      }
+    }
+    if (materializedFilePath) {
+      params.setFeedPosts((prev: FeedViewPost[]) =>
+        (Array.isArray(prev) ? prev : []).map((row) => {
+          if (row.id !== post.id) {
+            return row;
+          }
+          const attachments = Array.isArray(row.attachments) ? row.attachments : [];
+          const nextAttachments = attachments.map((attachment) =>
+            attachment.kind === "markdown" ? { ...attachment, filePath: materializedFilePath } : attachment,
+          );
+          return { ...row, attachments: nextAttachments };
+        }),
+      );
+    }
+    try {
+      await openPath(filePath);
+      params.setStatus("문서 파일 열림");
+    } catch (error) {
+      try {
+        await revealItemInDir(filePath);
+        params.setStatus("문서 파일 위치 열림");
+      } catch {
+        params.setError(`문서 파일 열기 실패: ${toErrorText(error)}`);
+      }
+    }
+  };
+}
@@ -204,7 +204,12 @@ export function createRunGraphRunner(params: any) {
         setNodeRuntimeFields: params.setNodeRuntimeFields,
         t: params.t,
         executeTurnNodeWithOutputSchemaRetry: params.executeTurnNodeWithOutputSchemaRetry,
-        executeTurnNode: params.executeTurnNode,
+        executeTurnNode: (node: any, input: unknown) =>
+          params.executeTurnNode(node, input, {
+            graphId: params.graphFileName || undefined,
+            runId: runRecord.runId,
+            nodeId: node.id,
+          }),
         addNodeLog: params.addNodeLog,
         validateSimpleSchema: params.validateSimpleSchema,
         turnOutputSchemaEnabled: params.turnOutputSchemaEnabled,
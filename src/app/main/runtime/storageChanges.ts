@@ -0,0 +1,49 @@
+import { listen } from "../../../shared/tauri";
+
+export const STORAGE_CHANGED_EVENT = "storage://changed";
+
+export type StorageChangeRefresh =
+  | "graphList"
+  | "runList"
+  | "dashboardSnapshotList"
+  | "dashboardRawList"
+  | "workspace";
+
+export type StorageChangeBatch = {
+  changes: Array<{
+    scope: string;
+    kind: "created" | "modified" | "deleted";
+    path: string;
+    name: string;
+  }>;
+  refresh: StorageChangeRefresh[];
+};
+
+// Subscribes to watcher batches. The returned cleanup also covers a listener
+// that is still registering when the caller unmounts.
+export function subscribeStorageChanges(onBatch: (batch: StorageChangeBatch) => void): () => void {
+  let cancelled = false;
+  let unlisten: null | (() => Promise<void>) = null;
+  void listen(STORAGE_CHANGED_EVENT, (event) => {
+    if (cancelled) {
+      return;
+    }
+    const payload = event.payload as Partial<StorageChangeBatch> | null;
+    onBatch({
+      changes: Array.isArray(payload?.changes) ? payload.changes : [],
+      refresh: Array.isArray(payload?.refresh) ? payload.refresh : [],
+    });
+  })
+    .then((off) => {
+      if (cancelled) {
+        void off();
+        return;
+      }
+      unlisten = off;
+    })
+    .catch(() => undefined);
+  return () => {
+    cancelled = true;
+    void unlisten?.();
+  };
+}
@@ -1,4 +1,5 @@
-import { useEffect } from "react";
+import { useEffect, useRef } from "react";
+import { subscribeStorageChanges } from "./storageChanges";
 
 export function useMainAppRuntimeEffects(params: any) {
   useEffect(() => {
@@ -26,11 +27,48 @@ export function useMainAppRuntimeEffects(params: any) {
     };
   }, []);
 
+  const legacyCwdAdoptionRef = useRef<Promise<unknown>>(Promise.resolve());
   useEffect(() => {
-    params.refreshGraphFiles();
-    params.refreshFeedTimeline();
+    // Workspace commands need a registered root; carry over the cwd saved before roots existed.
+    const adoptLegacyCwd =
+      params.hasTauriRuntime && params.legacyCwd
+        ? params.invokeFn("workspace_root_adopt_legacy", { path: params.legacyCwd }).catch(() => undefined)
+        : Promise.resolve();
+    legacyCwdAdoptionRef.current = adoptLegacyCwd;
+    void adoptLegacyCwd.finally(() => {
+      params.refreshGraphFiles();
+      params.refreshFeedTimeline();
+    });
   }, []);
 
+  useEffect(() => {
+    // Re-point the storage watcher at the active workspace's `.rail` tree.
+    // Without a registered root the previous watch stays in place.
+    if (!params.hasTauriRuntime) {
+      return;
+    }
+    const cwd = String(params.cwd ?? "").trim();
+    void legacyCwdAdoptionRef.current
+      .then(() => params.invokeFn("storage_watch_start", cwd ? { cwd } : {}))
+      .catch(() => undefined);
+  }, [params.cwd, params.hasTauriRuntime]);
+
+  const storageRefreshRef = useRef(params);
+  storageRefreshRef.current = params;
+  useEffect(() => {
+    if (!params.hasTauriRuntime) {
+      return;
+    }
+    return subscribeStorageChanges((batch) => {
+      if (batch.refresh.includes("graphList")) {
+        void storageRefreshRef.current.refreshGraphFiles();
+      }
+      if (batch.refresh.includes("runList")) {
+        void storageRefreshRef.current.refreshFeedTimeline();
+      }
+    });
+  }, [params.hasTauriRuntime]);
+
   useEffect(() => {
     params.setStatus("대기 중");
     return () => {
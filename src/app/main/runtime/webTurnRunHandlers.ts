@@ -1,3 +1,5 @@
+import type { TurnUsageScope } from "../types";
+
 export function createWebTurnRunHandlers(params: any) {
   async function saveRunRecord(runRecord: any) {
     const fileName = `run-${runRecord.runId}.json`;
@@ -80,7 +82,7 @@ export function createWebTurnRunHandlers(params: any) {
     });
   }
 
-  async function executeTurnNode(node: any, input: unknown) {
+  async function executeTurnNode(node: any, input: unknown, usageScope?: TurnUsageScope) {
     return params.executeTurnNodeWithContext(node, input, {
       model: params.model,
       cwd: params.cwd,
@@ -135,6 +137,7 @@ export function createWebTurnRunHandlers(params: any) {
       invokeFn: params.invokeFn,
       openUrlFn: params.openUrlFn,
       t: params.t,
+      usageScope,
     });
   }
 
@@ -24,6 +24,13 @@ export type ThreadStartResult = {
   raw: unknown;
 };
 
+// Attribution recorded with a turn's token usage in the workspace ledger.
+export type TurnUsageScope = {
+  graphId?: string;
+  runId?: string;
+  nodeId?: string;
+};
+
 export type UsageCheckResult = {
   sourceMethod: string;
   raw: unknown;
@@ -192,6 +199,9 @@ export type FeedAttachment = {
   truncated: boolean;
   charCount: number;
   filePath?: string;
+  artifactId?: string;
+  contentTruncated?: boolean;
+  contentBytes?: number;
 };
 
 export type FeedPostStatus = "draft" | "done" | "low_quality" | "failed" | "cancelled";
@@ -1,4 +1,7 @@
 import { t } from "../i18n";
+import { toErrorText } from "../shared/lib/valueUtils";
+
+export { toErrorText };
 
 export const WORKSPACE_CWD_STORAGE_KEY = "rail.settings.cwd";
 export const LOGIN_COMPLETED_STORAGE_KEY = "rail.settings.login_completed";
@@ -46,33 +49,6 @@ export function extractStringByPaths(value: unknown, paths: string[]): string |
   return null;
 }
 
-export function toErrorText(error: unknown): string {
-  if (error instanceof Error) {
-    return error.message;
-  }
-  if (typeof error === "string") {
-    return error;
-  }
-  if (error && typeof error === "object") {
-    const message = extractStringByPaths(error, [
-      "message",
-      "error",
-      "details",
-      "cause.message",
-      "data.message",
-    ]);
-    if (message) {
-      return message;
-    }
-    try {
-      return JSON.stringify(error);
-    } catch {
-      // fall through
-    }
-  }
-  return String(error);
-}
-
 export function extractAuthMode(input: unknown, depth = 0): AuthModeValue | null {
   if (depth > 6 || input == null) {
     return null;
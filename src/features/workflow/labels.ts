@@ -131,6 +131,15 @@ export function lifecycleStateLabel(state: string): string {
     parseError: t("lifecycle.parseError"),
     readError: t("lifecycle.readError"),
     stderrError: t("lifecycle.stderrError"),
+    restarting: t("lifecycle.restarting"),
+    restartError: t("lifecycle.restartError"),
+    restartFailed: t("lifecycle.restartFailed"),
+    recovered: t("lifecycle.recovered"),
+    threadResumed: t("lifecycle.threadResumed"),
+    threadResumeFailed: t("lifecycle.threadResumeFailed"),
+    replayMismatch: t("lifecycle.replayMismatch"),
+    replayFinished: t("lifecycle.replayFinished"),
+    replayError: t("lifecycle.replayError"),
   };
   return map[state] ?? state;
 }
@@ -500,6 +500,15 @@ export const EN_MESSAGES: Dictionary = {
     "lifecycle.parseError": "Parse error",
     "lifecycle.readError": "Read error",
     "lifecycle.stderrError": "stderr stream error",
+    "lifecycle.restarting": "Restarting",
+    "lifecycle.restartError": "Restart error",
+    "lifecycle.restartFailed": "Restart failed",
+    "lifecycle.recovered": "Recovered",
+    "lifecycle.threadResumed": "Thread resumed",
+    "lifecycle.threadResumeFailed": "Thread resume failed",
+    "lifecycle.replayMismatch": "Replay mismatch",
+    "lifecycle.replayFinished": "Replay finished",
+    "lifecycle.replayError": "Replay error",
     "usage.error.unsupported": "This engine version does not support the usage API. Engine run/login can still work, but usage cannot be fetched in this version.",
     "usage.error.unauthorized": "You do not have permission to query usage. Please sign in to Codex again.",
     "usage.error.timeout": "Usage query timed out. Please try again shortly.",
@@ -500,6 +500,15 @@ export const JA_MESSAGES: Dictionary = {
     "lifecycle.parseError": "解析エラー",
     "lifecycle.readError": "読み取りエラー",
     "lifecycle.stderrError": "標準エラーストリームエラー",
+    "lifecycle.restarting": "再起動中",
+    "lifecycle.restartError": "再起動エラー",
+    "lifecycle.restartFailed": "再起動失敗",
+    "lifecycle.recovered": "復旧済み",
+    "lifecycle.threadResumed": "スレッド復元済み",
+    "lifecycle.threadResumeFailed": "スレッド復元失敗",
+    "lifecycle.replayMismatch": "再生不一致",
+    "lifecycle.replayFinished": "再生完了",
+    "lifecycle.replayError": "再生エラー",
     "usage.error.unsupported": "このエンジンは使用量APIに対応していません。実行/ログインは正常でも、使用量は取得できません。",
     "usage.error.unauthorized": "使用量照会の権限がありません。Codexへ再ログインしてください。",
     "usage.error.timeout": "使用量照会がタイムアウトしました。しばらくしてから再試行してください。",
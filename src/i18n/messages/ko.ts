@@ -500,6 +500,15 @@ export const KO_MESSAGES: Dictionary = {
     "lifecycle.parseError": "파싱 오류",
     "lifecycle.readError": "읽기 오류",
     "lifecycle.stderrError": "표준오류 스트림 오류",
+    "lifecycle.restarting": "재시작 중",
+    "lifecycle.restartError": "재시작 오류",
+    "lifecycle.restartFailed": "재시작 실패",
+    "lifecycle.recovered": "복구됨",
+    "lifecycle.threadResumed": "스레드 복원됨",
+    "lifecycle.threadResumeFailed": "스레드 복원 실패",
+    "lifecycle.replayMismatch": "재생 불일치",
+    "lifecycle.replayFinished": "재생 완료",
+    "lifecycle.replayError": "재생 오류",
     "usage.error.unsupported": "사용량 조회 API를 지원하지 않는 엔진 버전입니다. 엔진 실행/로그인은 정상이어도 사용량은 현재 버전에서 조회할 수 없습니다.",
     "usage.error.unauthorized": "사용량 조회 권한이 없습니다. 코덱스 로그인을 다시 시도해주세요.",
     "usage.error.timeout": "사용량 조회가 시간 초과되었습니다. 잠시 후 다시 시도해주세요.",
@@ -500,6 +500,15 @@ export const ZH_MESSAGES: Dictionary = {
     "lifecycle.parseError": "解析错误",
     "lifecycle.readError": "读取错误",
     "lifecycle.stderrError": "标准错误流错误",
+    "lifecycle.restarting": "正在重启",
+    "lifecycle.restartError": "重启错误",
+    "lifecycle.restartFailed": "重启失败",
+    "lifecycle.recovered": "已恢复",
+    "lifecycle.threadResumed": "线程已恢复",
+    "lifecycle.threadResumeFailed": "线程恢复失败",
+    "lifecycle.replayMismatch": "回放不匹配",
+    "lifecycle.replayFinished": "回放完成",
+    "lifecycle.replayError": "回放错误",
     "usage.error.unsupported": "当前引擎版本不支持用量查询 API。即使引擎运行/登录正常，也无法查询用量。",
     "usage.error.unauthorized": "没有查询用量的权限。请重新登录 Codex。",
     "usage.error.timeout": "用量查询超时。请稍后重试。",
@@ -7,6 +7,7 @@ import {
   upsertKnowledgeEntry,
 } from "../../features/studio/knowledgeIndex";
 import type { KnowledgeEntry, KnowledgeSourcePost } from "../../features/studio/knowledgeTypes";
+import { toErrorText } from "../../shared/lib/valueUtils";
 import { invoke, revealItemInDir } from "../../shared/tauri";
 import {
   isHiddenKnowledgeEntry,
@@ -41,11 +42,11 @@ async function deleteIfExists(
     await action(normalized);
     return null;
   } catch (error) {
-    const message = String(error ?? "").toLowerCase();
+    const message = toErrorText(error).toLowerCase();
     if (message.includes("not found") || message.includes("enoent")) {
       return null;
     }
-    return `${failureLabel}: ${String(error)}`;
+    return `${failureLabel}: ${toErrorText(error)}`;
   }
 }
 
@@ -127,7 +128,7 @@ export function useKnowledgeBaseState({ cwd, posts }: UseKnowledgeBaseStateParam
             }
             setMarkdownContent(String(markdownText ?? ""));
           } catch (error) {
-            errors.push(`Markdown 읽기 실패: ${String(error)}`);
+            errors.push(`Markdown 읽기 실패: ${toErrorText(error)}`);
             setMarkdownContent("");
           }
         } else {
@@ -144,7 +145,7 @@ export function useKnowledgeBaseState({ cwd, posts }: UseKnowledgeBaseStateParam
             }
             setJsonContent(String(jsonText ?? ""));
           } catch (error) {
-            errors.push(`JSON 읽기 실패: ${String(error)}`);
+            errors.push(`JSON 읽기 실패: ${toErrorText(error)}`);
             setJsonContent("");
           }
         } else {
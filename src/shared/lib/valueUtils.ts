@@ -39,6 +39,33 @@ export function extractStringByPaths(value: unknown, paths: string[]): string |
   return null;
 }
 
+export function toErrorText(error: unknown): string {
+  if (error instanceof Error) {
+    return error.message;
+  }
+  if (typeof error === "string") {
+    return error;
+  }
+  if (error && typeof error === "object") {
+    const message = extractStringByPaths(error, [
+      "message",
+      "error",
+      "details",
+      "cause.message",
+      "data.message",
+    ]);
+    if (message) {
+      return message;
+    }
+    try {
+      return JSON.stringify(error);
+    } catch {
+      // fall through
+    }
+  }
+  return String(error);
+}
+
 export function readNumber(value: unknown): number | undefined {
   if (typeof value === "number" && Number.isFinite(value)) {
     return value;